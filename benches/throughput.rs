@@ -0,0 +1,256 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Number of jobs submitted per iteration of the throughput benchmarks.
+const JOBS: usize = 10_000;
+
+/// Number of threads submitting jobs at the same time under contention.
+const PRODUCERS: usize = 8;
+
+/// Submit `JOBS` trivial jobs and wait for all of them, checking that every
+/// one of them ran.
+fn run_jobs(pool: &ThreadPool) {
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..JOBS {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::Relaxed), JOBS);
+}
+
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+
+    group.throughput(Throughput::Elements(JOBS as u64));
+
+    for threads in [1, 4, 8, 16] {
+        let pool = ThreadPool::new(threads);
+
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &pool, |b, pool| {
+            b.iter(|| run_jobs(pool));
+        });
+    }
+
+    group.finish();
+}
+
+/// Time from `execute` returning to the job starting on a Worker. Criterion
+/// reports the median and spread of these samples; the p99 shows up as the
+/// upper end of the distribution in its report.
+fn latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("latency");
+
+    for threads in [1, 4] {
+        let pool = ThreadPool::new(threads);
+
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &pool, |b, pool| {
+            b.iter_custom(|iters| {
+                let (tx, rx) = mpsc::channel();
+                let mut total = Duration::ZERO;
+
+                for _ in 0..iters {
+                    let tx = tx.clone();
+                    let submitted = Instant::now();
+
+                    pool.execute(move || tx.send(submitted.elapsed()).unwrap())
+                        .unwrap();
+
+                    total += rx.recv().unwrap();
+                }
+
+                total
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// The `throughput` workload with different `worker_batch_size`s, the
+/// default included.
+fn batch_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_size");
+
+    group.throughput(Throughput::Elements(JOBS as u64));
+
+    for batch_size in [None, Some(1), Some(8), Some(128)] {
+        let mut builder = ThreadPoolBuilder::new().num_threads(4);
+
+        if let Some(n) = batch_size {
+            builder = builder.worker_batch_size(n);
+        }
+
+        let pool = builder.build().unwrap();
+        let id = batch_size.map_or("default".to_string(), |n| n.to_string());
+
+        group.bench_with_input(BenchmarkId::from_parameter(id), &pool, |b, pool| {
+            b.iter(|| run_jobs(pool));
+        });
+    }
+
+    group.finish();
+}
+
+/// Submitting `JOBS` trivial jobs with `execute` and with
+/// `execute_unchecked`.
+fn unchecked(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unchecked");
+    let pool = ThreadPool::new(4);
+
+    group.throughput(Throughput::Elements(JOBS as u64));
+    group.bench_function("execute", |b| {
+        b.iter(|| {
+            let done = Arc::new(AtomicUsize::new(0));
+
+            for _ in 0..JOBS {
+                let done = Arc::clone(&done);
+
+                pool.execute(move || {
+                    done.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            }
+
+            pool.join();
+        });
+    });
+    group.bench_function("execute_unchecked", |b| {
+        b.iter(|| {
+            let done = Arc::new(AtomicUsize::new(0));
+
+            for _ in 0..JOBS {
+                let done = Arc::clone(&done);
+
+                pool.execute_unchecked(move || {
+                    done.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+
+            // `join` doesn't wait for unchecked jobs.
+            while done.load(Ordering::Relaxed) < JOBS {
+                thread::yield_now();
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contention");
+    let pool = ThreadPool::new(8);
+
+    group.throughput(Throughput::Elements((JOBS * PRODUCERS) as u64));
+    group.bench_function(BenchmarkId::from_parameter(PRODUCERS), |b| {
+        b.iter(|| {
+            let done = Arc::new(AtomicUsize::new(0));
+
+            thread::scope(|s| {
+                for _ in 0..PRODUCERS {
+                    s.spawn(|| {
+                        for _ in 0..JOBS {
+                            let done = Arc::clone(&done);
+
+                            pool.execute(move || {
+                                done.fetch_add(1, Ordering::Relaxed);
+                            })
+                            .unwrap();
+                        }
+                    });
+                }
+            });
+
+            pool.join();
+
+            assert_eq!(done.load(Ordering::Relaxed), JOBS * PRODUCERS);
+        });
+    });
+
+    group.finish();
+}
+
+/// `PRODUCERS` threads each spawn `JOBS` jobs and join their handles, which
+/// sends every result back over the result channel. Run once without and
+/// once with `--features crossbeam` to compare the `std::sync::mpsc` and
+/// `crossbeam-channel` backends; the benchmark is named after the one in use.
+fn results(c: &mut Criterion) {
+    let mut group = c.benchmark_group("results");
+    let pool = ThreadPool::new(8);
+    let backend = match cfg!(feature = "crossbeam") {
+        true => "crossbeam",
+        false => "std",
+    };
+
+    group.throughput(Throughput::Elements((JOBS * PRODUCERS) as u64));
+    group.bench_function(BenchmarkId::new(backend, PRODUCERS), |b| {
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..PRODUCERS {
+                    s.spawn(|| {
+                        let handles: Vec<_> = (0..JOBS)
+                            .map(|i| pool.spawn(move || black_box(i)).unwrap())
+                            .collect();
+
+                        for (i, handle) in handles.into_iter().enumerate() {
+                            assert_eq!(handle.join().unwrap(), i);
+                        }
+                    });
+                }
+            });
+        });
+    });
+
+    group.finish();
+}
+
+/// The same workload as `throughput` on `rayon::ThreadPool`, for comparison.
+fn rayon(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rayon");
+
+    group.throughput(Throughput::Elements(JOBS as u64));
+
+    for threads in [1, 4, 8, 16] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &pool, |b, pool| {
+            b.iter(|| {
+                let done = AtomicUsize::new(0);
+
+                pool.scope(|s| {
+                    for _ in 0..JOBS {
+                        s.spawn(|_| {
+                            black_box(done.fetch_add(1, Ordering::Relaxed));
+                        });
+                    }
+                });
+
+                assert_eq!(done.load(Ordering::Relaxed), JOBS);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, throughput, latency, batch_size, unchecked, contention, results, rayon);
+criterion_main!(benches);
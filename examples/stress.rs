@@ -0,0 +1,91 @@
+//! Floods a ThreadPool with jobs and reports how it kept up, for checking
+//! the pool's behavior on a particular machine.
+//!
+//! cargo run --release --example stress -- --threads 8 --jobs 1000000
+
+use clap::Parser;
+use mtserver::ThreadPool;
+use std::{
+    hint, thread,
+    time::{Duration, Instant},
+};
+
+/// Floods a ThreadPool with jobs and reports throughput and queue waits.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The number of Workers.
+    #[arg(long, default_value_t = mtserver::worker_count_for_parallelism(1.0))]
+    threads: usize,
+
+    /// The total number of jobs, split between the producers.
+    #[arg(long, default_value_t = 100_000)]
+    jobs: usize,
+
+    /// How long each job spins for, in microseconds.
+    #[arg(long, default_value_t = 0)]
+    job_duration_us: u64,
+
+    /// The number of threads submitting jobs at once.
+    #[arg(long, default_value_t = 1)]
+    producers: usize,
+}
+
+/// Keep the CPU busy for `duration`, like a job doing real work.
+fn spin(duration: Duration) {
+    let started = Instant::now();
+
+    while started.elapsed() < duration {
+        hint::spin_loop();
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let pool = ThreadPool::new(args.threads);
+    let producers = args.producers.max(1);
+    let job_duration = Duration::from_micros(args.job_duration_us);
+    let started = Instant::now();
+
+    thread::scope(|s| {
+        for producer in 0..producers {
+            // The first producers take the remainder.
+            let jobs = args.jobs / producers + usize::from(producer < args.jobs % producers);
+            let pool = &pool;
+
+            s.spawn(move || {
+                for _ in 0..jobs {
+                    pool.execute(move || spin(job_duration))
+                        .expect("Unable to submit a job");
+                }
+            });
+        }
+    });
+
+    pool.join();
+
+    let elapsed = started.elapsed();
+    let waits = pool.queue_wait_histogram();
+    let snapshot = pool.snapshot();
+    let busy = Duration::from_nanos(snapshot.per_worker_busy_ns.iter().sum());
+    let utilization = busy.as_secs_f64() / (elapsed.as_secs_f64() * args.threads as f64);
+    let quantile = |q| {
+        waits
+            .quantile(q)
+            .map_or("-".to_string(), |wait| format!("<= {wait:?}"))
+    };
+
+    println!("{:<22}{}", "threads", args.threads);
+    println!("{:<22}{}", "producers", producers);
+    println!("{:<22}{}", "jobs", args.jobs);
+    println!("{:<22}{}us", "job duration", args.job_duration_us);
+    println!("{:<22}{elapsed:.2?}", "elapsed");
+    println!(
+        "{:<22}{:.0} jobs/s",
+        "throughput",
+        args.jobs as f64 / elapsed.as_secs_f64()
+    );
+    println!("{:<22}{}", "queue wait p50", quantile(0.5));
+    println!("{:<22}{}", "queue wait p99", quantile(0.99));
+    println!("{:<22}{}", "queue wait p999", quantile(0.999));
+    println!("{:<22}{:.1}%", "worker utilization", utilization * 100.0);
+}
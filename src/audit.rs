@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// How a job recorded in the audit log ended.
+///
+/// # Variants
+///
+/// - `Completed` The job returned normally.
+/// - `Panicked` The job panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Outcome {
+    Completed,
+    Panicked,
+}
+
+/// AuditEntry struct
+///
+/// A job that ran on a ThreadPool built with
+/// `ThreadPoolBuilder::with_audit_log`, as returned by
+/// `ThreadPool::audit_log`.
+///
+/// # Members
+///
+/// - `job_id` Counts up from 0 in the order the jobs started.
+/// - `worker_id` The id of the Worker that ran the job.
+/// - `started_at` When the job started.
+/// - `finished_at` When the job returned or panicked.
+/// - `outcome` Whether the job completed or panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AuditEntry {
+    pub job_id: u64,
+    pub worker_id: usize,
+    pub started_at: SystemTime,
+    pub finished_at: SystemTime,
+    pub outcome: Outcome,
+}
+
+/// AuditLog struct
+///
+/// A ring buffer of the last jobs a ThreadPool ran, shared with its Workers.
+///
+/// # Members
+///
+/// - `next_job_id` The id given to the next job that starts
+/// - `entries` The entries, which wrap around once there are `capacity` of
+///   them, and where the next one goes
+/// - `capacity` The number of entries kept
+#[derive(Debug)]
+pub(crate) struct AuditLog {
+    next_job_id: AtomicU64,
+    entries: Mutex<(Vec<AuditEntry>, usize)>,
+    capacity: usize,
+}
+
+impl AuditLog {
+    /// Create an empty AuditLog.
+    ///
+    /// # Arguments
+    ///
+    /// - `capacity` is the number of entries kept. It is raised to 1 if it
+    ///   is 0.
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            next_job_id: AtomicU64::new(0),
+            entries: Mutex::new((Vec::with_capacity(capacity), 0)),
+            capacity,
+        }
+    }
+
+    /// The number of entries kept.
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Give a job that is starting its id.
+    ///
+    /// # Return
+    ///
+    /// The job's id and when it started, to pass to `finish`.
+    pub(crate) fn start(&self) -> (u64, SystemTime) {
+        (
+            self.next_job_id.fetch_add(1, Ordering::Relaxed),
+            SystemTime::now(),
+        )
+    }
+
+    /// Record a job that has finished, overwriting the oldest entry if the
+    /// AuditLog is full.
+    ///
+    /// # Arguments
+    ///
+    /// - `(job_id, started_at)` are what `start` returned for the job.
+    /// - `worker_id` is the id of the Worker that ran the job.
+    /// - `run_duration` is how long the job ran, which saves reading the
+    ///   clock again.
+    /// - `outcome` is how the job ended.
+    pub(crate) fn finish(
+        &self,
+        (job_id, started_at): (u64, SystemTime),
+        worker_id: usize,
+        run_duration: Duration,
+        outcome: Outcome,
+    ) {
+        let entry = AuditEntry {
+            job_id,
+            worker_id,
+            started_at,
+            finished_at: started_at + run_duration,
+            outcome,
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let (entries, next) = &mut *entries;
+
+        if entries.len() < self.capacity {
+            entries.push(entry);
+        } else {
+            entries[*next] = entry;
+        }
+
+        *next = (*next + 1) % self.capacity;
+    }
+
+    /// The entries, oldest first.
+    pub(crate) fn snapshot(&self) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let (entries, next) = &*entries;
+
+        if entries.len() < self.capacity {
+            return entries.clone();
+        }
+
+        let mut snapshot = Vec::with_capacity(self.capacity);
+
+        snapshot.extend_from_slice(&entries[*next..]);
+        snapshot.extend_from_slice(&entries[..*next]);
+        snapshot
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::{Condvar, Mutex, PoisonError};
+
+/// PoolBarrier struct
+///
+/// A rendezvous point for a fixed number of jobs, for splitting work into
+/// phases that must all finish before the next one starts. Share it between
+/// the jobs in an `Arc`, and each job blocks in `wait` until all of them
+/// have called it. The PoolBarrier then resets itself, so the same jobs can
+/// use it again for the next phase.
+///
+/// Every job waiting in `wait` holds on to its Worker, so the ThreadPool
+/// needs at least as many Workers as the PoolBarrier has parties, with
+/// nothing else keeping them busy. Otherwise the last jobs never get a
+/// Worker and the others wait forever.
+///
+/// # Members
+///
+/// - `parties` The number of callers `wait` waits for
+/// - `state` How many callers have arrived in the current phase
+/// - `released` Notified when the last caller of a phase arrives
+#[derive(Debug)]
+pub struct PoolBarrier {
+    parties: usize,
+    state: Mutex<Phase>,
+    released: Condvar,
+}
+
+/// Phase struct
+///
+/// # Members
+///
+/// - `arrived` The number of callers waiting in the current phase
+/// - `generation` Counts the phases, so a caller can tell its own phase
+///   ended rather than being woken spuriously
+#[derive(Debug)]
+struct Phase {
+    arrived: usize,
+    generation: u64,
+}
+
+impl PoolBarrier {
+    /// Create a new PoolBarrier for `n` callers.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the number of callers of `wait` to wait for. It is raised
+    ///   to 1 if it is 0.
+    pub fn new(n: usize) -> Self {
+        Self {
+            parties: n.max(1),
+            state: Mutex::new(Phase {
+                arrived: 0,
+                generation: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Block the current thread until `n` callers, this one included, have
+    /// called `wait` in the current phase, then start the next phase.
+    ///
+    /// # Return
+    ///
+    /// Whether this caller was the last to arrive, e.g. to have exactly one
+    /// job do some work between phases.
+    pub fn wait(&self) -> bool {
+        let mut phase = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        phase.arrived += 1;
+
+        if phase.arrived == self.parties {
+            phase.arrived = 0;
+            phase.generation += 1;
+            self.released.notify_all();
+
+            return true;
+        }
+
+        let generation = phase.generation;
+
+        drop(
+            self.released
+                .wait_while(phase, |phase| phase.generation == generation)
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+
+        false
+    }
+
+    /// The number of callers `wait` waits for.
+    pub fn parties(&self) -> usize {
+        self.parties
+    }
+}
@@ -0,0 +1,885 @@
+#[cfg(feature = "metrics")]
+use crate::metrics::PoolCollector;
+use crate::{
+    audit::AuditLog, logging::log_warn, panic_hook, Counters, Hooks, Inner, Job, JobMetrics,
+    JobQueue, JobRegistry, PanicError, Pending, ShedPolicy, ThreadPool, ThreadPoolConfig,
+    ThreadPoolError, TraceContextPropagator, WorkerConfig,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        mpsc::SyncSender,
+        Arc, Mutex, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Number of threads used when the available parallelism can't be determined.
+const FALLBACK_NUM_THREADS: usize = 4;
+
+/// Prefix used for worker thread names when none is set.
+const DEFAULT_THREAD_NAME_PREFIX: &str = "worker";
+
+/// Number of times a crashed Worker is restarted when no limit is set.
+const DEFAULT_MAX_RESTARTS: usize = 3;
+
+/// Number of Workers that are never evicted for being idle.
+const DEFAULT_MIN_THREADS: usize = 1;
+
+/// A number of Workers in proportion to the number of logical CPUs.
+///
+/// How many Workers a ThreadPool needs depends on how much of its time a job
+/// spends waiting rather than computing:
+///
+/// - `1.0` for CPU-bound jobs, one Worker per CPU, which is the default.
+/// - Less than `1.0`, e.g. `0.5`, to leave CPUs free for other work in the
+///   process, such as an async runtime or a second ThreadPool.
+/// - More than `1.0`, e.g. `2.0` or more, for jobs that block on I/O, so
+///   there are still Workers computing while others wait.
+///
+/// Too few Workers leave CPUs idle, and too many spend time switching
+/// between threads and memory on their stacks.
+///
+/// # Arguments
+///
+/// - `factor` is the number of Workers per CPU.
+///
+/// # Return
+///
+/// The number of CPUs times `factor`, rounded, and at least 1. The number of
+/// CPUs is taken to be 4 if it can't be determined.
+pub fn worker_count_for_parallelism(factor: f32) -> usize {
+    let cpus = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(FALLBACK_NUM_THREADS);
+    let count = (cpus as f32 * factor).round();
+
+    // Also catches a NaN factor.
+    if count >= 1.0 {
+        count as usize
+    } else {
+        1
+    }
+}
+
+/// ThreadPoolBuilder struct
+///
+/// Configures and creates a ThreadPool. Every setting has a default, so only
+/// the settings that matter to the caller need to be set.
+///
+/// # Members
+///
+/// - `num_threads` The number of Workers to spawn. Defaults to the available
+///   parallelism of the machine, or 4 if that can't be determined.
+/// - `thread_name_prefix` The prefix of each worker thread's name. Threads
+///   are named `{prefix}-{id}`. Defaults to `worker`.
+/// - `stack_size` The stack size, in bytes, of each worker thread. Defaults
+///   to `None`, which uses the OS default.
+/// - `channel_capacity` The maximum number of jobs waiting in the queue.
+///   Defaults to `None`, which means the queue is unbounded.
+/// - `shed_policy` What to do with new jobs once the queue is full. Defaults
+///   to `ShedPolicy::RejectNew`.
+/// - `worker_batch_size` The most jobs a Worker takes off the queue at once.
+///   Defaults to `None`, which leaves it to crossbeam.
+/// - `hold_batches` Whether a Worker runs its whole batch before it looks at
+///   the queue again, set by `channel_batch_size`. Defaults to `false`.
+/// - `max_restarts` The number of times a crashed Worker is restarted before
+///   it gives up. Defaults to 3.
+/// - `lazy` Whether Workers are only spawned once there are jobs for them.
+///   Defaults to `false`.
+/// - `keep_alive` How long an idle Worker waits for a job before exiting.
+///   Defaults to `None`, which means Workers never exit for being idle.
+/// - `min_threads` The number of Workers that are always alive. Defaults to
+///   `None`, which spawns every Worker up front unless `lazy` is set and
+///   keeps at least one Worker alive when evicting.
+/// - `max_consecutive_jobs` How many jobs in a row from the same thread a
+///   Worker runs before yielding. Defaults to `None`, which never yields.
+/// - `autoscale` How often to resize the ThreadPool to the available
+///   parallelism. Defaults to `None`, which never does.
+/// - `hooks` The callbacks run by the Workers. Defaults to none.
+/// - `panic_hook` Whether to install the panic hook that names the Worker
+///   and job a panic came from. Defaults to `false`.
+/// - `audit_log` The number of jobs to keep in the audit log. Defaults to
+///   `None`, which keeps no audit log.
+/// - `cores` The ids of the CPU cores to pin the Workers to, with the
+///   `affinity` feature. Defaults to empty, which doesn't pin them.
+/// - `metrics` The Prometheus registry to export the ThreadPool's metrics to
+///   and the name to label them with, with the `metrics` feature. Defaults
+///   to `None`.
+#[derive(Debug, Clone)]
+pub struct ThreadPoolBuilder {
+    num_threads: usize,
+    thread_name_prefix: String,
+    stack_size: Option<usize>,
+    channel_capacity: Option<usize>,
+    shed_policy: ShedPolicy,
+    worker_batch_size: Option<usize>,
+    hold_batches: bool,
+    max_restarts: usize,
+    lazy: bool,
+    keep_alive: Option<Duration>,
+    min_threads: Option<usize>,
+    max_consecutive_jobs: Option<usize>,
+    autoscale: Option<Duration>,
+    hooks: Hooks,
+    panic_hook: bool,
+    audit_log: Option<usize>,
+    #[cfg(feature = "affinity")]
+    cores: Vec<usize>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<(prometheus::Registry, String)>,
+}
+
+impl ThreadPoolBuilder {
+    /// Create a new ThreadPoolBuilder with the default settings.
+    pub fn new() -> Self {
+        Self {
+            num_threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(FALLBACK_NUM_THREADS),
+            thread_name_prefix: DEFAULT_THREAD_NAME_PREFIX.to_string(),
+            stack_size: None,
+            channel_capacity: None,
+            shed_policy: ShedPolicy::RejectNew,
+            worker_batch_size: None,
+            hold_batches: false,
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            lazy: false,
+            keep_alive: None,
+            min_threads: None,
+            max_consecutive_jobs: None,
+            autoscale: None,
+            hooks: Hooks::default(),
+            panic_hook: false,
+            audit_log: None,
+            #[cfg(feature = "affinity")]
+            cores: Vec::new(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Create a new ThreadPoolBuilder from a ThreadPoolConfig.
+    ///
+    /// Settings the ThreadPoolConfig leaves as `None` keep their defaults,
+    /// and any setting can still be overridden afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// - `config` is the configuration to apply.
+    pub fn from_config(config: ThreadPoolConfig) -> Self {
+        let mut builder = Self::new();
+
+        if let Some(num_threads) = config.num_threads {
+            builder = builder.num_threads(num_threads);
+        }
+
+        if let Some(prefix) = config.thread_name_prefix {
+            builder = builder.thread_name_prefix(prefix);
+        }
+
+        if let Some(bytes) = config.stack_size {
+            builder = builder.stack_size(bytes);
+        }
+
+        if let Some(capacity) = config.channel_capacity {
+            builder = builder.channel_capacity(capacity);
+        }
+
+        if let Some(max_restarts) = config.max_restarts {
+            builder = builder.max_restarts(max_restarts);
+        }
+
+        if config.lazy {
+            builder = builder.lazy_spawn();
+        }
+
+        if let Some(ms) = config.keep_alive_ms {
+            builder = builder.keep_alive(Duration::from_millis(ms));
+        }
+
+        if let Some(min_threads) = config.min_threads {
+            builder = builder.min_threads(min_threads);
+        }
+
+        builder
+    }
+
+    /// Set the number of threads in the pool.
+    ///
+    /// # Arguments
+    ///
+    /// - `num_threads` is the number of Workers to spawn.
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = num_threads;
+        self
+    }
+
+    /// Set the number of threads in proportion to the number of logical
+    /// CPUs. See `worker_count_for_parallelism` for how to pick `factor`.
+    ///
+    /// # Arguments
+    ///
+    /// - `factor` is the number of Workers per CPU.
+    pub fn num_threads_by_factor(self, factor: f32) -> Self {
+        self.num_threads(worker_count_for_parallelism(factor))
+    }
+
+    /// Set the prefix used to name the worker threads.
+    ///
+    /// Named threads make it possible to tell Workers apart in profilers,
+    /// debuggers, and panic messages.
+    ///
+    /// # Arguments
+    ///
+    /// - `prefix` is prepended to each Worker's id, i.e. `{prefix}-{id}`.
+    pub fn thread_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.thread_name_prefix = prefix.into();
+        self
+    }
+
+    /// Set the stack size of the worker threads.
+    ///
+    /// Useful for jobs with deep recursion or large stack-allocated buffers
+    /// that would overflow the default stack.
+    ///
+    /// # Arguments
+    ///
+    /// - `bytes` is the stack size of each worker thread in bytes.
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    /// Set the maximum number of jobs that can wait in the queue.
+    ///
+    /// A bounded queue provides backpressure: once it is full, `execute`
+    /// rejects new jobs with `ExecuteError::QueueFull` and `execute_blocking`
+    /// waits until a Worker frees up space.
+    ///
+    /// # Arguments
+    ///
+    /// - `capacity` is the number of jobs the queue can hold. A capacity of
+    ///   0 makes the queue unbounded.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = (capacity > 0).then_some(capacity);
+        self
+    }
+
+    /// Set the maximum number of jobs that can wait in the queue before
+    /// jobs are shed.
+    ///
+    /// This is the same limit as `channel_capacity`, under the name that
+    /// goes with `shed_policy`.
+    ///
+    /// # Arguments
+    ///
+    /// - `depth` is the number of jobs the queue can hold. A depth of 0
+    ///   makes the queue unbounded, so no jobs are ever shed.
+    pub fn max_queue_depth(self, depth: usize) -> Self {
+        self.channel_capacity(depth)
+    }
+
+    /// Set what happens to new jobs once the queue is full.
+    ///
+    /// Shedding jobs keeps the queue short under overload, so the jobs that
+    /// are accepted don't wait behind a growing backlog. Under
+    /// `ShedPolicy::DropNewest` and `ShedPolicy::DropOldest`, submitting a
+    /// job never fails or waits because the queue is full, even with
+    /// `execute_blocking`; see `ThreadPool::shed_count` for how many jobs
+    /// were dropped. Has no effect on an unbounded queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `policy` is the ShedPolicy to apply.
+    pub fn shed_policy(mut self, policy: ShedPolicy) -> Self {
+        self.shed_policy = policy;
+        self
+    }
+
+    /// Set the most jobs a Worker takes off the shared queue at once.
+    ///
+    /// A Worker that finds its own queue empty moves a batch of jobs from
+    /// the shared queue to it in one go, then runs them one by one without
+    /// touching the shared queue again. Bigger batches mean less contention
+    /// with tiny jobs; smaller ones spread slow jobs more evenly, although
+    /// idle Workers can still steal jobs that are waiting in a batch. By
+    /// default crossbeam picks the size: half of the waiting jobs, up to 32.
+    ///
+    /// A batch doesn't make a Worker any slower to notice that the
+    /// ThreadPool is paused, shrunk or shut down, since it looks for those
+    /// before each job, nor does it hold up jobs of a higher priority than
+    /// the batch. With a bounded queue, the jobs in a batch still count
+    /// towards its capacity until they start, so batching doesn't make room
+    /// for a burst any sooner; a size of 1 takes one job at a time. Use
+    /// `channel_batch_size` to trade that responsiveness for throughput.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the most jobs per batch. It is raised to 1 if it is 0.
+    pub fn worker_batch_size(mut self, n: usize) -> Self {
+        self.worker_batch_size = Some(n.max(1));
+        self
+    }
+
+    /// Make each Worker take up to `n` jobs off the shared queue at once and
+    /// run all of them before it looks at the queue again.
+    ///
+    /// Unlike `worker_batch_size`, the jobs in a batch stop counting towards
+    /// a bounded queue's capacity as soon as they are taken, so a burst that
+    /// fills a small queue drains up to `n` jobs per Worker at a time and
+    /// blocked or rejected `execute` calls get room sooner. Neither do they
+    /// count in `queued_count`. A Worker also checks for messages only once
+    /// per batch rather than once per job.
+    ///
+    /// The tradeoff is responsiveness. A Worker finishes its batch before it
+    /// notices that the ThreadPool has been paused, shrunk or shut down, and
+    /// before it runs a job of a higher priority or one sent to it alone.
+    /// `shutdown_now` doesn't hand back the jobs in a batch; their Worker
+    /// runs them before it exits. Idle Workers can't steal them either. A
+    /// size of 1 restores the per-job checks of `worker_batch_size(1)`.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the most jobs per batch. It is raised to 1 if it is 0.
+    pub fn channel_batch_size(mut self, n: usize) -> Self {
+        self.worker_batch_size = Some(n.max(1));
+        self.hold_batches = true;
+        self
+    }
+
+    /// Make each Worker yield its thread after running `n` jobs in a row
+    /// that were submitted from the same thread.
+    ///
+    /// When one thread submits a burst of jobs, the Workers can keep every
+    /// core busy with it for as long as it lasts, starving the threads that
+    /// submit everything else, and any other work on the same cores, of CPU
+    /// time. Yielding now and then lets the OS schedule those threads in
+    /// between. It is a fairness nudge rather than a limit: the queue stays
+    /// in order, and a Worker carries on with the burst as soon as it is
+    /// scheduled again. Defaults to never yielding.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the number of jobs in a row. It is raised to 1 if it is 0.
+    pub fn max_consecutive_jobs(mut self, n: usize) -> Self {
+        self.max_consecutive_jobs = Some(n.max(1));
+        self
+    }
+
+    /// Resize the ThreadPool to the available parallelism every `interval`.
+    ///
+    /// The number of CPUs a process may use can change while it runs, e.g.
+    /// when the CPU quota of a container's cgroup is changed. A thread named
+    /// `{prefix}-autoscale` calls `ThreadPool::resize_to_parallelism` every
+    /// `interval` to follow it, replacing the number of Workers set with
+    /// `num_threads`. It exits once the ThreadPool has been shut down or
+    /// dropped, which it notices at the next interval. Handles to the
+    /// ThreadPool that are dropped while it resizes wait for it to finish,
+    /// so the last one still shuts the Workers down on its own thread.
+    /// Defaults to never resizing.
+    ///
+    /// # Arguments
+    ///
+    /// - `interval` is how long to wait between resizes.
+    pub fn autoscale(mut self, interval: Duration) -> Self {
+        self.autoscale = Some(interval);
+        self
+    }
+
+    /// Set the number of times a crashed Worker is restarted.
+    ///
+    /// A panic inside a job never crashes a Worker. This limit only applies
+    /// to panics in the Worker's own loop, such as a panicking
+    /// `on_job_complete` callback.
+    ///
+    /// # Arguments
+    ///
+    /// - `max_restarts` is the number of restarts before the Worker gives up.
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Spawn Workers on demand instead of all at once when the ThreadPool is
+    /// built.
+    ///
+    /// A new Worker is spawned when a job is submitted while every spawned
+    /// Worker is busy, until the configured number of threads is reached.
+    /// This avoids paying for threads in pools that sit idle.
+    pub fn lazy_spawn(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+
+    /// Set how long an idle Worker waits for a job before it exits.
+    ///
+    /// Workers evicted this way are spawned again on demand when jobs arrive
+    /// and every live Worker is busy, so threads started during a traffic
+    /// spike don't linger afterwards. At least one Worker is always kept
+    /// alive.
+    ///
+    /// # Arguments
+    ///
+    /// - `keep_alive` is the maximum time a Worker may sit idle.
+    pub fn keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Set the number of Workers that are always alive.
+    ///
+    /// Only `min_threads` Workers are spawned when the ThreadPool is built.
+    /// When a job arrives and every live Worker is busy, another Worker is
+    /// spawned, up to `max_threads`. Combined with `keep_alive`, Workers
+    /// above the minimum exit again once they have been idle for too long.
+    ///
+    /// # Arguments
+    ///
+    /// - `min_threads` is the number of Workers that never exit for being
+    ///   idle.
+    pub fn min_threads(mut self, min_threads: usize) -> Self {
+        self.min_threads = Some(min_threads);
+        self
+    }
+
+    /// Set the maximum number of threads in the pool.
+    ///
+    /// This is the same setting as `num_threads`, named to pair with
+    /// `min_threads` when building an elastic pool.
+    ///
+    /// # Arguments
+    ///
+    /// - `max_threads` is the number of Workers the pool can burst to.
+    pub fn max_threads(self, max_threads: usize) -> Self {
+        self.num_threads(max_threads)
+    }
+
+    /// Set a callback that is called after every job finishes.
+    ///
+    /// The callback runs on the Worker that executed the job and is given
+    /// how long the job waited in the queue and how long it ran, which is
+    /// enough to track latency percentiles. It is called even if the job
+    /// panicked, but only once the job is no longer pending, so it may still
+    /// be running when `ThreadPool::join` returns. A panic in the callback
+    /// crashes the Worker.
+    ///
+    /// # Arguments
+    ///
+    /// - `callback` is called with the JobMetrics of each finished job.
+    pub fn on_job_complete(
+        mut self,
+        callback: impl Fn(JobMetrics) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.on_job_complete = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set a callback that is called whenever a job panics.
+    ///
+    /// Panics never kill a Worker; the Worker calls this callback and then
+    /// carries on with the next job. Jobs submitted with `spawn`, `map`,
+    /// `broadcast`, or through a Scope hand their panic to the caller
+    /// instead, so this callback isn't called for them.
+    ///
+    /// # Arguments
+    ///
+    /// - `callback` is called with the id of the Worker the job panicked on
+    ///   and the panic itself.
+    pub fn on_job_panic(
+        mut self,
+        callback: impl Fn(usize, &PanicError) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.on_job_panic = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set a callback that is called on every worker thread when it starts.
+    ///
+    /// The callback runs on the new thread before it executes any jobs, which
+    /// makes it the place to set up per-thread resources. It also runs for
+    /// Workers spawned later, e.g. by `resize` or a lazily spawned ThreadPool.
+    ///
+    /// # Arguments
+    ///
+    /// - `callback` is called with the id of the Worker that started.
+    pub fn on_thread_start(mut self, callback: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        self.hooks.on_thread_start = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set a callback that initializes the thread-locals of every worker
+    /// thread.
+    ///
+    /// The callback runs once on each new thread, after it has been named and
+    /// pinned to a core and before the `on_thread_start` hook and any jobs.
+    /// It takes no arguments and is meant to fill `thread_local!` statics,
+    /// e.g. with a connection for the jobs on that thread to reuse. Since a
+    /// Worker whose loop is restarted keeps its thread, its thread-locals
+    /// survive the restart and the callback isn't called again.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is called on each worker thread as it starts.
+    pub fn thread_local_init(mut self, f: impl Fn() + Send + Sync + 'static) -> Self {
+        self.hooks.thread_local_init = Some(Arc::new(f));
+        self
+    }
+
+    /// Set the function that creates the thread::Builder each Worker's
+    /// thread is spawned with, for settings the ThreadPoolBuilder has no
+    /// method for.
+    ///
+    /// The `thread_name_prefix` and `stack_size` settings are ignored once
+    /// this is set, so the thread::Builder should set them itself. Anything
+    /// that has to be done on the new thread, such as changing its
+    /// scheduling policy, belongs in `on_thread_start` instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is called with the id of each Worker as it is spawned.
+    pub fn thread_builder(
+        mut self,
+        f: impl Fn(usize) -> thread::Builder + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.thread_builder = Some(Arc::new(f));
+        self
+    }
+
+    /// Set a callback that is called on every worker thread just before it
+    /// exits.
+    ///
+    /// The callback runs on the exiting thread after its last job, whether
+    /// the Worker is being shut down, retired by `resize`, or evicted for
+    /// being idle.
+    ///
+    /// # Arguments
+    ///
+    /// - `callback` is called with the id of the Worker that is stopping.
+    pub fn on_thread_stop(mut self, callback: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        self.hooks.on_thread_stop = Some(Arc::new(callback));
+        self
+    }
+
+    /// Carry the trace context of the thread that submits each job over to
+    /// the Worker that runs it, so work done in the job shows up in the
+    /// same distributed trace, e.g. the HTTP request it is handling.
+    ///
+    /// The context is extracted on the submitting thread whenever a job is
+    /// submitted, and injected on the Worker just before the job runs. Any
+    /// scheme can be plugged in, e.g. OpenTelemetry's `Context::current` and
+    /// `Context::attach`:
+    ///
+    /// ```
+    /// # use mtserver::ThreadPoolBuilder;
+    /// thread_local! {
+    ///     static TRACE_ID: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    /// }
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(1)
+    ///     .with_trace_propagator((|| TRACE_ID.get(), |id| TRACE_ID.set(id)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// TRACE_ID.set(42);
+    /// assert_eq!(pool.spawn(|| TRACE_ID.get()).unwrap().join().unwrap(), 42);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// - `propagator` extracts and injects the trace context.
+    pub fn with_trace_propagator(mut self, propagator: impl TraceContextPropagator) -> Self {
+        self.hooks.trace_propagator = Some(Arc::new(propagator));
+        self
+    }
+
+    /// Install a panic hook that says which Worker, and which of its jobs,
+    /// a panic came from.
+    ///
+    /// A job that panics prints `[ThreadPool worker {id} executing job #{n}]`
+    /// to stderr, where `n` counts the jobs that Worker's thread has started,
+    /// followed by whatever the previously installed hook prints, normally
+    /// the usual panic message. The hook is installed when the ThreadPool is
+    /// built and stays installed for the rest of the process, for every
+    /// ThreadPool; installing it again does nothing. Panics outside jobs are
+    /// passed straight on to the previous hook.
+    pub fn install_panic_hook(mut self) -> Self {
+        self.panic_hook = true;
+        self
+    }
+
+    /// Keep a record of the last `capacity` jobs the ThreadPool runs: which
+    /// Worker ran each one, when, and whether it panicked. Read it with
+    /// `ThreadPool::audit_log`.
+    ///
+    /// Jobs are recorded once they finish, and given ids in the order they
+    /// start. Jobs dropped for missing their deadline never run, so aren't
+    /// recorded. Each job takes a lock on the log while it is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// - `capacity` is the number of jobs kept; once there are more, the
+    ///   oldest is overwritten. It is raised to 1 if it is 0.
+    pub fn with_audit_log(mut self, capacity: usize) -> Self {
+        self.audit_log = Some(capacity);
+        self
+    }
+
+    /// Pin each Worker's thread to one of the given CPU cores.
+    ///
+    /// Workers take the cores in order of their id, wrapping around if there
+    /// are more Workers than cores. A thread is pinned as it starts, before
+    /// the `on_thread_start` hook runs. If the OS refuses to pin a thread,
+    /// or pinning isn't supported on this platform, a warning is logged and
+    /// the thread runs unpinned.
+    ///
+    /// # Arguments
+    ///
+    /// - `cores` are the ids of the cores to pin the Workers to.
+    #[cfg(feature = "affinity")]
+    pub fn pin_workers_to_cores(mut self, cores: Vec<usize>) -> Self {
+        self.cores = cores;
+        self
+    }
+
+    /// Export the ThreadPool's metrics to a Prometheus registry.
+    ///
+    /// The `thread_pool_active_threads` and `thread_pool_queued_jobs` gauges
+    /// and the `thread_pool_completed_jobs_total` and
+    /// `thread_pool_panics_total` counters are registered when the
+    /// ThreadPool is built, each with a `pool` label set to `pool_name`.
+    /// They are read from the ThreadPool's own counters whenever the
+    /// registry is gathered, so they cost nothing while jobs run, and are
+    /// unregistered when the ThreadPool is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// - `registry` is the registry to register the metrics with.
+    /// - `pool_name` tells the metrics of different ThreadPools apart.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, registry: &prometheus::Registry, pool_name: &str) -> Self {
+        self.metrics = Some((registry.clone(), pool_name.to_string()));
+        self
+    }
+
+    /// Create the ThreadPool using the current settings.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::InvalidSize` if the number of threads is 0 or is
+    ///   less than `min_threads`.
+    /// - `ThreadPoolError::Spawn` if a worker thread could not be spawned.
+    /// - `ThreadPoolError::Metrics` if the metrics set with `with_metrics`
+    ///   could not be registered, e.g. because another ThreadPool with the
+    ///   same name already registered them.
+    pub fn build(self) -> Result<ThreadPool, ThreadPoolError> {
+        if self.num_threads == 0 || self.min_threads > Some(self.num_threads) {
+            return Err(ThreadPoolError::InvalidSize);
+        }
+
+        let initial_threads = match (self.min_threads, self.lazy) {
+            (Some(min_threads), _) => min_threads,
+            (None, true) => 0,
+            (None, false) => self.num_threads,
+        };
+
+        let on_demand = initial_threads < self.num_threads || self.keep_alive.is_some();
+
+        if self.panic_hook {
+            panic_hook::install();
+        }
+
+        let queue = JobQueue::new(
+            self.channel_capacity,
+            self.shed_policy,
+            self.worker_batch_size,
+            self.hold_batches,
+        );
+        let autoscale = self.autoscale;
+        #[cfg(feature = "metrics")]
+        let metrics = self.metrics.clone();
+        #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+        let mut inner = self.into_inner(queue, on_demand);
+
+        #[cfg(feature = "metrics")]
+        if let Some((registry, pool_name)) = metrics {
+            let collector = PoolCollector::new(
+                &pool_name,
+                Arc::clone(&inner.counters),
+                Arc::clone(&inner.queue),
+            )?;
+
+            registry.register(Box::new(collector.clone()))?;
+            inner.metrics = Some((registry, collector));
+        }
+
+        for _ in 0..initial_threads {
+            inner.spawn_worker()?;
+        }
+
+        let inner = Arc::new(inner);
+
+        if let Some(interval) = autoscale {
+            spawn_autoscaler(&inner, interval)?;
+        }
+
+        Ok(ThreadPool { inner })
+    }
+
+    /// Create a ThreadPool that sends its jobs to existing threads instead
+    /// of spawning Workers. See `ThreadPool::from_existing_threads`.
+    ///
+    /// # Arguments
+    ///
+    /// - `senders` are the SyncSenders the threads receive jobs from.
+    pub(crate) fn build_external(mut self, senders: Vec<SyncSender<Job>>) -> ThreadPool {
+        self.num_threads = senders.len();
+
+        let inner = self.into_inner(JobQueue::external(senders), false);
+
+        ThreadPool {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// A ThreadPoolBuilder with the settings of an existing ThreadPool, for a
+    /// ThreadPool with its own queue and Workers. See `ThreadPool::split`.
+    ///
+    /// The metrics aren't copied, since a second ThreadPool can't register
+    /// them under the same name. Neither is `autoscale`, since the new
+    /// ThreadPool is given its own number of Workers.
+    ///
+    /// # Arguments
+    ///
+    /// - `inner` is the state of the existing ThreadPool.
+    /// - `offset` is the number of the existing ThreadPool's Workers to
+    ///   leave the cores of, with the `affinity` feature.
+    /// - `num_threads` is the number of Workers of the new ThreadPool.
+    pub(crate) fn like(
+        inner: &Inner,
+        #[cfg_attr(not(feature = "affinity"), allow(unused_variables))] offset: usize,
+        num_threads: usize,
+    ) -> Self {
+        #[cfg_attr(not(feature = "affinity"), allow(unused_mut))]
+        let mut config = inner.config.clone();
+
+        #[cfg(feature = "affinity")]
+        if !config.cores.is_empty() {
+            let len = config.cores.len();
+            config.cores.rotate_left(offset % len);
+        }
+
+        let (channel_capacity, shed_policy, worker_batch_size, hold_batches) = inner.queue.limits();
+
+        Self {
+            num_threads,
+            thread_name_prefix: config.thread_name_prefix,
+            stack_size: config.stack_size,
+            channel_capacity,
+            shed_policy,
+            worker_batch_size,
+            hold_batches,
+            max_restarts: config.max_restarts,
+            lazy: false,
+            keep_alive: config.keep_alive,
+            min_threads: inner.on_demand.then(|| config.min_threads.min(num_threads)),
+            max_consecutive_jobs: config.max_consecutive_jobs,
+            autoscale: None,
+            panic_hook: false,
+            audit_log: config.hooks.audit_log.as_ref().map(|log| log.capacity()),
+            hooks: config.hooks,
+            #[cfg(feature = "affinity")]
+            cores: config.cores,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// The state of a new ThreadPool, before any Workers are spawned.
+    ///
+    /// # Arguments
+    ///
+    /// - `queue` is the queue jobs are submitted to.
+    /// - `on_demand` is whether Workers are spawned as jobs arrive.
+    fn into_inner(mut self, queue: JobQueue, on_demand: bool) -> Inner {
+        // Each ThreadPool has its own, even when built from a clone of this
+        // ThreadPoolBuilder.
+        self.hooks.audit_log = self
+            .audit_log
+            .map(|capacity| Arc::new(AuditLog::new(capacity)));
+
+        Inner {
+            workers: Mutex::new(Vec::with_capacity(self.num_threads)),
+            size: AtomicUsize::new(self.num_threads),
+            on_demand,
+            queue: Arc::new(queue),
+            pending: Arc::new(Pending::new().propagating(self.hooks.trace_propagator.clone())),
+            counters: Arc::new(Counters::default()),
+            config: WorkerConfig {
+                thread_name_prefix: self.thread_name_prefix,
+                stack_size: self.stack_size,
+                max_restarts: self.max_restarts,
+                keep_alive: self.keep_alive,
+                min_threads: self.min_threads.unwrap_or(DEFAULT_MIN_THREADS),
+                max_consecutive_jobs: self.max_consecutive_jobs,
+                hooks: self.hooks,
+                #[cfg(feature = "affinity")]
+                cores: self.cores,
+            },
+            next_id: AtomicUsize::new(0),
+            jobs: Arc::new(JobRegistry::default()),
+            resizing: Mutex::new(()),
+            started: AtomicBool::new(false),
+            context: RwLock::new(None),
+            handles: AtomicUsize::new(1),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+}
+
+impl Default for ThreadPoolBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Spawn the thread that resizes a ThreadPool built with `autoscale`.
+///
+/// # Arguments
+///
+/// - `inner` is the state of the ThreadPool. The thread only holds on to it
+///   while resizing, so it doesn't keep the ThreadPool alive, and stops once
+///   the queue has been closed. Dropping the last handle shuts the Workers
+///   down even while the thread is resizing.
+/// - `interval` is how long to wait between resizes.
+///
+/// # Errors
+///
+/// `ThreadPoolError::Spawn` if the thread could not be spawned.
+fn spawn_autoscaler(inner: &Arc<Inner>, interval: Duration) -> Result<(), ThreadPoolError> {
+    let name = format!("{}-autoscale", inner.config.thread_name_prefix);
+    let inner = Arc::downgrade(inner);
+
+    thread::Builder::new().name(name).spawn(move || loop {
+        thread::sleep(interval);
+
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+
+        if inner.queue.is_closed() {
+            return;
+        }
+
+        match inner.resize_to_parallelism() {
+            Ok(_) => {}
+            Err(ThreadPoolError::Shutdown) => return,
+            Err(e) => log_warn!("Unable to autoscale - {e}; trying again later."),
+        }
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// CancellationToken struct
+///
+/// A flag a long-running job can check to find out whether it should stop
+/// early. Clones share the same flag, so cancelling any of them cancels them
+/// all. See `ThreadPool::execute_with_token`.
+///
+/// # Members
+///
+/// - `cancelled` Whether `cancel` has been called on any clone of the token
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Cancelled struct
+///
+/// The error returned by `CancellationToken::check` once the token has been
+/// cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl CancellationToken {
+    /// Create a new CancellationToken that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every job holding a clone of this token to stop.
+    ///
+    /// Jobs are not interrupted; they stop the next time they check the
+    /// token. Cancelling a token more than once does nothing.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel` has been called on any clone of this token.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Check the token, for use with `?` inside a job.
+    ///
+    /// # Errors
+    ///
+    /// `Cancelled` if the token has been cancelled.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "job was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
@@ -0,0 +1,12 @@
+// The channels used to send results and replies back from the Workers.
+//
+// These are `std::sync::mpsc` channels by default, or `crossbeam-channel`
+// channels with the `crossbeam` feature. Both expose the same `channel`,
+// `Sender`, and `Receiver` API, so the rest of the crate doesn't need to
+// know which one it is using.
+
+#[cfg(not(feature = "crossbeam"))]
+pub(crate) use std::sync::mpsc::{channel, Receiver, Sender};
+
+#[cfg(feature = "crossbeam")]
+pub(crate) use crossbeam_channel::{unbounded as channel, Receiver, Sender};
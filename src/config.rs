@@ -0,0 +1,148 @@
+use crate::{ThreadPool, ThreadPoolBuilder, ThreadPoolError};
+use std::{env, error::Error, fmt, num::ParseIntError};
+
+/// ThreadPoolConfig struct
+///
+/// The plain-data settings of a ThreadPoolBuilder, for pools configured from
+/// a file instead of code. With the `serde` feature it can be serialized and
+/// deserialized, e.g. from TOML or JSON; any setting left out uses the
+/// ThreadPoolBuilder's default. Pass it to `ThreadPoolBuilder::from_config`.
+///
+/// Callbacks can't be expressed as data, so they still have to be set on
+/// the ThreadPoolBuilder.
+///
+/// # Members
+///
+/// - `num_threads` The number of Workers to spawn.
+/// - `thread_name_prefix` The prefix of each worker thread's name.
+/// - `stack_size` The stack size, in bytes, of each worker thread.
+/// - `channel_capacity` The maximum number of jobs waiting in the queue.
+/// - `max_restarts` The number of times a crashed Worker is restarted.
+/// - `lazy` Whether Workers are only spawned once there are jobs for them.
+/// - `keep_alive_ms` How long, in milliseconds, an idle Worker waits for a
+///   job before exiting.
+/// - `min_threads` The number of Workers that are always alive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ThreadPoolConfig {
+    pub num_threads: Option<usize>,
+    pub thread_name_prefix: Option<String>,
+    pub stack_size: Option<usize>,
+    pub channel_capacity: Option<usize>,
+    pub max_restarts: Option<usize>,
+    pub lazy: bool,
+    pub keep_alive_ms: Option<u64>,
+    pub min_threads: Option<usize>,
+}
+
+/// Errors that can occur while configuring a ThreadPool from environment
+/// variables.
+///
+/// # Variants
+///
+/// - `Parse` The named variable isn't a valid number.
+/// - `NotUnicode` The named variable isn't valid unicode.
+/// - `Build` The ThreadPool could not be built with the settings read.
+#[derive(Debug)]
+pub enum ThreadPoolConfigError {
+    Parse(&'static str, ParseIntError),
+    NotUnicode(&'static str),
+    Build(ThreadPoolError),
+}
+
+impl fmt::Display for ThreadPoolConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(variable, e) => write!(f, "invalid {variable}: {e}"),
+            Self::NotUnicode(variable) => write!(f, "{variable} is not valid unicode"),
+            Self::Build(e) => write!(f, "unable to build thread pool: {e}"),
+        }
+    }
+}
+
+impl Error for ThreadPoolConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(_, e) => Some(e),
+            Self::NotUnicode(_) => None,
+            Self::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<ThreadPoolError> for ThreadPoolConfigError {
+    fn from(e: ThreadPoolError) -> Self {
+        Self::Build(e)
+    }
+}
+
+impl ThreadPoolConfig {
+    /// Read a ThreadPoolConfig from environment variables, for tuning a
+    /// deployed service without changing its code.
+    ///
+    /// - `THREADPOOL_SIZE` sets `num_threads`.
+    /// - `THREADPOOL_STACK_SIZE` sets `stack_size`, in bytes.
+    /// - `THREADPOOL_CHANNEL_CAPACITY` sets `channel_capacity`.
+    /// - `THREADPOOL_KEEP_ALIVE_SECS` sets `keep_alive_ms`, in seconds.
+    ///
+    /// Variables that aren't set, or are empty, are left as `None`.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolConfigError::Parse` if a variable isn't a valid number.
+    /// - `ThreadPoolConfigError::NotUnicode` if a variable isn't valid
+    ///   unicode.
+    pub fn from_env() -> Result<Self, ThreadPoolConfigError> {
+        Ok(Self {
+            num_threads: var("THREADPOOL_SIZE")?,
+            stack_size: var("THREADPOOL_STACK_SIZE")?,
+            channel_capacity: var("THREADPOOL_CHANNEL_CAPACITY")?,
+            keep_alive_ms: var::<u64>("THREADPOOL_KEEP_ALIVE_SECS")?
+                .map(|secs| secs.saturating_mul(1000)),
+            ..Self::default()
+        })
+    }
+}
+
+impl ThreadPool {
+    /// Create a new ThreadPool configured from environment variables. See
+    /// `ThreadPoolConfig::from_env` for the variables read; anything they
+    /// don't set keeps the ThreadPoolBuilder's default.
+    ///
+    /// # Errors
+    ///
+    /// - The same errors as `ThreadPoolConfig::from_env`.
+    /// - `ThreadPoolConfigError::Build` if the ThreadPool could not be built,
+    ///   e.g. because `THREADPOOL_SIZE` is 0.
+    pub fn from_env() -> Result<Self, ThreadPoolConfigError> {
+        let config = ThreadPoolConfig::from_env()?;
+
+        Ok(ThreadPoolBuilder::from_config(config).build()?)
+    }
+}
+
+/// Read a number from an environment variable.
+///
+/// # Arguments
+///
+/// - `variable` is the name of the variable.
+///
+/// # Return
+///
+/// The number, or `None` if the variable isn't set or is empty.
+fn var<T>(variable: &'static str) -> Result<Option<T>, ThreadPoolConfigError>
+where
+    T: std::str::FromStr<Err = ParseIntError>,
+{
+    match env::var(variable) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| ThreadPoolConfigError::Parse(variable, e)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ThreadPoolConfigError::NotUnicode(variable)),
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{queue::Backpressure, ExecuteError, Job};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{SyncSender, TrySendError},
+        PoisonError, RwLock, RwLockReadGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long to sleep between attempts while waiting for space with
+/// `Backpressure::Until`, since a SyncSender can't wait with a timeout.
+const RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// ExternalThreads struct
+///
+/// The threads of a ThreadPool created with
+/// `ThreadPool::from_existing_threads`. They weren't spawned by the pool and
+/// can't share its queue, so each job is sent straight to one of them, in
+/// turn.
+///
+/// # Members
+///
+/// - `senders` One SyncSender per thread, emptied when the pool shuts down
+///   so the threads see their channel disconnect
+/// - `next` The position of the thread the next job is offered to first
+#[derive(Debug)]
+pub(crate) struct ExternalThreads {
+    senders: RwLock<Vec<SyncSender<Job>>>,
+    next: AtomicUsize,
+}
+
+impl ExternalThreads {
+    /// Create a new ExternalThreads.
+    ///
+    /// # Arguments
+    ///
+    /// - `senders` are the SyncSenders the threads receive jobs from.
+    pub(crate) fn new(senders: Vec<SyncSender<Job>>) -> Self {
+        Self {
+            senders: RwLock::new(senders),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Send the job to the next thread that has room for it.
+    ///
+    /// Threads are tried in turn, starting after the one the last job was
+    /// offered to. If every thread's channel is full, `backpressure` decides
+    /// whether to fail or to wait for the first of them.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to send.
+    /// - `backpressure` is what to do if every channel is full.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the pool has been shut down.
+    /// - `ExecuteError::Disconnected` if every thread has stopped receiving.
+    /// - `ExecuteError::QueueFull` if every channel is full and
+    ///   `backpressure` is `Backpressure::Reject`.
+    /// - `ExecuteError::Timeout` if every channel is still full at the
+    ///   deadline given by `Backpressure::Until`.
+    pub(crate) fn send(&self, job: Job, backpressure: Backpressure) -> Result<(), ExecuteError> {
+        // Cloned so a blocking send doesn't hold up `close`.
+        let senders = self.senders().clone();
+
+        if senders.is_empty() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut job = job;
+
+        loop {
+            let mut full = false;
+
+            for i in 0..senders.len() {
+                match senders[(start + i) % senders.len()].try_send(job) {
+                    Ok(()) => return Ok(()),
+                    Err(TrySendError::Full(rejected)) => {
+                        full = true;
+                        job = rejected;
+                    }
+                    Err(TrySendError::Disconnected(rejected)) => job = rejected,
+                }
+            }
+
+            if !full {
+                return Err(ExecuteError::Disconnected);
+            }
+
+            match backpressure {
+                Backpressure::Reject => return Err(ExecuteError::QueueFull),
+                Backpressure::Block => {
+                    return senders[start % senders.len()]
+                        .send(job)
+                        .map_err(|_| ExecuteError::Disconnected);
+                }
+                Backpressure::Until(deadline) => {
+                    let now = Instant::now();
+
+                    if now >= deadline {
+                        return Err(ExecuteError::Timeout);
+                    }
+
+                    thread::sleep(RETRY_INTERVAL.min(deadline - now));
+                }
+            }
+        }
+    }
+
+    /// Send the job to the thread picked by `key`, waiting for room in its
+    /// channel, so every job sent with the same key goes to the same thread.
+    ///
+    /// # Arguments
+    ///
+    /// - `key` picks the thread, modulo the number of threads.
+    /// - `job` is the Job to send.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the pool has been shut down.
+    /// - `ExecuteError::Disconnected` if the thread has stopped receiving.
+    pub(crate) fn send_to(&self, key: u64, job: Job) -> Result<(), ExecuteError> {
+        let sender = {
+            let senders = self.senders();
+
+            if senders.is_empty() {
+                return Err(ExecuteError::Shutdown);
+            }
+
+            senders[(key % senders.len() as u64) as usize].clone()
+        };
+
+        sender.send(job).map_err(|_| ExecuteError::Disconnected)
+    }
+
+    /// Send a job to every thread, waiting for room in each channel.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is called once per thread to create the Job sent to it.
+    ///
+    /// # Return
+    ///
+    /// The number of threads a job was sent to. Threads that have stopped
+    /// receiving are skipped.
+    pub(crate) fn broadcast(&self, mut job: impl FnMut() -> Job) -> usize {
+        let senders = self.senders().clone();
+
+        senders
+            .iter()
+            .filter(|sender| sender.send(job()).is_ok())
+            .count()
+    }
+
+    /// Drop every SyncSender, so the threads see their channel disconnect
+    /// once they have received the jobs already sent to them.
+    pub(crate) fn close(&self) {
+        self.senders
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Read the list of SyncSenders.
+    ///
+    /// A poisoned lock is recovered, since the list is only ever cleared.
+    fn senders(&self) -> RwLockReadGuard<'_, Vec<SyncSender<Job>>> {
+        self.senders.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
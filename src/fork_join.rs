@@ -0,0 +1,226 @@
+use crate::{ThreadPool, WorkerHandle};
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+/// ForkJoinPool struct
+///
+/// Runs recursive divide-and-conquer work, like merge sort, on a
+/// ThreadPool. A task forks its sub-tasks with `fork` and waits for them
+/// with `ForkHandle::join`, which never deadlocks however few Workers there
+/// are: a sub-task no Worker has started yet is run by the thread that
+/// joins it, and while a started sub-task is running elsewhere the joining
+/// thread runs other jobs from the queue instead of sitting idle.
+///
+/// Clones share the same ThreadPool.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the sub-tasks are submitted to
+/// - `helper` Takes jobs off the ThreadPool's queue while a join waits, or
+///   `None` if the ThreadPool has no queue to take them from
+#[derive(Debug, Clone)]
+pub struct ForkJoinPool {
+    pool: ThreadPool,
+    helper: Option<WorkerHandle>,
+}
+
+/// ForkHandle struct
+///
+/// A sub-task forked with `ForkJoinPool::fork`, to be joined once its result
+/// is needed.
+///
+/// # Members
+///
+/// - `shared` The sub-task itself, shared with the job that runs it
+/// - `helper` Takes jobs off the ThreadPool's queue while `join` waits
+pub struct ForkHandle<T> {
+    shared: Arc<Shared<T>>,
+    helper: Option<WorkerHandle>,
+}
+
+/// Shared struct
+///
+/// # Members
+///
+/// - `state` How far along the sub-task is
+/// - `done` Notified when the sub-task finishes
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    done: Condvar,
+}
+
+/// How far along a forked sub-task is.
+///
+/// # Variants
+///
+/// - `Queued` Nobody has started the sub-task yet.
+/// - `Running` A Worker or the joining thread is running it.
+/// - `Done` It returned, or panicked.
+enum State<T> {
+    Queued(Box<dyn FnOnce() -> T + Send>),
+    Running,
+    Done(thread::Result<T>),
+}
+
+impl ForkJoinPool {
+    /// Create a ForkJoinPool that runs its sub-tasks on `pool`.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to submit the sub-tasks to.
+    pub fn new(pool: ThreadPool) -> Self {
+        Self {
+            helper: pool.receiver_handle(),
+            pool,
+        }
+    }
+
+    /// Submit a sub-task to the ThreadPool.
+    ///
+    /// If it can't be submitted, e.g. because the queue is full, it is left
+    /// for `ForkHandle::join` to run instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to run.
+    ///
+    /// # Return
+    ///
+    /// A ForkHandle to join the sub-task with.
+    pub fn fork<F, T>(&self, f: F) -> ForkHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::Queued(Box::new(f))),
+            done: Condvar::new(),
+        });
+        let job = Arc::clone(&shared);
+
+        // Whoever takes the sub-task first runs it, so a failed submission
+        // only means the joining thread does.
+        let _ = self.pool.execute(move || {
+            if let Some(f) = job.start() {
+                job.finish(panic::catch_unwind(AssertUnwindSafe(f)));
+            }
+        });
+
+        ForkHandle {
+            shared,
+            helper: self.helper.clone(),
+        }
+    }
+
+    /// The ThreadPool the sub-tasks run on.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+}
+
+impl<T> ForkHandle<T> {
+    /// Wait for the sub-task and return its result.
+    ///
+    /// If no Worker has started the sub-task yet, it runs on the current
+    /// thread. Otherwise the current thread runs other jobs from the
+    /// ThreadPool's queue until the sub-task is done, and then blocks if
+    /// there were none.
+    ///
+    /// # Return
+    ///
+    /// The value returned by the sub-task.
+    ///
+    /// # Panics
+    ///
+    /// If the sub-task panicked, the panic carries on on the current thread.
+    pub fn join(self) -> T {
+        if let Some(f) = self.shared.start() {
+            return f();
+        }
+
+        loop {
+            let state = self.shared.lock();
+
+            if matches!(*state, State::Done(_)) {
+                return take_result(state);
+            }
+
+            drop(state);
+
+            if self.help().is_none() {
+                break;
+            }
+        }
+
+        let state = self
+            .shared
+            .done
+            .wait_while(self.shared.lock(), |state| !matches!(state, State::Done(_)))
+            .unwrap_or_else(PoisonError::into_inner);
+
+        take_result(state)
+    }
+
+    /// Whether the sub-task has finished.
+    pub fn is_finished(&self) -> bool {
+        matches!(*self.shared.lock(), State::Done(_))
+    }
+
+    /// Run one job from the ThreadPool's queue on the current thread.
+    ///
+    /// # Return
+    ///
+    /// `Some(())` if a job was run, or `None` if there were none.
+    fn help(&self) -> Option<()> {
+        self.helper.as_ref()?.run_one()
+    }
+}
+
+impl<T> Shared<T> {
+    /// Take the sub-task to run it, if nobody has yet.
+    fn start(&self) -> Option<Box<dyn FnOnce() -> T + Send>> {
+        let mut state = self.lock();
+
+        match std::mem::replace(&mut *state, State::Running) {
+            State::Queued(f) => Some(f),
+            other => {
+                *state = other;
+                None
+            }
+        }
+    }
+
+    /// Leave the sub-task's result for `ForkHandle::join`.
+    fn finish(&self, result: thread::Result<T>) {
+        *self.lock() = State::Done(result);
+        self.done.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Take the result of a finished sub-task.
+fn take_result<T>(mut state: MutexGuard<'_, State<T>>) -> T {
+    match std::mem::replace(&mut *state, State::Running) {
+        State::Done(Ok(value)) => value,
+        State::Done(Err(payload)) => {
+            drop(state);
+            panic::resume_unwind(payload)
+        }
+        State::Queued(_) | State::Running => unreachable!("the sub-task has finished"),
+    }
+}
+
+impl<T> fmt::Debug for ForkHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForkHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{logging::log_warn, ExecuteError, ThreadPool};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Wake, Waker},
+};
+
+#[cfg(feature = "futures")]
+use crate::PoolState;
+#[cfg(feature = "futures")]
+use futures_task::{FutureObj, Spawn, SpawnError};
+
+/// The future is waiting to be woken.
+const IDLE: u8 = 0;
+/// A job to poll the future is in the queue.
+const SCHEDULED: u8 = 1;
+/// A Worker is polling the future.
+const RUNNING: u8 = 2;
+/// The future was woken while a Worker was polling it.
+const NOTIFIED: u8 = 3;
+/// The future has finished, or can't be polled any more.
+const COMPLETE: u8 = 4;
+
+/// A boxed future that can be polled on any Worker.
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// FuturePool struct
+///
+/// A ThreadPool that drives futures to completion, without an async runtime.
+/// Each time a future is woken, a job that polls it once is submitted to the
+/// ThreadPool, so a future only takes up a Worker while it is making
+/// progress, never while it is waiting. A future that hasn't finished keeps
+/// the ThreadPool from shutting down for as long as anything holds its Waker.
+///
+/// With the `futures` feature, FuturePool and ThreadPool both implement
+/// `futures_task::Spawn`. `ThreadPool::spawn` takes precedence over
+/// `SpawnExt::spawn`, so call the latter as `SpawnExt::spawn(&pool, fut)`.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the futures are polled on
+#[derive(Debug, Clone)]
+pub struct FuturePool {
+    pool: ThreadPool,
+}
+
+/// Task struct
+///
+/// A future submitted to a FuturePool, which is also its own Waker.
+///
+/// # Members
+///
+/// - `future` The future, until it has finished
+/// - `state` Whether the future is idle, scheduled, being polled, or done
+/// - `pool` The ThreadPool to submit the next poll to
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    state: AtomicU8,
+    pool: ThreadPool,
+}
+
+impl FuturePool {
+    /// Wrap a ThreadPool so futures can be run on it.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to poll the futures on.
+    pub fn new(pool: ThreadPool) -> Self {
+        Self { pool }
+    }
+
+    /// Run the future on the ThreadPool until it completes.
+    ///
+    /// The future is polled once straight away and then again every time it
+    /// is woken, on whichever Worker is free. A future that panics is never
+    /// polled again; the panic is handled like that of a job passed to
+    /// `ThreadPool::execute`. If the ThreadPool is shut down while the
+    /// future is waiting, further wakes are ignored and the future is
+    /// dropped.
+    ///
+    /// # Arguments
+    ///
+    /// - `fut` is the future to run.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `ThreadPool::execute`, if the first poll couldn't
+    /// be submitted.
+    pub fn execute_future<F>(&self, fut: F) -> Result<(), ExecuteError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(fut))),
+            state: AtomicU8::new(SCHEDULED),
+            pool: self.pool.clone(),
+        });
+
+        task.submit()
+    }
+
+    /// The ThreadPool the futures are polled on.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+
+    /// Unwrap the ThreadPool.
+    pub fn into_inner(self) -> ThreadPool {
+        self.pool
+    }
+}
+
+impl Task {
+    /// Submit a job that polls the future. The state must be `SCHEDULED`.
+    ///
+    /// # Errors
+    ///
+    /// The error returned by `ThreadPool::execute`, in which case the future
+    /// is dropped.
+    fn submit(self: Arc<Self>) -> Result<(), ExecuteError> {
+        let task = Arc::clone(&self);
+
+        self.pool.execute(move || task.poll()).inspect_err(|_| {
+            self.state.store(COMPLETE, Ordering::Release);
+            self.future
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        })
+    }
+
+    /// Poll the future once, submitting another poll if it was woken in the
+    /// meantime.
+    fn poll(self: Arc<Self>) {
+        self.state.store(RUNNING, Ordering::Release);
+
+        let waker = Waker::from(Arc::clone(&self));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = self.future.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let Some(fut) = future.as_mut() else {
+            return;
+        };
+
+        if fut.as_mut().poll(&mut cx).is_ready() {
+            self.state.store(COMPLETE, Ordering::Release);
+            future.take();
+            return;
+        }
+
+        drop(future);
+
+        // A wake while polling would have been missed, so poll again, but at
+        // the back of the queue so other jobs get a turn.
+        if self
+            .state
+            .compare_exchange(RUNNING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            self.state.store(SCHEDULED, Ordering::Release);
+            self.resubmit();
+        }
+    }
+
+    /// Submit the next poll from a Waker, which has nowhere to return an
+    /// error to.
+    fn resubmit(self: Arc<Self>) {
+        if let Err(e) = self.submit() {
+            log_warn!("Unable to poll a future - {e}; dropping it.");
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        let mut state = self.state.load(Ordering::Acquire);
+
+        loop {
+            let next = match state {
+                IDLE => SCHEDULED,
+                RUNNING => NOTIFIED,
+                _ => return,
+            };
+
+            match self
+                .state
+                .compare_exchange(state, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) if next == SCHEDULED => return self.resubmit(),
+                Ok(_) => return,
+                Err(current) => state = current,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl Spawn for FuturePool {
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.execute_future(future)
+            .map_err(|_| SpawnError::shutdown())
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        self.pool.status()
+    }
+}
+
+#[cfg(feature = "futures")]
+impl Spawn for ThreadPool {
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        FuturePool::new(self.clone()).spawn_obj(future)
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        match self.state() {
+            PoolState::Shutdown => Err(SpawnError::shutdown()),
+            _ => Ok(()),
+        }
+    }
+}
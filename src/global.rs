@@ -0,0 +1,19 @@
+use crate::ThreadPool;
+use std::sync::OnceLock;
+
+/// A process-wide ThreadPool, for running jobs in the background without
+/// keeping a ThreadPool around.
+///
+/// The ThreadPool is created by the first call, with a Worker per logical
+/// CPU, or 4 Workers if the number of CPUs can't be determined. It is never
+/// shut down, so jobs still running when the process exits are cut short.
+/// Shutting down a clone of it would close it for everyone, so don't.
+///
+/// # Panics
+///
+/// If a worker thread could not be spawned on the first call.
+pub fn global() -> &'static ThreadPool {
+    static GLOBAL: OnceLock<ThreadPool> = OnceLock::new();
+
+    GLOBAL.get_or_init(ThreadPool::default)
+}
@@ -0,0 +1,281 @@
+use crate::{ExecuteError, Job, Scope, ThreadPool};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+/// TaskId struct
+///
+/// The caller-chosen id of a task in a TaskGraph.
+///
+/// # Members
+///
+/// - `0` The id itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(pub usize);
+
+/// TaskGraph struct
+///
+/// A set of jobs where some jobs must not start until others have finished.
+/// Each task is submitted to the ThreadPool as soon as every task it depends
+/// on has finished, so independent tasks run in parallel.
+///
+/// A task can only depend on tasks that were added before it, which means a
+/// TaskGraph can never contain a cycle.
+///
+/// # Members
+///
+/// - `tasks` Every task, in the order they were added
+/// - `indices` The position of each task in `tasks`, keyed by its id
+#[derive(Default)]
+pub struct TaskGraph {
+    tasks: Vec<Task>,
+    indices: HashMap<TaskId, usize>,
+}
+
+/// Task struct
+///
+/// # Members
+///
+/// - `id` The id the task was added with
+/// - `job` The job itself, taken when the task is submitted
+/// - `remaining` The number of dependencies that haven't finished yet
+/// - `dependents` The positions of the tasks that depend on this one
+struct Task {
+    id: TaskId,
+    job: Mutex<Option<Job>>,
+    remaining: AtomicUsize,
+    dependents: Vec<usize>,
+}
+
+/// Errors that can occur while building or running a TaskGraph.
+///
+/// # Variants
+///
+/// - `DuplicateTask` A task with the given id has already been added.
+/// - `UnknownDependency` A task depends on an id that hasn't been added yet.
+/// - `Execute` A task could not be submitted to the ThreadPool. Tasks that
+///   depend on it never run.
+/// - `Panicked` The given tasks panicked. Tasks that depend on them never
+///   run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    DuplicateTask(TaskId),
+    UnknownDependency(TaskId),
+    Execute(ExecuteError),
+    Panicked(Vec<TaskId>),
+}
+
+/// RunState struct
+///
+/// The outcome of a run, shared by every task in it.
+///
+/// # Members
+///
+/// - `failed` Why the first task that couldn't be submitted was rejected
+/// - `panicked` The tasks that panicked
+#[derive(Default)]
+struct RunState {
+    failed: Mutex<Option<ExecuteError>>,
+    panicked: Mutex<Vec<TaskId>>,
+}
+
+impl TaskGraph {
+    /// Create a new, empty TaskGraph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a task that only starts once every task in `deps` has finished.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the new task.
+    /// - `job` is the function to be executed.
+    /// - `deps` are the ids of the tasks that must finish first.
+    ///
+    /// # Errors
+    ///
+    /// - `GraphError::DuplicateTask` if a task with `id` was already added.
+    /// - `GraphError::UnknownDependency` if one of `deps` hasn't been added.
+    pub fn add_task<F>(&mut self, id: TaskId, job: F, deps: &[TaskId]) -> Result<(), GraphError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if self.indices.contains_key(&id) {
+            return Err(GraphError::DuplicateTask(id));
+        }
+
+        let mut dep_indices = Vec::with_capacity(deps.len());
+
+        for dep in deps {
+            match self.indices.get(dep) {
+                Some(&index) => dep_indices.push(index),
+                None => return Err(GraphError::UnknownDependency(*dep)),
+            }
+        }
+
+        dep_indices.sort_unstable();
+        dep_indices.dedup();
+
+        let index = self.tasks.len();
+
+        for &dep in &dep_indices {
+            self.tasks[dep].dependents.push(index);
+        }
+
+        self.tasks.push(Task {
+            id,
+            job: Mutex::new(Some(Box::new(job))),
+            remaining: AtomicUsize::new(dep_indices.len()),
+            dependents: Vec::new(),
+        });
+        self.indices.insert(id, index);
+
+        Ok(())
+    }
+
+    /// The number of tasks in the TaskGraph.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Whether the TaskGraph has no tasks.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Run every task on the ThreadPool, respecting their dependencies.
+    ///
+    /// The current thread blocks until every task that can run has
+    /// finished. Like `ThreadPool::scope`, calling this from inside a job
+    /// can deadlock if every other Worker is busy.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to run the tasks on.
+    ///
+    /// # Errors
+    ///
+    /// - `GraphError::Execute` if a task could not be submitted.
+    /// - `GraphError::Panicked` if any of the tasks panicked.
+    pub fn submit_to(self, pool: &ThreadPool) -> Result<(), GraphError> {
+        let state = RunState::default();
+        let tasks = &self.tasks;
+
+        pool.scope(|s| {
+            for (index, task) in tasks.iter().enumerate() {
+                if task.remaining.load(Ordering::Relaxed) == 0 {
+                    submit(s, tasks, index, &state);
+                }
+            }
+        });
+
+        if let Some(e) = state
+            .failed
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            return Err(GraphError::Execute(e));
+        }
+
+        let mut panicked = state
+            .panicked
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if panicked.is_empty() {
+            Ok(())
+        } else {
+            panicked.sort_unstable();
+            Err(GraphError::Panicked(panicked))
+        }
+    }
+}
+
+/// Submit the task at `index`, which submits its dependents in turn once it
+/// finishes and they have no dependencies left.
+///
+/// # Arguments
+///
+/// - `s` is the Scope the whole graph runs in.
+/// - `tasks` are every task in the graph.
+/// - `index` is the position of the task to submit.
+/// - `state` is where failures are recorded.
+fn submit<'scope>(
+    s: &'scope Scope<'scope, '_>,
+    tasks: &'scope [Task],
+    index: usize,
+    state: &'scope RunState,
+) {
+    let task = &tasks[index];
+
+    let result = s.execute(move || {
+        let job = task
+            .job
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        let Some(job) = job else {
+            return;
+        };
+
+        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+            state
+                .panicked
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(task.id);
+            return;
+        }
+
+        for &dependent in &task.dependents {
+            if tasks[dependent].remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+                submit(s, tasks, dependent, state);
+            }
+        }
+    });
+
+    if let Err(e) = result {
+        state
+            .failed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(e);
+    }
+}
+
+impl fmt::Debug for TaskGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskGraph")
+            .field("tasks", &self.tasks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateTask(id) => write!(f, "task {} was already added", id.0),
+            Self::UnknownDependency(id) => write!(f, "task {} hasn't been added", id.0),
+            Self::Execute(e) => write!(f, "unable to submit task: {e}"),
+            Self::Panicked(ids) => write!(f, "tasks {ids:?} panicked"),
+        }
+    }
+}
+
+impl Error for GraphError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Execute(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::ThreadPool;
+
+/// WorkerGuard struct
+///
+/// An extra Worker added to a ThreadPool by `ThreadPool::temporary_worker`.
+/// Dropping the WorkerGuard tells that Worker to exit once it has finished
+/// the job it is executing, if any, and blocks until it has.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the Worker was added to
+/// - `id` The id of the Worker
+#[derive(Debug)]
+pub struct WorkerGuard<'a> {
+    pool: &'a ThreadPool,
+    id: usize,
+}
+
+impl<'a> WorkerGuard<'a> {
+    /// Wrap the Worker with the given id, which has just been spawned.
+    pub(crate) fn new(pool: &'a ThreadPool, id: usize) -> Self {
+        Self { pool, id }
+    }
+
+    /// The id of the temporary Worker.
+    pub fn worker_id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Drop for WorkerGuard<'_> {
+    fn drop(&mut self) {
+        self.pool.inner.retire_worker(self.id);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::channel::Receiver;
+use std::{any::Any, error::Error, fmt, thread};
+
+/// JoinHandle struct
+///
+/// An owned permission to wait on the result of a job submitted with
+/// `ThreadPool::spawn`.
+///
+/// # Members
+///
+/// - `receiver` The channel the job sends its result, or its panic, through.
+pub struct JoinHandle<T> {
+    receiver: Receiver<thread::Result<T>>,
+}
+
+/// PanicError struct
+///
+/// The payload of a job that panicked, forwarded to the thread that waited
+/// on it.
+///
+/// # Members
+///
+/// - `0` The value the job panicked with, as passed to `panic!`.
+pub struct PanicError(pub Box<dyn Any + Send + 'static>);
+
+/// Errors that can occur while waiting on a JoinHandle.
+///
+/// # Variants
+///
+/// - `Panicked` The job panicked. Holds the payload the job panicked with.
+/// - `Disconnected` The job was dropped without being run, e.g. because the
+///   ThreadPool was shut down first.
+pub enum JoinError {
+    Panicked(PanicError),
+    Disconnected,
+}
+
+impl<T> JoinHandle<T> {
+    /// Create a new JoinHandle.
+    ///
+    /// # Arguments
+    ///
+    /// - `receiver` is the channel the job will send its result through.
+    pub(crate) fn new(receiver: Receiver<thread::Result<T>>) -> Self {
+        Self { receiver }
+    }
+
+    /// Block the current thread until the job has finished.
+    ///
+    /// # Return
+    ///
+    /// The value returned by the job.
+    ///
+    /// # Errors
+    ///
+    /// - `JoinError::Panicked` if the job panicked.
+    /// - `JoinError::Disconnected` if the job was dropped without being run.
+    pub fn join(self) -> Result<T, JoinError> {
+        match self.receiver.recv() {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(payload)) => Err(JoinError::Panicked(PanicError(payload))),
+            Err(_) => Err(JoinError::Disconnected),
+        }
+    }
+}
+
+impl PanicError {
+    /// The message the job panicked with, if it panicked with a string.
+    ///
+    /// This covers `panic!` with a literal or a format string, which is
+    /// almost every panic.
+    pub fn message(&self) -> Option<&str> {
+        self.0
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| self.0.downcast_ref::<String>().map(String::as_str))
+    }
+
+    /// The value the job panicked with.
+    ///
+    /// Pass it to `std::panic::resume_unwind` to continue the panic on the
+    /// current thread.
+    pub fn into_payload(self) -> Box<dyn Any + Send + 'static> {
+        self.0
+    }
+}
+
+impl fmt::Debug for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(message) => f.debug_tuple("PanicError").field(&message).finish(),
+            None => f.debug_tuple("PanicError").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl fmt::Display for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(message) => write!(f, "job panicked: {message}"),
+            None => write!(f, "job panicked"),
+        }
+    }
+}
+
+impl Error for PanicError {}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked(e) => f.debug_tuple("Panicked").field(e).finish(),
+            Self::Disconnected => f.write_str("Disconnected"),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked(e) => e.fmt(f),
+            Self::Disconnected => write!(f, "job was dropped before it could run"),
+        }
+    }
+}
+
+impl Error for JoinError {}
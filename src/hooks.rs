@@ -0,0 +1,112 @@
+use crate::{audit::AuditLog, logging::log_warn, propagate::Propagate, JobMetrics, PanicError};
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+};
+
+/// A callback given a Worker's id and the panic of a job that ran on it.
+type PanicHook = Arc<dyn Fn(usize, &PanicError) + Send + Sync>;
+
+/// Hooks struct
+///
+/// The callbacks registered on a ThreadPoolBuilder, shared by every Worker.
+///
+/// # Members
+///
+/// - `on_job_complete` Called with the JobMetrics of every job once it has
+///   finished
+/// - `on_job_panic` Called with a Worker's id and the panic of every job
+///   that panics on it
+/// - `thread_local_init` Called on every worker thread when it starts, to
+///   set up its thread-locals
+/// - `on_thread_start` Called with a Worker's id when its thread starts
+/// - `on_thread_stop` Called with a Worker's id just before its thread exits
+/// - `audit_log` Where every job that runs is recorded, if the ThreadPool
+///   keeps an audit log
+/// - `trace_propagator` Carries the submitting thread's trace context into
+///   every job
+/// - `thread_builder` Called with a Worker's id for the thread::Builder to
+///   spawn its thread with
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_job_complete: Option<Arc<dyn Fn(JobMetrics) + Send + Sync>>,
+    pub(crate) on_job_panic: Option<PanicHook>,
+    pub(crate) thread_local_init: Option<Arc<dyn Fn() + Send + Sync>>,
+    pub(crate) on_thread_start: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+    pub(crate) on_thread_stop: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+    pub(crate) audit_log: Option<Arc<AuditLog>>,
+    pub(crate) trace_propagator: Option<Arc<dyn Propagate>>,
+    pub(crate) thread_builder: Option<Arc<dyn Fn(usize) -> thread::Builder + Send + Sync>>,
+}
+
+impl Hooks {
+    /// Run the `on_job_panic` hook, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker the job panicked on.
+    /// - `error` is the panic the job unwound with.
+    pub(crate) fn job_panic(&self, id: usize, error: &PanicError) {
+        if let Some(hook) = &self.on_job_panic {
+            if panic::catch_unwind(AssertUnwindSafe(|| hook(id, error))).is_err() {
+                log_warn!("Worker {id} on_job_panic hook panicked; continuing.");
+            }
+        }
+    }
+
+    /// Run the `thread_local_init` hook, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker whose thread is starting.
+    pub(crate) fn thread_local_init(&self, id: usize) {
+        if let Some(hook) = &self.thread_local_init {
+            if panic::catch_unwind(AssertUnwindSafe(|| hook())).is_err() {
+                log_warn!("Worker {id} thread_local_init hook panicked; continuing.");
+            }
+        }
+    }
+
+    /// Run the `on_thread_start` hook, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker whose thread is starting.
+    pub(crate) fn thread_start(&self, id: usize) {
+        if let Some(hook) = &self.on_thread_start {
+            if panic::catch_unwind(AssertUnwindSafe(|| hook(id))).is_err() {
+                log_warn!("Worker {id} on_thread_start hook panicked; continuing.");
+            }
+        }
+    }
+
+    /// Run the `on_thread_stop` hook, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker whose thread is stopping.
+    pub(crate) fn thread_stop(&self, id: usize) {
+        if let Some(hook) = &self.on_thread_stop {
+            if panic::catch_unwind(AssertUnwindSafe(|| hook(id))).is_err() {
+                log_warn!("Worker {id} on_thread_stop hook panicked; continuing.");
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_job_complete", &self.on_job_complete.is_some())
+            .field("on_job_panic", &self.on_job_panic.is_some())
+            .field("thread_local_init", &self.thread_local_init.is_some())
+            .field("on_thread_start", &self.on_thread_start.is_some())
+            .field("on_thread_stop", &self.on_thread_stop.is_some())
+            .field("audit_log", &self.audit_log.is_some())
+            .field("trace_propagator", &self.trace_propagator.is_some())
+            .field("thread_builder", &self.thread_builder.is_some())
+            .finish()
+    }
+}
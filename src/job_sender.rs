@@ -0,0 +1,106 @@
+use crate::{
+    queue::{Backpressure, Priority},
+    ExecuteError, Inner, Job, Pool,
+};
+use std::{
+    fmt,
+    sync::{atomic::Ordering, Arc, Weak},
+};
+
+/// JobSender struct
+///
+/// Submits jobs to a ThreadPool without keeping it alive, for code that
+/// can't hold on to the ThreadPool itself, such as a callback stored in a
+/// long-lived struct. See `ThreadPool::clone_sender`.
+///
+/// Jobs are submitted like with `ThreadPool::execute`. Once every handle
+/// to the ThreadPool has been dropped, sending fails with
+/// `ExecuteError::Shutdown`. Clones send to the same ThreadPool.
+///
+/// The ThreadPool's state is held while a job is being sent, but holding it
+/// doesn't count as a handle: if the last handle is dropped meanwhile, the
+/// thread dropping it still shuts the Workers down and waits for them,
+/// rather than the sending thread.
+///
+/// # Members
+///
+/// - `inner` The state of the ThreadPool jobs are sent to
+#[derive(Clone)]
+pub struct JobSender {
+    inner: Weak<Inner>,
+}
+
+impl JobSender {
+    /// Create a JobSender for a ThreadPool.
+    ///
+    /// # Arguments
+    ///
+    /// - `inner` is the state of the ThreadPool.
+    pub(crate) fn new(inner: &Arc<Inner>) -> Self {
+        Self {
+            inner: Arc::downgrade(inner),
+        }
+    }
+
+    /// Puts an already boxed job in the ThreadPool's queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn send(&self, job: Job) -> Result<(), ExecuteError> {
+        self.execute(job)
+    }
+
+    /// Puts the job in the ThreadPool's queue for a Worker to execute.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down or
+    ///   dropped.
+    /// - The same errors as `ThreadPool::execute` otherwise.
+    pub fn execute<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let inner = self
+            .inner
+            .upgrade()
+            .filter(|inner| inner.handles.load(Ordering::Acquire) > 0)
+            .ok_or(ExecuteError::Shutdown)?;
+
+        inner.submit(
+            inner.pending.job(f),
+            Priority::Normal,
+            Backpressure::Reject,
+            None,
+        )
+    }
+}
+
+impl Pool for JobSender {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        self.send(job)
+    }
+}
+
+impl fmt::Debug for JobSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobSender")
+            .field(
+                "connected",
+                &self
+                    .inner
+                    .upgrade()
+                    .is_some_and(|inner| inner.handles.load(Ordering::Acquire) > 0),
+            )
+            .finish()
+    }
+}
@@ -1,31 +1,453 @@
+mod audit;
+mod barrier;
+mod builder;
+mod cancel;
+mod channel;
+mod config;
+mod external;
+mod fork_join;
+mod future;
+mod global;
+mod graph;
+mod guard;
+mod handle;
+mod hooks;
+mod job_sender;
+mod logging;
+mod metrics;
+mod micro;
+#[cfg(feature = "numa")]
+mod numa;
+mod panic_hook;
+mod pending;
+mod pool;
+mod propagate;
+mod queue;
+mod rate_limit;
+#[cfg(feature = "rayon-compat")]
+mod rayon_compat;
+mod retry;
+#[cfg(feature = "async-task")]
+mod schedule;
+mod scope;
+#[cfg(feature = "tower")]
+mod service;
+mod spawn_handle;
+mod state;
+mod tagged;
+mod testing;
+mod worker;
+mod worker_handle;
+
+pub use audit::{AuditEntry, Outcome};
+pub use barrier::PoolBarrier;
+pub use builder::{worker_count_for_parallelism, ThreadPoolBuilder};
+pub use cancel::{CancellationToken, Cancelled};
+pub use config::{ThreadPoolConfig, ThreadPoolConfigError};
+pub use fork_join::{ForkHandle, ForkJoinPool};
+pub use future::FuturePool;
+pub use global::global;
+pub use graph::{GraphError, TaskGraph, TaskId};
+pub use guard::WorkerGuard;
+pub use handle::{JoinError, JoinHandle, PanicError};
+pub use job_sender::JobSender;
+pub use metrics::{Histogram, JobMetrics, LabelStats, PoolSnapshot};
+pub use micro::{MicroTask, MicroTaskPool};
+#[cfg(feature = "numa")]
+pub use numa::{NUMAPool, NUMAPoolBuilder};
+pub use pool::Pool;
+pub use propagate::TraceContextPropagator;
+pub use queue::{Priority, ShedPolicy};
+pub use rate_limit::RateLimitedPool;
+#[cfg(feature = "rayon-compat")]
+pub use rayon_compat::join;
+pub use scope::Scope;
+pub use spawn_handle::SpawnHandle;
+pub use state::PoolState;
+pub use tagged::JobId;
+pub use testing::TestPool;
+pub use worker_handle::WorkerHandle;
+
+use hooks::Hooks;
+use logging::{log_error, log_info, log_warn};
+use metrics::Counters;
+use pending::{Pending, PendingGuard};
+use queue::{Backpressure, JobQueue};
+use retry::RetryJob;
+use tagged::JobRegistry;
+use worker::{Worker, WorkerConfig};
+
 use std::{
-    sync::{mpsc, Arc, Mutex},
+    any::Any,
+    collections::HashMap,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc, Mutex, MutexGuard, PoisonError, RwLock,
+    },
     thread,
+    time::{Duration, Instant},
+    vec,
 };
 
 /// ThreadPool struct
 ///
+/// A handle to a pool of Workers. Cloning it is cheap and gives another
+/// handle to the same Workers and queue, so a ThreadPool can be moved into
+/// closures or shared between threads without wrapping it in an `Arc`. The
+/// Workers are shut down once the last handle is dropped.
+///
+/// ThreadPool is `Send` and `Sync`, so `&ThreadPool` can be used from any
+/// thread as well.
+///
+/// Two ThreadPools are equal, and hash the same, when they are handles to
+/// the same Workers, e.g. one is a clone of the other. Their settings are
+/// never compared.
+///
 /// # Members
 ///
-/// - `workers` A vec containing all the Workers
-/// - `sender` A Sender to send Jobs to Workers
+/// - `inner` The state shared by every handle
 pub struct ThreadPool {
-    workers: Vec<Worker>,
-    sender: Option<mpsc::Sender<Job>>,
+    inner: Arc<Inner>,
+}
+
+/// Inner struct
+///
+/// The state of a ThreadPool, shared by all of its clones.
+///
+/// # Members
+///
+/// - `workers` A vec containing all the Workers that have been spawned
+/// - `size` The number of Workers the ThreadPool is configured to have
+/// - `on_demand` Whether Workers are spawned as jobs arrive, which is the
+///   case for lazy and elastic pools and whenever Workers can be evicted for
+///   being idle
+/// - `queue` The queue Jobs wait in until a Worker takes them
+/// - `pending` The number of submitted jobs that haven't finished yet
+/// - `counters` Utilization counters shared with the Workers
+/// - `config` The settings used to spawn new Workers
+/// - `next_id` The id given to the next Worker spawned
+/// - `jobs` The jobs submitted with an id that haven't started yet
+/// - `resizing` Held for the whole of a resize, so resizes don't overlap
+/// - `started` Whether a job has ever been submitted
+/// - `context` The value set with `set_default_context`, if any
+/// - `handles` The number of ThreadPool handles alive. The Inner can also
+///   be held for a moment by the autoscale thread or a JobSender, so the
+///   thread dropping the last handle shuts the Workers down rather than
+///   whichever thread lets go of the Inner last
+/// - `metrics` The registry the ThreadPool's metrics are exported to, with
+///   the `metrics` feature, and what to unregister from it when dropped
+struct Inner {
+    workers: Mutex<Vec<Worker>>,
+    size: AtomicUsize,
+    on_demand: bool,
+    queue: Arc<JobQueue>,
+    pending: Arc<Pending>,
+    counters: Arc<Counters>,
+    config: WorkerConfig,
+    next_id: AtomicUsize,
+    jobs: Arc<JobRegistry>,
+    resizing: Mutex<()>,
+    started: AtomicBool,
+    context: RwLock<Option<Arc<dyn Any + Send + Sync>>>,
+    handles: AtomicUsize,
+    #[cfg(feature = "metrics")]
+    metrics: Option<(prometheus::Registry, metrics::PoolCollector)>,
+}
+
+/// The number of jobs per Worker `ThreadPool::for_each` keeps submitted at
+/// once, so a Worker that finishes a job always has the next one waiting.
+const FOR_EACH_JOBS_PER_WORKER: usize = 2;
+
+/// How long the Workers above the minimum of a ThreadPool created with
+/// `ThreadPool::with_min_workers` wait for a job before exiting.
+const ELASTIC_KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/// A boxed function that can be sent to a Worker to be executed.
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+// ThreadPool is `Send` and `Sync` without any `unsafe impl`: every piece of
+// shared state is behind a Mutex, an RwLock, an atomic or a lock-free deque,
+// and jobs are only required to be `Send` because a job is only ever owned by
+// one thread at a time. These fail to compile if a future change adds a field
+// that breaks that, instead of silently making ThreadPool `!Send` or `!Sync`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    const fn assert_send<T: Send>() {}
+
+    assert_send_sync::<ThreadPool>();
+    assert_send_sync::<Inner>();
+    assert_send_sync::<JobQueue>();
+    assert_send_sync::<Worker>();
+    assert_send_sync::<Scope<'static, 'static>>();
+    assert_send::<Job>();
+};
+
+/// Errors that can occur while creating or resizing a ThreadPool.
+///
+/// # Variants
+///
+/// - `InvalidSize` The requested number of threads was 0.
+/// - `Spawn` The OS refused to spawn one of the worker threads.
+/// - `Shutdown` The ThreadPool has been shut down and can't be changed.
+/// - `ExternalThreads` The ThreadPool was created from existing threads,
+///   which it can't add to or remove.
+/// - `UnknownWorker` There is no Worker with the given id.
+/// - `WorkerStillAlive` The Worker with the given id is still running, so
+///   it can't be replaced.
+/// - `Metrics` The ThreadPool's metrics could not be registered, with the
+///   `metrics` feature.
+#[derive(Debug)]
+pub enum ThreadPoolError {
+    InvalidSize,
+    Spawn(io::Error),
+    Shutdown,
+    ExternalThreads,
+    UnknownWorker(usize),
+    WorkerStillAlive(usize),
+    #[cfg(feature = "metrics")]
+    Metrics(prometheus::Error),
+}
+
+impl fmt::Display for ThreadPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "thread pool size must be greater than 0"),
+            Self::Spawn(e) => write!(f, "unable to spawn worker thread: {e}"),
+            Self::Shutdown => write!(f, "thread pool has been shut down"),
+            Self::ExternalThreads => write!(f, "thread pool doesn't own its threads"),
+            Self::UnknownWorker(id) => write!(f, "no worker with id {id}"),
+            Self::WorkerStillAlive(id) => write!(f, "worker {id} is still alive"),
+            #[cfg(feature = "metrics")]
+            Self::Metrics(e) => write!(f, "unable to register metrics: {e}"),
+        }
+    }
+}
+
+impl Error for ThreadPoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidSize
+            | Self::Shutdown
+            | Self::ExternalThreads
+            | Self::UnknownWorker(_)
+            | Self::WorkerStillAlive(_) => None,
+            Self::Spawn(e) => Some(e),
+            #[cfg(feature = "metrics")]
+            Self::Metrics(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ThreadPoolError {
+    fn from(e: io::Error) -> Self {
+        Self::Spawn(e)
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl From<prometheus::Error> for ThreadPoolError {
+    fn from(e: prometheus::Error) -> Self {
+        Self::Metrics(e)
+    }
+}
+
+/// Errors that can occur while submitting a job to a ThreadPool.
+///
+/// # Variants
+///
+/// - `Shutdown` The ThreadPool has been shut down and no longer accepts jobs.
+/// - `Disconnected` Every Worker has died and no new ones will be spawned, so
+///   the job would never run.
+/// - `QueueFull` The queue is bounded and already holds as many jobs as it
+///   can.
+/// - `Timeout` The queue stayed full for the whole time the caller was
+///   willing to wait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecuteError {
+    Shutdown,
+    Disconnected,
+    QueueFull,
+    Timeout,
+}
+
+impl fmt::Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shutdown => write!(f, "thread pool has been shut down"),
+            Self::Disconnected => write!(f, "no workers are receiving jobs"),
+            Self::QueueFull => write!(f, "job queue is full"),
+            Self::Timeout => write!(f, "timed out waiting for space in the job queue"),
+        }
+    }
+}
+
+impl Error for ExecuteError {}
+
+/// Errors that can occur while submitting a job with `try_execute`.
+///
+/// # Variants
+///
+/// - `QueueFull` The queue is bounded and already holds as many jobs as it
+///   can.
+/// - `Shutdown` The ThreadPool has been shut down and no longer accepts jobs.
+/// - `Disconnected` Every Worker has died and no new ones will be spawned, so
+///   the job would never run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryExecuteError {
+    QueueFull,
+    Shutdown,
+    Disconnected,
+}
+
+impl fmt::Display for TryExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => write!(f, "job queue is full"),
+            Self::Shutdown => write!(f, "thread pool has been shut down"),
+            Self::Disconnected => write!(f, "no workers are receiving jobs"),
+        }
+    }
 }
 
-/// Worker struct
+impl Error for TryExecuteError {}
+
+/// Error returned when one or more Workers failed to shut down cleanly.
 ///
 /// # Members
 ///
-/// - `id` The id representing this Worker
-/// - `thread` The thread running the Job
-struct Worker {
-    id: usize,
-    thread: Option<thread::JoinHandle<()>>,
+/// - `worker_ids` The ids of the Workers whose threads panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownError {
+    worker_ids: Vec<usize>,
+}
+
+impl ShutdownError {
+    /// The ids of the Workers whose threads panicked.
+    pub fn worker_ids(&self) -> &[usize] {
+        &self.worker_ids
+    }
+}
+
+impl fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "workers {:?} panicked while shutting down",
+            self.worker_ids
+        )
+    }
+}
+
+impl Error for ShutdownError {}
+
+/// Errors that can occur while broadcasting a function to every Worker.
+///
+/// # Variants
+///
+/// - `Shutdown` The ThreadPool has been shut down and no longer accepts jobs.
+/// - `Panicked` The function panicked on this many Workers. It still ran on
+///   every other Worker.
+/// - `Disconnected` This many copies of the function were dropped without
+///   being run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastError {
+    Shutdown,
+    Panicked(usize),
+    Disconnected(usize),
+}
+
+impl fmt::Display for BroadcastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shutdown => write!(f, "thread pool has been shut down"),
+            Self::Panicked(count) => write!(f, "broadcast panicked on {count} workers"),
+            Self::Disconnected(count) => write!(f, "broadcast never ran on {count} workers"),
+        }
+    }
+}
+
+impl Error for BroadcastError {}
+
+/// Errors that can occur while mapping items with `ThreadPool::map` or
+/// `ThreadPool::for_each`.
+///
+/// # Variants
+///
+/// - `Execute` One of the jobs could not be submitted.
+/// - `Join` One of the jobs panicked or was dropped without being run.
+#[derive(Debug)]
+pub enum MapError {
+    Execute(ExecuteError),
+    Join(JoinError),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Execute(e) => write!(f, "unable to submit job: {e}"),
+            Self::Join(e) => write!(f, "unable to collect result: {e}"),
+        }
+    }
+}
+
+impl Error for MapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Execute(e) => Some(e),
+            Self::Join(e) => Some(e),
+        }
+    }
+}
+
+impl From<ExecuteError> for MapError {
+    fn from(e: ExecuteError) -> Self {
+        Self::Execute(e)
+    }
+}
+
+impl From<JoinError> for MapError {
+    fn from(e: JoinError) -> Self {
+        Self::Join(e)
+    }
+}
+
+/// Errors that can occur while moving jobs to another ThreadPool with
+/// `ThreadPool::transfer_jobs_to`.
+///
+/// # Variants
+///
+/// - `NotPaused` The ThreadPool the jobs would be taken from isn't paused,
+///   so its Workers could take them first.
+/// - `Execute` The other ThreadPool stopped accepting jobs. The jobs that
+///   weren't moved yet are back in the queue they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferError {
+    NotPaused,
+    Execute(ExecuteError),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotPaused => write!(f, "thread pool is not paused"),
+            Self::Execute(e) => write!(f, "unable to submit job: {e}"),
+        }
+    }
 }
 
-type Job = Box<dyn FnOnce() + Send + 'static>;
+impl Error for TransferError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::NotPaused => None,
+            Self::Execute(e) => Some(e),
+        }
+    }
+}
 
 impl ThreadPool {
     /// Create a new ThreadPool
@@ -34,103 +456,2557 @@ impl ThreadPool {
     ///
     /// # Panics
     ///
-    /// The `new` function will panic if size is less than or equal to 0.
+    /// The `new` function will panic if size is less than or equal to 0 or
+    /// if a worker thread could not be spawned. Use `try_new` to handle
+    /// these cases instead.
     pub fn new(size: usize) -> Self {
-        assert!(size > 0);
+        Self::try_new(size).expect("Unable to create the ThreadPool")
+    }
 
-        let mut workers = Vec::with_capacity(size);
-        let (tx, rx) = mpsc::channel();
-        let rx = Arc::new(Mutex::new(rx));
+    /// Create a new ThreadPool, returning an error instead of panicking.
+    ///
+    /// - `size` is the number of threads in the pool.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::InvalidSize` if size is 0.
+    /// - `ThreadPoolError::Spawn` if a worker thread could not be spawned,
+    ///   e.g. because the process has hit its limit on threads
+    ///   (`RLIMIT_NPROC` on Linux). The Workers spawned before it are shut
+    ///   down again, so a smaller ThreadPool can be tried instead.
+    pub fn try_new(size: usize) -> Result<Self, ThreadPoolError> {
+        ThreadPoolBuilder::new().num_threads(size).build()
+    }
 
-        for i in 0..size {
-            workers.push(Worker::new(i, Arc::clone(&rx)));
-        }
+    /// Create a ThreadPool with exactly `n` Workers, all spawned up front.
+    ///
+    /// The same as `new`, under a name that says what the number means.
+    ///
+    /// ```
+    /// let pool = mtserver::ThreadPool::with_workers(4);
+    ///
+    /// assert_eq!(pool.size(), 4);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the number of Workers.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0 or a worker thread could not be spawned.
+    pub fn with_workers(n: usize) -> Self {
+        Self::new(n)
+    }
 
-        Self {
-            workers,
-            sender: Some(tx),
-        }
+    /// Create a ThreadPool that grows and shrinks with the load.
+    ///
+    /// `min` Workers are spawned up front and are always alive. When a job
+    /// arrives and every live Worker is busy, another one is spawned, up to
+    /// `max`, and Workers above `min` exit once they have been idle for a
+    /// minute. Use the builder's `min_threads` and `keep_alive` for other
+    /// timeouts.
+    ///
+    /// ```
+    /// let pool = mtserver::ThreadPool::with_min_workers(1, 8);
+    ///
+    /// assert_eq!(pool.size(), 8);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// - `min` is the number of Workers that are always alive.
+    /// - `max` is the most Workers that are alive at once.
+    ///
+    /// # Panics
+    ///
+    /// If `max` is 0 or less than `min`, or a worker thread could not be
+    /// spawned.
+    pub fn with_min_workers(min: usize, max: usize) -> Self {
+        ThreadPoolBuilder::new()
+            .num_threads(max)
+            .min_threads(min)
+            .keep_alive(ELASTIC_KEEP_ALIVE)
+            .build()
+            .expect("Unable to create the ThreadPool")
+    }
+
+    /// Create a ThreadPool with a Worker per logical CPU, or 4 Workers if
+    /// the number of CPUs can't be determined. The same as `default`.
+    ///
+    /// ```
+    /// let pool = mtserver::ThreadPool::with_cpu_count();
+    ///
+    /// assert_eq!(pool.size(), mtserver::worker_count_for_parallelism(1.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If a worker thread could not be spawned.
+    pub fn with_cpu_count() -> Self {
+        Self::default()
+    }
+
+    /// Create a ThreadPoolBuilder to configure a new ThreadPool.
+    pub fn builder() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new()
     }
 
-    /// Sends the job into the channel for a Worker to execute.
+    /// Wrap the ThreadPool in an `Arc`, for APIs that take one.
+    ///
+    /// Cloning the ThreadPool already shares it without an `Arc`. Methods
+    /// can be called on the `Arc` directly, and it implements `Pool`.
+    pub fn into_arc(self) -> Arc<ThreadPool> {
+        Arc::new(self)
+    }
+
+    /// Create a ThreadPool that dispatches jobs to threads it didn't spawn.
+    ///
+    /// Each thread owns the receiving end of one of the channels and is
+    /// expected to `recv` jobs in a loop and call them. Since the threads
+    /// can't share the pool's queue, each job is sent to the next thread in
+    /// turn, skipping threads whose channel is full. When every channel is
+    /// full, `execute` fails with `ExecuteError::QueueFull` and
+    /// `execute_blocking` waits for the next thread in line.
+    ///
+    /// No threads are spawned. The pool can't see inside the threads, so
+    /// `active_count` and the other counters stay at 0, hooks are never
+    /// called, and the threads are never joined: shutting the pool down
+    /// drops the SyncSenders, which disconnects the threads' channels once
+    /// they have received every job already sent. `resize` and
+    /// `execute_after` are not supported, and `pause` has no effect.
     ///
     /// # Arguments
     ///
-    /// - `f` is the function to be executed.
+    /// - `senders` are the SyncSenders the threads receive jobs from, one
+    ///   per thread.
     ///
     /// # Panics
     ///
-    /// If the sender of the ThreadPool is invalidated or if there was an issue
-    /// putting the job into the channel.
-    pub fn execute<F>(&self, f: F)
+    /// The `from_existing_threads` function will panic if `senders` is
+    /// empty.
+    pub fn from_existing_threads(senders: Vec<mpsc::SyncSender<Job>>) -> Self {
+        assert!(!senders.is_empty(), "Unable to create the ThreadPool");
+
+        ThreadPoolBuilder::new().build_external(senders)
+    }
+
+    /// Puts the job in the queue for a Worker to execute.
+    ///
+    /// This never blocks. If the queue is bounded and full, the job is
+    /// rejected; use `execute_blocking` to wait for space instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ExecuteError::Disconnected` if every Worker has died.
+    /// - `ExecuteError::QueueFull` if the queue is bounded and full.
+    pub fn execute<F>(&self, f: F) -> Result<(), ExecuteError>
     where
         F: FnOnce() + Send + 'static,
     {
-        let job = Box::new(f);
+        self.inner.submit(
+            self.inner.pending.job(f),
+            Priority::Normal,
+            Backpressure::Reject,
+            None,
+        )
+    }
 
-        self.sender
-            .as_ref()
-            .expect("Unable to get the sender as a reference.")
-            .send(job)
-            .expect("Unable to put the job in the channel");
+    /// Puts a job in the queue without any of the checks and bookkeeping of
+    /// `execute`, for hot paths that submit millions of jobs a second.
+    ///
+    /// `execute` checks that the ThreadPool hasn't been shut down and still
+    /// has Workers, makes room in a bounded queue, spawns Workers for lazy
+    /// and elastic pools, counts the job as pending, and reads the clock and
+    /// the submitting thread for the job's queue wait and
+    /// `max_consecutive_jobs`. This skips all of that, so:
+    ///
+    /// - `join` and `wait_for_idle` don't wait for the job, although it is
+    ///   counted in `queued_count` and `active_count`. Wait for it some
+    ///   other way, e.g. with a channel.
+    /// - Its queue wait is neither recorded nor passed to `on_job_complete`,
+    ///   and the submitting thread's span and trace context aren't carried
+    ///   into it.
+    /// - Every unchecked job counts as coming from the same thread for
+    ///   `max_consecutive_jobs`.
+    ///
+    /// It is only meant for a ThreadPool that spawned all of its Workers up
+    /// front, so it isn't lazy or elastic, doesn't evict idle Workers, and
+    /// wasn't created with `from_existing_threads`, whose queue is unbounded
+    /// and which hasn't been shut down. Debug builds assert all of that.
+    /// Otherwise the job may never run, or a bounded queue grows past its
+    /// capacity; none of it is undefined behaviour. Measure before reaching
+    /// for it.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    pub fn execute_unchecked<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let queue = &self.inner.queue;
+
+        debug_assert!(!self.inner.on_demand, "Workers are spawned on demand");
+        debug_assert!(!queue.is_external(), "the ThreadPool has no Workers");
+        debug_assert!(queue.limits().0.is_none(), "the queue is bounded");
+        debug_assert!(!queue.is_closed(), "the ThreadPool has been shut down");
+
+        queue.push_unchecked(Box::new(f));
+        self.inner.mark_started();
     }
-}
 
-impl Drop for ThreadPool {
-    fn drop(&mut self) {
-        drop(self.sender.take());
+    /// Puts an already boxed job in the queue for a Worker to execute.
+    ///
+    /// Behaves like `execute`, for jobs that are already Jobs, such as those
+    /// returned by `drain_queue` or `shutdown_now`, or kept in a collection
+    /// of callbacks. The job is queued as it is, without being boxed again,
+    /// so it costs one allocation less than passing it to `execute`. With
+    /// the `tracing` feature or a trace propagator it is still wrapped, to
+    /// carry the submitting thread's span and trace context.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        let (job, guard) = self.inner.pending.boxed(job);
+
+        self.inner
+            .submit_guarded(job, guard, Priority::Normal, Backpressure::Reject, None)
+    }
 
-        for worker in &mut self.workers {
-            println!("Shutting down worker {}", worker.id);
+    /// Puts a job in the queue that is passed a shared context, such as a
+    /// configuration or a connection pool.
+    ///
+    /// The same as cloning the `Arc` and moving the clone into the job, but
+    /// says so at the call site, and the job can't take ownership of the
+    /// context by accident.
+    ///
+    /// # Arguments
+    ///
+    /// - `ctx` is the context to pass to `f`.
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_with_context<C, F>(&self, ctx: &Arc<C>, f: F) -> Result<(), ExecuteError>
+    where
+        C: Send + Sync + 'static,
+        F: FnOnce(&C) + Send + 'static,
+    {
+        let ctx = Arc::clone(ctx);
 
-            if let Some(thread) = worker.thread.take() {
-                thread
-                    .join()
-                    .unwrap_or_else(|_| println!("Error dropping {}", worker.id));
-            }
-        }
+        self.execute(move || f(&ctx))
     }
-}
 
-impl Worker {
-    /// Create a new worker.
+    /// Store a context every holder of the ThreadPool can fetch with
+    /// `default_context`, e.g. to pass to `execute_with_context`, instead
+    /// of keeping it next to the ThreadPool. Replaces any context set
+    /// before, whatever its type.
     ///
     /// # Arguments
     ///
-    /// - `id` is the ID corresponding to this Worker.
-    /// - `receiver` is the channel receiver for the Worker to get it's Job from.
+    /// - `ctx` is the context to store.
+    pub fn set_default_context<C>(&self, ctx: Arc<C>)
+    where
+        C: Send + Sync + 'static,
+    {
+        *self
+            .inner
+            .context
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(ctx);
+    }
+
+    /// The context stored with `set_default_context`.
     ///
     /// # Return
     ///
-    /// A new Worker struct
+    /// The context, or `None` if none has been set or it isn't a `C`.
+    pub fn default_context<C>(&self) -> Option<Arc<C>>
+    where
+        C: Send + Sync + 'static,
+    {
+        let ctx = self
+            .inner
+            .context
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()?;
+
+        ctx.downcast().ok()
+    }
+
+    /// Puts `n` jobs in the queue that each call `f`, e.g. for independent
+    /// trials of a simulation.
+    ///
+    /// `f` is shared between the jobs rather than cloned for each one.
+    /// Returns once the jobs are in the queue, without waiting for them to
+    /// run; see `execute_n_and_join` for that.
     ///
-    /// # Panics
+    /// # Arguments
     ///
-    /// This function will panic if another Worker or the ThreadPool panics
-    /// and causes the Mutex and/or Receiver to be invalidated.
-    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Self {
-        let thread = thread::spawn(move || loop {
-            let message = receiver
-                .lock()
-                .expect("Worker: {id} - The receiver was poisoned!")
-                .recv();
+    /// - `n` is the number of times to call `f`.
+    /// - `f` is the function to be called.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`, if one of the jobs could not be
+    /// submitted. The jobs submitted before it still run.
+    pub fn execute_n<F>(&self, n: usize, f: F) -> Result<(), ExecuteError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
 
-            match message {
-                Ok(job) => {
-                    println!("Worker {id} got a job; executing.");
+        for _ in 0..n {
+            let f = Arc::clone(&f);
 
-                    job();
-                }
-                Err(_) => {
-                    println!("Worker {id} disconnected; shutting down.");
-                    break;
-                }
-            }
-        });
+            self.execute(move || f())?;
+        }
 
-        Self {
-            id,
-            thread: Some(thread),
+        Ok(())
+    }
+
+    /// Like `execute_n`, but blocks the current thread until all `n` calls
+    /// of `f` have returned or panicked. Other jobs in the ThreadPool aren't
+    /// waited for.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is the number of times to call `f`.
+    /// - `f` is the function to be called.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`, if one of the jobs could not be
+    /// submitted, in which case nothing is waited for.
+    pub fn execute_n_and_join<F>(&self, n: usize, f: F) -> Result<(), ExecuteError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let pending = Arc::new(Pending::new());
+
+        for _ in 0..n {
+            let f = Arc::clone(&f);
+
+            self.execute(pending.job(move || f()))?;
         }
+
+        pending.wait();
+
+        Ok(())
+    }
+
+    /// Puts a `FnMut` closure in the queue, to be called once.
+    ///
+    /// `execute` already accepts any `FnMut`, since every `FnMut` is also a
+    /// `FnOnce`; this spells it out for closures that keep state between
+    /// calls. The closure and its state move into the job and are dropped
+    /// after the one call, so calling it repeatedly takes a new closure, or
+    /// shared state such as an `Arc<Mutex<_>>`, each time.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be called.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_mut<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.execute(f)
+    }
+
+    /// Tries to put the job in the queue without waiting for space.
+    ///
+    /// Meant for callers that must never wait for a Worker, such as game
+    /// loops or audio callbacks. If the queue is bounded and full, the job
+    /// is handed back as an error straight away. An unbounded queue is never
+    /// full, so this only fails if the ThreadPool can no longer run jobs.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// - `TryExecuteError::QueueFull` if the queue is bounded and full.
+    /// - `TryExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `TryExecuteError::Disconnected` if every Worker has died.
+    pub fn try_execute<F>(&self, f: F) -> Result<(), TryExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner
+            .submit(
+                self.inner.pending.job(f),
+                Priority::Normal,
+                Backpressure::Reject,
+                None,
+            )
+            .map_err(|e| match e {
+                // Rejecting never waits, so it never times out.
+                ExecuteError::QueueFull | ExecuteError::Timeout => TryExecuteError::QueueFull,
+                ExecuteError::Shutdown => TryExecuteError::Shutdown,
+                ExecuteError::Disconnected => TryExecuteError::Disconnected,
+            })
+    }
+
+    /// Puts the job in the queue, blocking until there is space for it.
+    ///
+    /// Only differs from `execute` when the queue is bounded.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ExecuteError::Disconnected` if every Worker has died.
+    pub fn execute_blocking<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner.submit(
+            self.inner.pending.job(f),
+            Priority::Normal,
+            Backpressure::Block,
+            None,
+        )
+    }
+
+    /// Puts the job in the queue, waiting up to `timeout` for space.
+    ///
+    /// This is the middle ground between `execute`, which never waits, and
+    /// `execute_blocking`, which may wait forever. Only differs from them
+    /// when the queue is bounded.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `timeout` is the longest to wait for space in the queue.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ExecuteError::Disconnected` if every Worker has died.
+    /// - `ExecuteError::Timeout` if the queue was still full after `timeout`.
+    pub fn execute_timeout<F>(&self, f: F, timeout: Duration) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let deadline = Instant::now() + timeout;
+
+        self.inner.submit(
+            self.inner.pending.job(f),
+            Priority::Normal,
+            Backpressure::Until(deadline),
+            None,
+        )
+    }
+
+    /// Puts the job in the queue, running it again each time it panics until
+    /// it succeeds or has been retried `max_retries` times.
+    ///
+    /// Meant for idempotent jobs, which is why `f` must be `Fn`. A retry goes
+    /// to the back of the queue, and is waited on by `join` like the first
+    /// attempt. Only the panic of the last attempt reaches the
+    /// `on_job_panic` callback.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `max_retries` is the number of times the job is run again if it
+    ///   panics.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`. Retries are never rejected, even if the
+    /// queue is full.
+    pub fn execute_with_retry<F>(&self, f: F, max_retries: u32) -> Result<(), ExecuteError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let retry = RetryJob::new(
+            Arc::new(f),
+            max_retries,
+            Arc::clone(&self.inner.queue),
+            Arc::clone(&self.inner.pending),
+        );
+
+        self.execute(move || retry.run())
+    }
+
+    /// Puts the job in the queue with the given priority.
+    ///
+    /// Workers always take the oldest job of the highest priority waiting,
+    /// so a `High` job submitted after a burst of `Low` jobs runs before the
+    /// `Low` jobs that haven't started yet.
+    ///
+    /// Every ThreadPool has a queue per priority, so this can be used on any
+    /// ThreadPool at any time. Jobs submitted with `execute` and the other
+    /// methods that don't take a Priority wait in the `Normal` queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `priority` is how urgently the job should be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_with_priority<F>(&self, f: F, priority: Priority) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner.submit(
+            self.inner.pending.job(f),
+            priority,
+            Backpressure::Reject,
+            None,
+        )
+    }
+
+    /// Puts the job in the queue so it runs after every job submitted
+    /// before it with the same `group_key`.
+    ///
+    /// Every job in a group is sent to the same Worker, picked by
+    /// `group_key` modulo the number of Workers, which runs them one at a
+    /// time in the order they were submitted. Jobs in different groups still
+    /// run in parallel, though two groups may share a Worker. The order is
+    /// only guaranteed while the set of Workers stays the same: resizing the
+    /// ThreadPool, spawning Workers lazily or evicting idle ones can move a
+    /// group to another Worker while its earlier jobs are still waiting.
+    ///
+    /// Like `broadcast`, these jobs don't count towards `channel_capacity`.
+    ///
+    /// # Arguments
+    ///
+    /// - `group_key` identifies the group the job belongs to.
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`, except `ExecuteError::QueueFull`.
+    pub fn execute_ordered<F>(&self, group_key: u64, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner.check_connected()?;
+        self.inner.spawn_first();
+        self.inner
+            .queue
+            .push_ordered(group_key, self.inner.pending.job(f))?;
+        self.inner.mark_started();
+
+        Ok(())
+    }
+
+    /// Puts the job in the queue, to be dropped instead of executed if no
+    /// Worker has started it by `deadline`.
+    ///
+    /// Meant for jobs whose result is worthless once it is late. Dropped
+    /// jobs are counted by `dropped_due_to_deadline` and don't trigger the
+    /// `on_job_complete` callback. A job that has started before the
+    /// deadline always runs to completion.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `deadline` is the latest time a Worker may start the job.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_with_deadline<F>(&self, f: F, deadline: Instant) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner.submit(
+            self.inner.pending.job(f),
+            Priority::Normal,
+            Backpressure::Reject,
+            Some(deadline),
+        )
+    }
+
+    /// Puts the job in the queue along with a CancellationToken it can check
+    /// to find out whether it should stop early.
+    ///
+    /// The job is given a clone of the returned token. Cancelling the token
+    /// doesn't interrupt the job or remove it from the queue; the job is
+    /// expected to check `is_cancelled`, or `check` with `?`, as it goes and
+    /// return early once it has been cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed, given its CancellationToken.
+    ///
+    /// # Return
+    ///
+    /// Whether the job was submitted, with the same errors as `execute`, and
+    /// the token that cancels it.
+    pub fn execute_with_token<F>(&self, f: F) -> (Result<(), ExecuteError>, CancellationToken)
+    where
+        F: FnOnce(CancellationToken) + Send + 'static,
+    {
+        let token = CancellationToken::new();
+        let job_token = token.clone();
+
+        (self.execute(move || f(job_token)), token)
+    }
+
+    /// Puts the job in the queue and returns an id that can be used to cancel
+    /// it with `cancel_job`.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Return
+    ///
+    /// The JobId of the submitted job.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_with_id<F>(&self, f: F) -> Result<JobId, ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let (id, tagged) = JobRegistry::register(&self.inner.jobs, Box::new(f));
+
+        self.execute(move || tagged.run())?;
+
+        Ok(id)
+    }
+
+    /// Puts the job in the queue tagged with a label saying what kind of
+    /// work it is, so `label_stats` can tell how many jobs of each kind ran
+    /// and how long they took.
+    ///
+    /// With the `tracing` feature, an event with the label is emitted when
+    /// the job starts and when it finishes.
+    ///
+    /// # Arguments
+    ///
+    /// - `label` says what kind of work the job is, e.g. `"resize_image"`.
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn execute_with_label<F>(&self, label: &'static str, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let counters = self.inner.counters.label(label);
+
+        self.execute(move || {
+            counters.started.fetch_add(1, Ordering::Relaxed);
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(label, "labelled job started");
+
+            let started = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            let run_duration = started.elapsed();
+
+            counters.finish(run_duration, result.is_err());
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                label,
+                run_duration = ?run_duration,
+                panicked = result.is_err(),
+                "labelled job finished"
+            );
+
+            // So the Worker counts and reports the panic like any other.
+            if let Err(payload) = result {
+                panic::resume_unwind(payload);
+            }
+        })
+    }
+
+    /// Puts the job in the queue once `not_before` has passed.
+    ///
+    /// The job is held back by the queue itself and handed to a Worker once
+    /// it becomes due, like any other job; no extra thread is spawned for
+    /// it. Like `execute_with_id`, the returned id can be used to cancel the
+    /// job with `cancel_job` until a Worker starts it. Deferred jobs that
+    /// aren't due yet when the ThreadPool shuts down are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `not_before` is the earliest time the job may start.
+    ///
+    /// # Return
+    ///
+    /// The JobId of the submitted job.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ExecuteError::Disconnected` if every Worker has died.
+    pub fn execute_after<F>(&self, f: F, not_before: Instant) -> Result<JobId, ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let (id, tagged) = JobRegistry::register(&self.inner.jobs, Box::new(f));
+
+        self.inner.check_connected()?;
+        self.inner
+            .queue
+            .push_after(self.inner.pending.job(move || tagged.run()), not_before)?;
+        self.inner.mark_started();
+        self.inner.spawn_lazily();
+
+        Ok(id)
+    }
+
+    /// Cancel a job submitted with `execute_with_id` or `execute_after` so it
+    /// never runs.
+    ///
+    /// Only jobs that are still waiting in the queue can be cancelled. A
+    /// cancelled job is skipped when a Worker takes it off the queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the JobId returned by `execute_with_id` or `execute_after`.
+    ///
+    /// # Return
+    ///
+    /// Whether the job was cancelled. `false` if it has already started,
+    /// finished, been cancelled or been discarded.
+    pub fn cancel_job(&self, id: JobId) -> bool {
+        self.inner.jobs.cancel(id)
+    }
+
+    /// Puts every job in the queue at once.
+    ///
+    /// Idle Workers are woken only once for the whole batch, which is cheaper
+    /// than calling `execute` for each job. If the queue is bounded, jobs are
+    /// added until it is full and the rest are dropped without being run.
+    ///
+    /// # Arguments
+    ///
+    /// - `jobs` are the functions to be executed.
+    ///
+    /// # Return
+    ///
+    /// The number of jobs that were put in the queue.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ExecuteError::Disconnected` if every Worker has died.
+    pub fn execute_batch<F>(&self, jobs: impl IntoIterator<Item = F>) -> Result<usize, ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.inner.check_connected()?;
+
+        let count = self.inner.queue.push_batch(
+            jobs.into_iter().map(|f| self.inner.pending.job(f)),
+            Priority::Normal,
+        )?;
+
+        self.inner.mark_started();
+        self.inner.spawn_lazily();
+
+        Ok(count)
+    }
+
+    /// Puts a known number of already boxed jobs in the queue at once.
+    ///
+    /// Like `execute_batch`, but since the number of jobs is known up
+    /// front, room is made in the queue for all of them with a single atomic
+    /// operation instead of one per job. If the queue is bounded, jobs are
+    /// added until it is full and the rest are dropped without being run.
+    ///
+    /// # Arguments
+    ///
+    /// - `jobs` are the Jobs to be executed.
+    ///
+    /// # Return
+    ///
+    /// The number of jobs that were put in the queue.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute_batch`.
+    pub fn execute_many<I>(&self, jobs: I) -> Result<usize, ExecuteError>
+    where
+        I: ExactSizeIterator<Item = Job>,
+    {
+        self.inner.check_connected()?;
+
+        let mut boxed = Vec::with_capacity(jobs.len());
+
+        boxed.extend(jobs.map(|job| self.inner.pending.job(job)));
+
+        let count = self.inner.queue.push_many(boxed, Priority::Normal)?;
+
+        self.inner.mark_started();
+        self.inner.spawn_lazily();
+
+        Ok(count)
+    }
+
+    /// Puts the job in the queue and returns a handle to its result.
+    ///
+    /// Unlike `execute`, a panic inside the job is caught and handed to
+    /// whoever joins the returned JoinHandle rather than unwinding the
+    /// Worker's thread.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Return
+    ///
+    /// A JoinHandle that can be used to wait on the value returned by `f`.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn spawn<F, T>(&self, f: F) -> Result<JoinHandle<T>, ExecuteError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = channel::channel();
+
+        self.execute(move || {
+            // The JoinHandle may have been dropped, which is fine.
+            let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(f)));
+        })?;
+
+        Ok(JoinHandle::new(rx))
+    }
+
+    /// Puts the job in the queue and returns a future of its result, for
+    /// awaiting it from async code.
+    ///
+    /// Behaves like `spawn`, except that the SpawnHandle is awaited rather
+    /// than joined, so no thread blocks while the job runs. Any executor can
+    /// await it, or none: the job doesn't need the SpawnHandle to be polled
+    /// to make progress.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Return
+    ///
+    /// A SpawnHandle that resolves to the value returned by `f`, or to a
+    /// `JoinError` if it panicked or was dropped without being run.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`.
+    pub fn spawn_async<F, T>(&self, f: F) -> Result<SpawnHandle<T>, ExecuteError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (completer, handle) = spawn_handle::spawn_handle();
+
+        self.execute(move || completer.complete(panic::catch_unwind(AssertUnwindSafe(f))))?;
+
+        Ok(handle)
+    }
+
+    /// Runs a copy of `f` on every Worker's thread, exactly once each.
+    ///
+    /// This is useful for setting up thread-local state, such as a per-thread
+    /// connection or random number generator. Each copy is sent straight to
+    /// its Worker rather than through the shared queue, so a busy Worker runs
+    /// it after its current job. The current thread blocks until every copy
+    /// has run.
+    ///
+    /// Only Workers that are alive when this is called run `f`. Workers that
+    /// are spawned later, e.g. by a lazily spawned ThreadPool, don't.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to run on every Worker.
+    ///
+    /// # Errors
+    ///
+    /// - `BroadcastError::Shutdown` if the ThreadPool has been shut down.
+    /// - `BroadcastError::Panicked` if `f` panicked on any Worker.
+    /// - `BroadcastError::Disconnected` if a copy of `f` was dropped without
+    ///   being run.
+    pub fn broadcast<F>(&self, f: F) -> Result<(), BroadcastError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (tx, rx) = channel::channel();
+
+        let count = self
+            .inner
+            .queue
+            .broadcast(|| {
+                let f = Arc::clone(&f);
+                let tx = tx.clone();
+
+                self.inner.pending.job(move || {
+                    let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(|| f())).is_ok());
+                })
+            })
+            .map_err(|_| BroadcastError::Shutdown)?;
+
+        self.inner.mark_started();
+        drop(tx);
+
+        let results: Vec<bool> = rx.iter().collect();
+        let panicked = results.iter().filter(|&&ok| !ok).count();
+
+        if panicked > 0 {
+            Err(BroadcastError::Panicked(panicked))
+        } else if results.len() < count {
+            Err(BroadcastError::Disconnected(count - results.len()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run a job that does nothing on every Worker's thread, and wait for
+    /// them all, so the first real jobs don't pay for whatever each thread
+    /// sets up the first time it runs a job.
+    ///
+    /// This is `broadcast(|| {})`. Like `broadcast`, it only reaches the
+    /// Workers that are alive when it is called, and does nothing once the
+    /// ThreadPool has been shut down.
+    pub fn warm_up(&self) {
+        // A job that does nothing can't panic, so the only error is that
+        // there are no Workers to warm up.
+        let _ = self.broadcast(|| {});
+    }
+
+    /// Create a scope for submitting jobs that borrow from the caller.
+    ///
+    /// `f` is given a Scope, and jobs submitted through it may borrow any
+    /// non-`'static` data that outlives the call to `scope`, just like
+    /// `std::thread::scope`. The current thread blocks until every job
+    /// submitted through the Scope has finished before returning.
+    ///
+    /// Calling this from inside a job can deadlock if every other Worker is
+    /// busy, since no Worker may be left to run the scoped jobs.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function that submits the scoped jobs.
+    ///
+    /// # Return
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is resumed once every scoped job has
+    /// finished. Otherwise, this panics if any of the scoped jobs panicked.
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        let scope = Scope::new(self);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        let panicked = scope.wait();
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(_) if panicked => panic!("A scoped job panicked"),
+            Ok(value) => value,
+        }
+    }
+
+    /// Applies `f` to consecutive chunks of `data` in parallel, one job per
+    /// chunk.
+    ///
+    /// Lower level than `map`, for data-parallel work where the chunk size
+    /// matters, e.g. to keep each job within a cache. Every chunk has
+    /// `chunk_size` items except the last, which gets what is left. The jobs
+    /// borrow `data` and `f` like scoped jobs do, and the current thread
+    /// blocks until all of them have finished. Like `scope`, calling this
+    /// from inside a job can deadlock if every other Worker is busy.
+    ///
+    /// # Arguments
+    ///
+    /// - `data` is the slice to split into chunks.
+    /// - `chunk_size` is the number of items per chunk. It is raised to 1 if
+    ///   it is 0.
+    /// - `f` is the function applied to each chunk.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute`, if a chunk could not be submitted. The
+    /// chunks submitted before it are still waited for.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panicked for any chunk, once every chunk has finished.
+    pub fn execute_chunked<T, F>(
+        &self,
+        data: &[T],
+        chunk_size: usize,
+        f: F,
+    ) -> Result<(), ExecuteError>
+    where
+        T: Sync,
+        F: Fn(&[T]) + Sync,
+    {
+        let f = &f;
+
+        self.scope(|s| {
+            for chunk in data.chunks(chunk_size.max(1)) {
+                s.execute(move || f(chunk))?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Run `f` with a child ThreadPool of its own, for jobs that must not
+    /// wait behind this ThreadPool's queue.
+    ///
+    /// The child ThreadPool has `num_threads` Workers and the same settings
+    /// as this one, e.g. the stack size, but shares nothing else with it. A
+    /// job that splits its work into sub-jobs can submit them to the child,
+    /// which can't deadlock the way submitting them to a busy parent can.
+    /// Once `f` returns, the child is shut down, which waits for every job
+    /// submitted to it to finish.
+    ///
+    /// # Arguments
+    ///
+    /// - `num_threads` is the number of Workers of the child ThreadPool.
+    /// - `f` is the function that submits jobs to the child ThreadPool.
+    ///
+    /// # Return
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// If `num_threads` is 0 or a worker thread could not be spawned, like
+    /// `new`. If `f` panics, the child ThreadPool is shut down before the
+    /// panic carries on.
+    pub fn scope_with_pool<F, T>(&self, num_threads: usize, f: F) -> T
+    where
+        F: FnOnce(&ThreadPool) -> T,
+    {
+        let child = ThreadPoolBuilder::like(&self.inner, 0, num_threads)
+            .build()
+            .expect("Unable to create the ThreadPool");
+        let result = f(&child);
+
+        // Worker panics have already been logged, as when it is dropped.
+        let _ = child.shutdown();
+
+        result
+    }
+
+    /// Applies `f` to every item in parallel and collects the results.
+    ///
+    /// One job is submitted per item, and the current thread blocks until
+    /// every job has finished. This behaves like `Iterator::map` followed by
+    /// `collect`, except that the items are processed by the Workers.
+    ///
+    /// # Arguments
+    ///
+    /// - `items` are the values to pass to `f`.
+    /// - `f` is the function applied to each item.
+    ///
+    /// # Return
+    ///
+    /// The results, in the same order as the items they were computed from.
+    ///
+    /// # Errors
+    ///
+    /// - `MapError::Execute` if a job could not be submitted. Jobs submitted
+    ///   before it still run, but their results are discarded.
+    /// - `MapError::Join` if a job panicked or was dropped without being run.
+    pub fn map<I, F, T>(&self, items: I, f: F) -> Result<Vec<T>, MapError>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) -> T + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let f = Arc::new(f);
+        let (tx, rx) = channel::channel();
+        let mut count = 0;
+
+        for (index, item) in items.into_iter().enumerate() {
+            let f = Arc::clone(&f);
+            let tx = tx.clone();
+
+            self.execute(move || {
+                let _ = tx.send((index, panic::catch_unwind(AssertUnwindSafe(|| f(item)))));
+            })?;
+
+            count += 1;
+        }
+
+        drop(tx);
+
+        let mut results = Vec::with_capacity(count);
+
+        for (index, result) in rx.iter() {
+            let value = result.map_err(|payload| JoinError::Panicked(PanicError(payload)))?;
+
+            results.push((index, value));
+        }
+
+        if results.len() < count {
+            return Err(JoinError::Disconnected.into());
+        }
+
+        results.sort_unstable_by_key(|&(index, _)| index);
+
+        Ok(results.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /// Applies `f` to every item and its index in parallel and collects the
+    /// results.
+    ///
+    /// Behaves like `map` over `items.into_iter().enumerate()`, which saves
+    /// pairing each item with its index by hand when the results are
+    /// written back into a slice.
+    ///
+    /// # Arguments
+    ///
+    /// - `items` are the values to pass to `f`.
+    /// - `f` is the function applied to each item, along with its index.
+    ///
+    /// # Return
+    ///
+    /// The results, in the same order as the items they were computed from.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `map`.
+    pub fn map_with_index<I, F, T>(&self, items: I, f: F) -> Result<Vec<T>, MapError>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(usize, I::Item) -> T + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        self.map(items.into_iter().enumerate(), move |(index, item)| {
+            f(index, item)
+        })
+    }
+
+    /// Maps every item in parallel and combines the results with `reduce`.
+    ///
+    /// The items are split into one chunk per Worker, the last chunk taking
+    /// whatever doesn't divide evenly, and one job per chunk maps its items
+    /// and reduces them in order. The current thread then reduces `init` and
+    /// the result of each chunk, also in order, so `reduce` only needs to be
+    /// associative, not commutative: joining strings works as well as
+    /// summing numbers.
+    ///
+    /// # Arguments
+    ///
+    /// - `items` are the values to pass to `map`.
+    /// - `map` is the function applied to each item.
+    /// - `reduce` combines two results into one.
+    /// - `init` is the value to start from, and the result if there are no
+    ///   items.
+    ///
+    /// # Return
+    ///
+    /// `init` reduced with the mapped value of every item.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `map`.
+    pub fn reduce<I, F, T, R>(&self, items: I, map: F, reduce: R, init: T) -> Result<T, MapError>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) -> T + Send + Sync + 'static,
+        R: Fn(T, T) -> T + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let mut items = items.into_iter().collect::<Vec<_>>().into_iter();
+        let chunks = self.size().min(items.len());
+
+        if chunks == 0 {
+            return Ok(init);
+        }
+
+        let chunk_len = items.len() / chunks;
+        let mut parts: Vec<Vec<I::Item>> = (1..chunks)
+            .map(|_| items.by_ref().take(chunk_len).collect())
+            .collect();
+
+        parts.push(items.collect());
+
+        let reduce = Arc::new(reduce);
+        let chunk_reduce = Arc::clone(&reduce);
+
+        let results = self.map(parts, move |part| {
+            part.into_iter().map(&map).reduce(|a, b| chunk_reduce(a, b))
+        })?;
+
+        Ok(results
+            .into_iter()
+            .flatten()
+            .fold(init, |a, b| reduce(a, b)))
+    }
+
+    /// Applies `f` to every item in parallel, discarding the results.
+    ///
+    /// Unlike `map`, items are taken from the iterator only as Workers free
+    /// up: at most two jobs per Worker are submitted at a time, so a huge
+    /// iterator is never queued all at once. The current thread blocks until
+    /// every job has finished. Once a job panics, no more items are taken.
+    ///
+    /// # Arguments
+    ///
+    /// - `items` are the values to pass to `f`.
+    /// - `f` is the function applied to each item.
+    ///
+    /// # Errors
+    ///
+    /// - `MapError::Execute` if a job could not be submitted. Jobs submitted
+    ///   before it still run, but aren't waited for.
+    /// - `MapError::Join` if a job panicked or was dropped without being run.
+    pub fn for_each<I, F>(&self, items: I, f: F) -> Result<(), MapError>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let pending = Arc::new(Pending::with_limit(self.size() * FOR_EACH_JOBS_PER_WORKER));
+        let first_panic = Arc::new(Mutex::new(None));
+        let ran = Arc::new(AtomicUsize::new(0));
+        let mut count = 0;
+
+        for item in items {
+            pending.wait_for_slot();
+
+            if first_panic
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some()
+            {
+                break;
+            }
+
+            let f = Arc::clone(&f);
+            let first_panic = Arc::clone(&first_panic);
+            let ran = Arc::clone(&ran);
+
+            self.execute(pending.job(move || {
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(item))) {
+                    first_panic
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .get_or_insert(PanicError(payload));
+                }
+
+                ran.fetch_add(1, Ordering::Relaxed);
+            }))?;
+
+            count += 1;
+        }
+
+        pending.wait();
+
+        if let Some(e) = first_panic
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            return Err(JoinError::Panicked(e).into());
+        }
+
+        if ran.load(Ordering::Relaxed) < count {
+            return Err(JoinError::Disconnected.into());
+        }
+
+        Ok(())
+    }
+
+    /// The number of Workers the ThreadPool is configured to have.
+    ///
+    /// For a lazily spawned ThreadPool this includes the Workers that have
+    /// not been spawned yet.
+    pub fn size(&self) -> usize {
+        self.inner.size.load(Ordering::Relaxed)
+    }
+
+    /// The number of Workers currently executing a job.
+    ///
+    /// This is a best-effort snapshot and may already be out of date by the
+    /// time it is returned. It is not guaranteed to be consistent with
+    /// `idle_count` or `queued_count`.
+    pub fn active_count(&self) -> usize {
+        self.inner.counters.active.load(Ordering::Relaxed)
+    }
+
+    /// The number of Workers waiting for a job.
+    ///
+    /// This is a best-effort snapshot, computed as the number of live
+    /// Workers minus `active_count`.
+    pub fn idle_count(&self) -> usize {
+        self.inner.live_count().saturating_sub(self.active_count())
+    }
+
+    /// The number of jobs waiting in the queue for a Worker.
+    ///
+    /// This is a best-effort snapshot. Jobs that are being executed are not
+    /// counted.
+    pub fn queued_count(&self) -> usize {
+        self.inner.queue.len()
+    }
+
+    /// The maximum number of jobs that can wait in the queue, as set with
+    /// `ThreadPoolBuilder::channel_capacity`.
+    ///
+    /// Together with `queued_count`, this tells how close the queue is to
+    /// rejecting or shedding jobs, e.g. to shed load earlier.
+    ///
+    /// # Return
+    ///
+    /// The capacity, or `None` if the queue is unbounded or the ThreadPool
+    /// was created with `from_existing_threads`, whose channels have
+    /// capacities of their own.
+    pub fn capacity(&self) -> Option<usize> {
+        self.inner.queue.limits().0
+    }
+
+    /// The number of jobs submitted with `execute_with_deadline` that were
+    /// dropped because their deadline passed before a Worker took them.
+    pub fn dropped_due_to_deadline(&self) -> usize {
+        self.inner.counters.expired.load(Ordering::Relaxed)
+    }
+
+    /// The number of jobs dropped because the queue was full, under
+    /// `ShedPolicy::DropNewest` or `ShedPolicy::DropOldest`.
+    pub fn shed_count(&self) -> usize {
+        self.inner.queue.shed_count()
+    }
+
+    /// Whether the ThreadPool is accepting jobs and none of its Workers has
+    /// died.
+    ///
+    /// Meant for liveness probes. Workers retired by `resize` or evicted for
+    /// being idle don't count as dead. A ThreadPool created with
+    /// `from_existing_threads` can't check on its threads, so it is healthy
+    /// until it is shut down.
+    pub fn is_healthy(&self) -> bool {
+        !self.inner.queue.is_closed() && self.unhealthy_worker_ids().is_empty()
+    }
+
+    /// The ids of the Workers whose threads have died, e.g. because their
+    /// loop crashed more than `max_restarts` times.
+    pub fn unhealthy_worker_ids(&self) -> Vec<usize> {
+        self.inner
+            .lock_workers()
+            .iter()
+            .filter(|worker| worker.is_dead())
+            .map(|worker| worker.id)
+            .collect()
+    }
+
+    /// Spawn a new Worker in place of one whose thread has stopped.
+    ///
+    /// Meant to be paired with `unhealthy_worker_ids` for targeted restarts.
+    /// The new Worker gets a new id, so logs never mix up the two, but takes
+    /// over the old one's share of the jobs submitted with
+    /// `execute_ordered`. Its thread runs the `on_thread_start` hook like
+    /// any other.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker to replace.
+    ///
+    /// # Return
+    ///
+    /// The id of the new Worker.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ThreadPoolError::UnknownWorker` if there is no Worker with `id`.
+    /// - `ThreadPoolError::WorkerStillAlive` if the Worker's thread is still
+    ///   running.
+    /// - `ThreadPoolError::Spawn` if the new thread could not be spawned.
+    pub fn replace_worker(&self, id: usize) -> Result<usize, ThreadPoolError> {
+        if self.inner.queue.is_closed() {
+            return Err(ThreadPoolError::Shutdown);
+        }
+
+        let _resizing = self
+            .inner
+            .resizing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let mut workers = self.inner.lock_workers();
+        let worker = workers
+            .iter_mut()
+            .find(|worker| worker.id == id)
+            .ok_or(ThreadPoolError::UnknownWorker(id))?;
+
+        match worker.thread.take() {
+            Some(thread) if !thread.is_finished() => {
+                worker.thread = Some(thread);
+                return Err(ThreadPoolError::WorkerStillAlive(id));
+            }
+            Some(thread) => {
+                thread
+                    .join()
+                    .unwrap_or_else(|_| log_error!("Error dropping {id}"));
+            }
+            None => {}
+        }
+
+        self.inner.counters.live.fetch_add(1, Ordering::Relaxed);
+
+        let new_id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        *worker = Worker::new(
+            new_id,
+            worker.slot,
+            &self.inner.config,
+            Arc::clone(&self.inner.queue),
+            Arc::clone(&self.inner.counters),
+        )
+        .inspect_err(|_| {
+            self.inner.counters.live.fetch_sub(1, Ordering::Relaxed);
+        })?;
+
+        log_info!("Replaced worker {id} with worker {new_id}");
+
+        Ok(new_id)
+    }
+
+    /// Whether the Worker with the given id is still running.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker, e.g. from iterating over `&pool`.
+    ///
+    /// # Return
+    ///
+    /// `false` once the Worker has exited or been retired, or if no Worker
+    /// ever had the id.
+    pub fn worker_is_alive(&self, id: usize) -> bool {
+        self.inner
+            .lock_workers()
+            .iter()
+            .any(|worker| worker.id == id && worker.is_alive())
+    }
+
+    /// How long the jobs executed so far waited in the queue before a
+    /// Worker started them.
+    ///
+    /// A growing tail, e.g. in `Histogram::p99`, means jobs are arriving
+    /// faster than the Workers can keep up with, even while the average
+    /// wait looks fine. Jobs dropped for being past their deadline aren't
+    /// counted. Jobs from `execute_after` count from when they were due.
+    pub fn queue_wait_histogram(&self) -> Histogram {
+        self.inner.counters.queue_wait.snapshot()
+    }
+
+    /// How many jobs of each label given to `execute_with_label` have run,
+    /// and how long they took.
+    ///
+    /// # Return
+    ///
+    /// The LabelStats of every label used so far, including labels whose
+    /// jobs haven't started yet.
+    pub fn label_stats(&self) -> HashMap<&'static str, LabelStats> {
+        self.inner.counters.label_stats()
+    }
+
+    /// The jobs recorded in the audit log, oldest first.
+    ///
+    /// # Return
+    ///
+    /// The last jobs to finish, up to the capacity given to
+    /// `ThreadPoolBuilder::with_audit_log`, or an empty Vec if the
+    /// ThreadPool keeps no audit log.
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.inner
+            .config
+            .hooks
+            .audit_log
+            .as_ref()
+            .map_or_else(Vec::new, |log| log.snapshot())
+    }
+
+    /// A point-in-time view of the ThreadPool's size, utilization and job
+    /// totals, for logging or alerting.
+    ///
+    /// This is a best-effort snapshot; see `PoolSnapshot`.
+    pub fn snapshot(&self) -> PoolSnapshot {
+        let counters = &self.inner.counters;
+        let (per_worker_idle_ns, per_worker_busy_ns) = self
+            .inner
+            .lock_workers()
+            .iter()
+            .filter(|worker| worker.is_alive())
+            .map(|worker| {
+                (
+                    worker.times.idle_ns.load(Ordering::Relaxed),
+                    worker.times.busy_ns.load(Ordering::Relaxed),
+                )
+            })
+            .unzip();
+
+        PoolSnapshot {
+            size: self.size(),
+            active: self.active_count(),
+            idle: self.idle_count(),
+            queued: self.queued_count(),
+            total_completed: counters.completed.load(Ordering::Relaxed),
+            total_panics: counters.panicked.load(Ordering::Relaxed),
+            per_worker_idle_ns,
+            per_worker_busy_ns,
+        }
+    }
+
+    /// Grow or shrink the ThreadPool to the given number of Workers.
+    ///
+    /// Growing spawns new Workers that share the existing queue, unless the
+    /// ThreadPool spawns its Workers on demand, in which case they are
+    /// spawned as jobs arrive. Shrinking puts a terminate message on the
+    /// queue for each Worker to be removed, so only idle Workers retire and
+    /// any job a Worker is already executing is allowed to finish. The
+    /// current thread blocks until the retired Workers have exited.
+    ///
+    /// Resizing affects every clone of the ThreadPool. Concurrent calls are
+    /// applied one after the other.
+    ///
+    /// # Arguments
+    ///
+    /// - `new_size` is the number of Workers the ThreadPool should have.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::InvalidSize` if `new_size` is 0.
+    /// - `ThreadPoolError::Spawn` if a new worker thread could not be spawned.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ThreadPoolError::ExternalThreads` if the ThreadPool was created
+    ///   with `from_existing_threads`.
+    pub fn resize(&self, new_size: usize) -> Result<(), ThreadPoolError> {
+        self.inner.resize(new_size)
+    }
+
+    /// Resize the ThreadPool to one Worker per CPU the process may use.
+    ///
+    /// `std::thread::available_parallelism` can change while the process
+    /// runs, e.g. when the CPU quota of a container's cgroup is changed, so
+    /// this can be called again to follow it. See also
+    /// `ThreadPoolBuilder::autoscale`, which calls it periodically.
+    ///
+    /// # Return
+    ///
+    /// The new number of Workers. It is left as it was if the available
+    /// parallelism can't be determined.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `resize`.
+    pub fn resize_to_parallelism(&self) -> Result<usize, ThreadPoolError> {
+        self.inner.resize_to_parallelism()
+    }
+
+    /// Move an idle Worker from another ThreadPool to this one.
+    ///
+    /// Meant for balancing load between the ThreadPools of different parts
+    /// of a service. A thread can't move between queues, so this retires an
+    /// idle Worker of `other` and spawns one in this ThreadPool instead:
+    /// `other` shrinks by one and this ThreadPool grows by one, as if both
+    /// had been resized. The current thread blocks until the retired Worker
+    /// has exited.
+    ///
+    /// # Arguments
+    ///
+    /// - `other` is the ThreadPool to take the Worker from.
+    ///
+    /// # Return
+    ///
+    /// Whether a Worker was moved. Nothing is moved if `other` has no idle
+    /// Workers, would be left with none, or is this ThreadPool.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Spawn` if the new thread could not be spawned.
+    ///   `other` has already shrunk by then.
+    /// - `ThreadPoolError::Shutdown` if either ThreadPool has been shut down.
+    /// - `ThreadPoolError::ExternalThreads` if either ThreadPool was created
+    ///   with `from_existing_threads`.
+    pub fn steal_from(&self, other: &ThreadPool) -> Result<bool, ThreadPoolError> {
+        if Arc::ptr_eq(&self.inner, &other.inner) {
+            return Ok(false);
+        }
+
+        if self.inner.queue.is_external() || other.inner.queue.is_external() {
+            return Err(ThreadPoolError::ExternalThreads);
+        }
+
+        if self.inner.queue.is_closed() || other.inner.queue.is_closed() {
+            return Err(ThreadPoolError::Shutdown);
+        }
+
+        // Always lock in the same order, so two threads stealing in opposite
+        // directions can't deadlock.
+        let (first, second) = if Arc::as_ptr(&self.inner) < Arc::as_ptr(&other.inner) {
+            (&self.inner, &other.inner)
+        } else {
+            (&other.inner, &self.inner)
+        };
+        let _first = first
+            .resizing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let _second = second
+            .resizing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let other_size = other.size();
+
+        if other_size <= 1 || other.idle_count() == 0 {
+            return Ok(false);
+        }
+
+        other.inner.resize_to(other_size - 1)?;
+        self.inner.resize_to(self.size() + 1)?;
+
+        Ok(true)
+    }
+
+    /// Divide the ThreadPool into two independent ThreadPools, e.g. to keep
+    /// latency-sensitive jobs clear of a backlog of bulk ones.
+    ///
+    /// The first ThreadPool is this one, shrunk to `first_size` Workers as
+    /// if it had been resized, and keeps the jobs already in the queue and
+    /// any other handles to it. The second one is new, with the remaining
+    /// Workers and its own empty queue, and the same settings except for the
+    /// metrics. With the `affinity` feature, its Workers start at the core
+    /// after the first ThreadPool's last one. The current thread blocks until
+    /// the retired Workers have exited.
+    ///
+    /// # Arguments
+    ///
+    /// - `first_size` is the number of Workers of the first ThreadPool.
+    ///
+    /// # Return
+    ///
+    /// The two ThreadPools, with `first_size` and `size - first_size`
+    /// Workers.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::InvalidSize` if `first_size` is 0 or is not less
+    ///   than the ThreadPool's size, which would leave one of them empty.
+    /// - `ThreadPoolError::Spawn` if a thread of the second ThreadPool could
+    ///   not be spawned. This ThreadPool is left as it was.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ThreadPoolError::ExternalThreads` if the ThreadPool was created
+    ///   with `from_existing_threads`.
+    pub fn split(self, first_size: usize) -> Result<(ThreadPool, ThreadPool), ThreadPoolError> {
+        if self.inner.queue.is_external() {
+            return Err(ThreadPoolError::ExternalThreads);
+        }
+
+        if self.inner.queue.is_closed() {
+            return Err(ThreadPoolError::Shutdown);
+        }
+
+        let second = {
+            let _resizing = self
+                .inner
+                .resizing
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let size = self.size();
+
+            if first_size == 0 || first_size >= size {
+                return Err(ThreadPoolError::InvalidSize);
+            }
+
+            let second =
+                ThreadPoolBuilder::like(&self.inner, first_size, size - first_size).build()?;
+            self.inner.resize_to(first_size)?;
+
+            second
+        };
+
+        Ok((self, second))
+    }
+
+    /// Add a Worker to the ThreadPool for as long as the returned
+    /// WorkerGuard is alive, e.g. to get through a burst of jobs.
+    ///
+    /// The Worker comes on top of the ThreadPool's size, which stays the
+    /// same, and takes jobs from the queue like any other Worker. When the
+    /// WorkerGuard is dropped, that Worker, and no other, exits once it is
+    /// idle. Resizing or shrinking the ThreadPool meanwhile may retire it
+    /// early, in which case dropping the WorkerGuard does nothing.
+    ///
+    /// # Return
+    ///
+    /// A WorkerGuard that retires the Worker when dropped.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Spawn` if the worker thread could not be spawned.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    /// - `ThreadPoolError::ExternalThreads` if the ThreadPool was created
+    ///   with `from_existing_threads`.
+    pub fn temporary_worker(&self) -> Result<WorkerGuard<'_>, ThreadPoolError> {
+        if self.inner.queue.is_external() {
+            return Err(ThreadPoolError::ExternalThreads);
+        }
+
+        if self.inner.queue.is_closed() {
+            return Err(ThreadPoolError::Shutdown);
+        }
+
+        let id = self.inner.spawn_worker()?;
+
+        Ok(WorkerGuard::new(self, id))
+    }
+
+    /// Get a handle that lets a thread the ThreadPool doesn't own take jobs
+    /// off its queue and run them, alongside the Workers.
+    ///
+    /// The handle is an escape hatch for threads whose lifetime is managed
+    /// elsewhere, such as an event loop that calls `WorkerHandle::run_one`
+    /// when it has nothing else to do. It keeps the ThreadPool alive, like a
+    /// clone of it would. Each call returns a handle with a new id.
+    ///
+    /// # Return
+    ///
+    /// The WorkerHandle, or `None` if the ThreadPool was created with
+    /// `from_existing_threads`, which has no queue to share, or has been
+    /// shut down.
+    pub fn receiver_handle(&self) -> Option<WorkerHandle> {
+        if self.inner.queue.is_external() || self.inner.queue.is_closed() {
+            return None;
+        }
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        Some(WorkerHandle::new(self.clone(), id))
+    }
+
+    /// Get a JobSender that submits jobs to the ThreadPool without keeping
+    /// it alive, unlike a clone of the ThreadPool.
+    ///
+    /// # Return
+    ///
+    /// The JobSender, or `None` if the ThreadPool has been shut down.
+    pub fn clone_sender(&self) -> Option<JobSender> {
+        if self.inner.queue.is_closed() {
+            return None;
+        }
+
+        Some(JobSender::new(&self.inner))
+    }
+
+    /// Retire the idle Workers of a ThreadPool that spawns its Workers on
+    /// demand, down to its minimum number of threads.
+    ///
+    /// This does straight away what a `keep_alive` timeout does eventually,
+    /// e.g. once a burst of jobs is over. Nothing is retired while jobs are
+    /// waiting in the queue, since the idle Workers are about to take them.
+    /// Workers are spawned again as jobs arrive, up to the ThreadPool's
+    /// size. The current thread blocks until the retired Workers have
+    /// exited.
+    ///
+    /// # Return
+    ///
+    /// The number of Workers retired. Always 0 for a ThreadPool whose
+    /// Workers are all spawned up front, or one that has been shut down.
+    pub fn shrink_to_fit(&self) -> usize {
+        if !self.inner.on_demand {
+            return 0;
+        }
+
+        let _resizing = self
+            .inner
+            .resizing
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if self.inner.queue.len() > 0 {
+            return 0;
+        }
+
+        let surplus = self
+            .inner
+            .live_count()
+            .saturating_sub(self.inner.config.min_threads);
+        let count = surplus.min(self.idle_count());
+
+        if count == 0 {
+            return 0;
+        }
+
+        match self.inner.retire_workers(count) {
+            Ok(()) => count,
+            Err(_) => 0,
+        }
+    }
+
+    /// Block the current thread until every submitted job has finished.
+    ///
+    /// Returns immediately if there are no outstanding jobs. Jobs submitted
+    /// while waiting, from any thread, extend the wait. A job has finished
+    /// once its Worker has also counted it and run the `on_job_complete` or
+    /// `on_job_panic` hook for it, so `active_count` and `snapshot` are up
+    /// to date when this returns.
+    pub fn join(&self) {
+        self.inner.pending.wait();
+    }
+
+    /// Block the current thread until no jobs are queued or executing.
+    ///
+    /// The waiting thread doesn't need to have submitted any of the jobs.
+    /// Jobs are counted in a single counter from the moment they are
+    /// submitted until they finish, so a job submitted just as the last one
+    /// finishes is always waited on rather than slipping through between the
+    /// checks of `active_count` and `queued_count`.
+    pub fn wait_for_idle(&self) {
+        self.inner.pending.wait();
+    }
+
+    /// Stop the Workers from taking any more jobs until `resume` is called.
+    ///
+    /// Jobs that are already executing are allowed to finish. Jobs can still
+    /// be submitted while the ThreadPool is paused; they wait in the queue
+    /// and run once it is resumed. Jobs sent to every Worker with `broadcast`
+    /// wait too. Idle Workers are not evicted while the ThreadPool is paused,
+    /// and shutting it down resumes it so the queue can drain.
+    ///
+    /// `join` and `wait_for_idle` block until the ThreadPool is resumed if
+    /// any jobs are queued.
+    pub fn pause(&self) {
+        self.inner.queue.pause();
+    }
+
+    /// Let the Workers take jobs again after a call to `pause`.
+    ///
+    /// Does nothing if the ThreadPool isn't paused.
+    pub fn resume(&self) {
+        self.inner.queue.resume();
+    }
+
+    /// Whether the ThreadPool has been paused with `pause`.
+    pub fn is_paused(&self) -> bool {
+        self.inner.queue.is_paused()
+    }
+
+    /// The stage of its life the ThreadPool is in.
+    ///
+    /// Being shut down takes precedence over being paused, and being paused
+    /// over whether any job has been submitted yet. See PoolState for the
+    /// transitions between the states.
+    pub fn state(&self) -> PoolState {
+        if self.inner.queue.is_closed() {
+            PoolState::Shutdown
+        } else if self.inner.queue.is_paused() {
+            PoolState::Paused
+        } else if self.inner.started.load(Ordering::Relaxed) {
+            PoolState::Running
+        } else {
+            PoolState::Created
+        }
+    }
+
+    /// Stop accepting jobs and let the Workers drain the queue, without
+    /// waiting for them.
+    ///
+    /// This is the first half of `shutdown`: jobs submitted afterwards,
+    /// through this handle or any of its clones, fail with
+    /// `ExecuteError::Shutdown`, while the jobs already queued keep running
+    /// and each Worker exits once the queue is empty. Call `await_shutdown`
+    /// to wait for them.
+    pub fn initiate_shutdown(&self) {
+        self.inner.queue.close();
+    }
+
+    /// Block the current thread until every Worker has exited, after
+    /// `initiate_shutdown`.
+    ///
+    /// This is the second half of `shutdown`, and shuts the ThreadPool down
+    /// itself if `initiate_shutdown` hasn't been called.
+    ///
+    /// # Errors
+    ///
+    /// A `ShutdownError` listing the Workers whose threads panicked.
+    pub fn await_shutdown(self) -> Result<(), ShutdownError> {
+        self.inner.shutdown_workers()
+    }
+
+    /// Shut the ThreadPool down, letting every queued job run first.
+    ///
+    /// No new jobs are accepted once this is called, through this handle or
+    /// any of its clones. The current thread blocks until every Worker has
+    /// drained the queue and exited. Dropping the last handle to the
+    /// ThreadPool does the same thing but discards any errors.
+    ///
+    /// # Errors
+    ///
+    /// A `ShutdownError` listing the Workers whose threads panicked.
+    pub fn shutdown(self) -> Result<(), ShutdownError> {
+        self.inner.shutdown_workers()
+    }
+
+    /// Take every job still waiting in the queue out of it without executing
+    /// them, leaving the ThreadPool running.
+    ///
+    /// Useful for moving work to another ThreadPool: pause this one, drain
+    /// it, and submit the jobs to the other one. Each job counts as pending
+    /// on this ThreadPool until it has run or been dropped, wherever that
+    /// happens. Jobs sent to a specific Worker with `broadcast` or
+    /// `execute_ordered` are left where they are, since they must run on
+    /// that Worker, as are jobs sent to external threads.
+    ///
+    /// # Return
+    ///
+    /// The jobs, highest priority first, followed by the jobs submitted with
+    /// `execute_after` that aren't due yet.
+    pub fn drain_queue(&self) -> impl Iterator<Item = Job> {
+        self.inner.queue.drain().into_iter()
+    }
+
+    /// Move every job waiting in the queue to another ThreadPool, e.g. one
+    /// of a different size that is replacing this one.
+    ///
+    /// The ThreadPool has to be paused, so its Workers don't take the jobs
+    /// while they are being moved. The jobs are taken as with `drain_queue`
+    /// and submitted to `dest` in order, blocking while its queue is full.
+    /// Each job keeps counting as pending on this ThreadPool as well until
+    /// it has run on `dest`.
+    ///
+    /// # Arguments
+    ///
+    /// - `dest` is the ThreadPool to move the jobs to. Moving jobs to the
+    ///   same ThreadPool does nothing.
+    ///
+    /// # Return
+    ///
+    /// The number of jobs moved.
+    ///
+    /// # Errors
+    ///
+    /// - `TransferError::NotPaused` if the ThreadPool isn't paused.
+    /// - `TransferError::Execute` if `dest` has been shut down or every one
+    ///   of its Workers has died. The jobs that weren't moved are put back
+    ///   in this ThreadPool's queue, at `Priority::Normal`.
+    pub fn transfer_jobs_to(&self, dest: &ThreadPool) -> Result<usize, TransferError> {
+        if !self.is_paused() {
+            return Err(TransferError::NotPaused);
+        }
+
+        if self == dest {
+            return Ok(0);
+        }
+
+        let mut jobs = self.inner.queue.drain().into_iter();
+        let mut transferred = 0;
+
+        while let Some(job) = jobs.next() {
+            // Kept here rather than moved into the job, so it can be put
+            // back if `dest` doesn't accept it.
+            let slot = Arc::new(Mutex::new(Some(job)));
+            let taken = Arc::clone(&slot);
+            let result = dest.execute_blocking(move || {
+                let job = taken.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+                if let Some(job) = job {
+                    job();
+                }
+            });
+
+            if let Err(e) = result {
+                let job = slot.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+                for job in job.into_iter().chain(jobs) {
+                    self.inner.queue.requeue(job);
+                }
+
+                return Err(TransferError::Execute(e));
+            }
+
+            transferred += 1;
+        }
+
+        Ok(transferred)
+    }
+
+    /// Shut the ThreadPool down without running the jobs still in the
+    /// queue.
+    ///
+    /// Jobs that Workers are already executing are allowed to finish, and
+    /// the current thread blocks until every Worker has exited, even while
+    /// other clones of the ThreadPool are still alive. Every job submitted
+    /// before this returns has either run or is returned.
+    ///
+    /// # Return
+    ///
+    /// The jobs that were still waiting in the queue, unexecuted.
+    pub fn shutdown_now(self) -> Vec<Job> {
+        // Paused first, so no Worker takes a job between draining the queue
+        // and closing it. Closing lifts the pause, so whatever a clone
+        // submitted in between is drained again afterwards.
+        self.inner.queue.pause();
+
+        let mut jobs = self.inner.queue.drain();
+
+        self.inner.queue.close();
+        jobs.extend(self.inner.queue.drain());
+
+        // Any failures have already been printed.
+        let _ = self.inner.shutdown_workers();
+
+        jobs
+    }
+}
+
+impl Inner {
+    /// Shut the Workers down once the last handle to the ThreadPool is gone.
+    ///
+    /// Calling it again does nothing.
+    fn release(&self) {
+        // Any failures have already been printed.
+        let _ = self.shutdown_workers();
+
+        // So a new ThreadPool can reuse the name.
+        #[cfg(feature = "metrics")]
+        if let Some((registry, collector)) = &self.metrics {
+            let _ = registry.unregister(Box::new(collector.clone()));
+        }
+    }
+
+    /// Close the queue and join every Worker's thread.
+    ///
+    /// The current thread is never joined, which happens when the last
+    /// handle to the ThreadPool is dropped by one of its own jobs; that
+    /// Worker exits on its own once the job returns.
+    ///
+    /// The threads are taken out of the list of Workers before they are
+    /// joined, so jobs still running can lock it, e.g. for a `snapshot`,
+    /// without waiting on their own Worker. No Worker is spawned once the
+    /// queue is closed, so none is missed.
+    ///
+    /// # Errors
+    ///
+    /// A `ShutdownError` listing the Workers whose threads panicked.
+    fn shutdown_workers(&self) -> Result<(), ShutdownError> {
+        self.queue.close();
+
+        let threads: Vec<_> = self
+            .lock_workers()
+            .iter_mut()
+            .filter(|worker| {
+                worker
+                    .thread
+                    .as_ref()
+                    .is_some_and(|t| t.thread().id() != thread::current().id())
+            })
+            .filter_map(|worker| Some((worker.id, worker.thread.take()?)))
+            .collect();
+        let mut worker_ids = Vec::new();
+
+        for (id, thread) in threads {
+            log_info!("Shutting down worker {id}");
+
+            if thread.join().is_err() {
+                log_error!("Error dropping {id}");
+                worker_ids.push(id);
+            }
+        }
+
+        if worker_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(ShutdownError { worker_ids })
+        }
+    }
+
+    /// Spawn a new Worker sharing the ThreadPool's queue.
+    ///
+    /// # Return
+    ///
+    /// The id of the new Worker.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Spawn` if the worker thread could not be spawned.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    fn spawn_worker(&self) -> Result<usize, ThreadPoolError> {
+        let mut workers = self.lock_workers();
+
+        self.spawn_worker_into(&mut workers)
+    }
+
+    /// Spawn a new Worker into the already locked list of Workers.
+    ///
+    /// # Arguments
+    ///
+    /// - `workers` is the locked list of Workers to add the new Worker to.
+    ///
+    /// # Return
+    ///
+    /// The id of the new Worker.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Spawn` if the worker thread could not be spawned.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    fn spawn_worker_into(&self, workers: &mut Vec<Worker>) -> Result<usize, ThreadPoolError> {
+        // Checked under the lock so `shutdown_workers` never misses a Worker.
+        if self.queue.is_closed() {
+            return Err(ThreadPoolError::Shutdown);
+        }
+
+        // Workers evicted for being idle have already exited.
+        workers.retain_mut(|worker| {
+            let finished = worker.thread.as_ref().is_none_or(|t| t.is_finished());
+
+            if finished {
+                if let Some(thread) = worker.thread.take() {
+                    thread
+                        .join()
+                        .unwrap_or_else(|_| log_error!("Error dropping {}", worker.id));
+                }
+            }
+
+            !finished
+        });
+
+        self.counters.live.fetch_add(1, Ordering::Relaxed);
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let worker = Worker::new(
+            id,
+            id,
+            &self.config,
+            Arc::clone(&self.queue),
+            Arc::clone(&self.counters),
+        )
+        .inspect_err(|_| {
+            self.counters.live.fetch_sub(1, Ordering::Relaxed);
+        })?;
+
+        workers.push(worker);
+
+        Ok(id)
+    }
+
+    /// Spawn more Workers while the ThreadPool spawns on demand, has slots
+    /// left, and there are more jobs than live Workers. Deferred jobs count
+    /// too, so there is a Worker waiting for them when they become due.
+    ///
+    /// A failure to spawn is printed rather than returned, since the jobs
+    /// have already been queued; the next submission will try again.
+    fn spawn_lazily(&self) {
+        if !self.on_demand {
+            return;
+        }
+
+        let mut workers = self.lock_workers();
+
+        while self.live_count() < self.size.load(Ordering::Relaxed)
+            && self.counters.active.load(Ordering::Relaxed)
+                + self.queue.len()
+                + self.queue.deferred_len()
+                > self.live_count()
+        {
+            match self.spawn_worker_into(&mut workers) {
+                Ok(_) => {}
+                // The Workers that are left drain the queue.
+                Err(ThreadPoolError::Shutdown) => break,
+                Err(e) => {
+                    log_warn!("Unable to spawn a worker lazily - {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Spawn a Worker if the ThreadPool spawns on demand and none are alive,
+    /// so there is a Worker to send jobs meant for a specific Worker to.
+    ///
+    /// A failure to spawn is printed rather than returned; pushing the job
+    /// then fails with `ExecuteError::Disconnected`.
+    fn spawn_first(&self) {
+        if !self.on_demand {
+            return;
+        }
+
+        let mut workers = self.lock_workers();
+
+        if self.live_count() == 0 {
+            match self.spawn_worker_into(&mut workers) {
+                Ok(_) | Err(ThreadPoolError::Shutdown) => {}
+                Err(e) => log_warn!("Unable to spawn a worker lazily - {e}"),
+            }
+        }
+    }
+
+    /// The number of Worker threads that are running.
+    fn live_count(&self) -> usize {
+        self.counters.live.load(Ordering::Relaxed)
+    }
+
+    /// Lock the list of spawned Workers.
+    ///
+    /// A poisoned lock is recovered, since a Worker is only ever pushed or
+    /// removed under it.
+    fn lock_workers(&self) -> MutexGuard<'_, Vec<Worker>> {
+        self.workers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Grow or shrink the ThreadPool. See `ThreadPool::resize`.
+    fn resize(&self, new_size: usize) -> Result<(), ThreadPoolError> {
+        if new_size == 0 {
+            return Err(ThreadPoolError::InvalidSize);
+        }
+
+        if self.queue.is_external() {
+            return Err(ThreadPoolError::ExternalThreads);
+        }
+
+        let _resizing = self.resizing.lock().unwrap_or_else(PoisonError::into_inner);
+
+        self.resize_to(new_size)
+    }
+
+    /// Resize the ThreadPool to the available parallelism. See
+    /// `ThreadPool::resize_to_parallelism`.
+    fn resize_to_parallelism(&self) -> Result<usize, ThreadPoolError> {
+        let size = thread::available_parallelism()
+            .map_or_else(|_| self.size.load(Ordering::Relaxed), |n| n.get());
+
+        self.resize(size)?;
+        Ok(size)
+    }
+
+    /// Set the number of Workers the ThreadPool should have, then retire or
+    /// spawn Workers to match. The caller must hold `resizing`.
+    ///
+    /// # Arguments
+    ///
+    /// - `new_size` is the number of Workers the ThreadPool should have.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::Spawn` if a new worker thread could not be spawned.
+    /// - `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    fn resize_to(&self, new_size: usize) -> Result<(), ThreadPoolError> {
+        self.size.store(new_size, Ordering::Relaxed);
+
+        let live = self.live_count();
+
+        if live > new_size {
+            self.retire_workers(live - new_size)?;
+        } else if !self.on_demand {
+            for _ in live..new_size {
+                self.spawn_worker()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tell `count` idle Workers to exit, then join and remove them.
+    ///
+    /// # Arguments
+    ///
+    /// - `count` is the number of Workers to retire.
+    ///
+    /// # Errors
+    ///
+    /// `ThreadPoolError::Shutdown` if the ThreadPool has been shut down.
+    fn retire_workers(&self, count: usize) -> Result<(), ThreadPoolError> {
+        let (tx, rx) = channel::channel();
+
+        for _ in 0..count {
+            self.queue
+                .terminate(tx.clone())
+                .map_err(|_| ThreadPoolError::Shutdown)?;
+        }
+
+        drop(tx);
+
+        // Wait for the replies before locking, so submitting jobs isn't
+        // blocked while the Workers finish what they are doing.
+        let ids: Vec<usize> = rx.iter().take(count).collect();
+        let retired: Vec<Worker> = {
+            let mut workers = self.lock_workers();
+
+            ids.iter()
+                .filter_map(|&id| {
+                    let index = workers.iter().position(|w| w.id == id)?;
+
+                    Some(workers.remove(index))
+                })
+                .collect()
+        };
+
+        for mut worker in retired {
+            if let Some(thread) = worker.thread.take() {
+                thread
+                    .join()
+                    .unwrap_or_else(|_| log_error!("Error dropping {}", worker.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tell the Worker with the given id to exit once it is idle, then join
+    /// and remove it.
+    ///
+    /// Does nothing if the Worker has already been removed. If it is the
+    /// Worker running the current thread, it is told to exit but not waited
+    /// for, and is removed the next time a Worker is spawned.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker to retire.
+    fn retire_worker(&self, id: usize) {
+        let (tx, rx) = channel::channel();
+        let is_current = self.lock_workers().iter().any(|worker| {
+            worker.id == id
+                && worker
+                    .thread
+                    .as_ref()
+                    .is_some_and(|t| t.thread().id() == thread::current().id())
+        });
+
+        // An error means the Worker is already exiting, or gone.
+        let told = self.queue.terminate_worker(id, tx).is_ok();
+
+        if is_current {
+            return;
+        }
+
+        if told {
+            let _ = rx.recv();
+        }
+
+        let retired = {
+            let mut workers = self.lock_workers();
+
+            workers
+                .iter()
+                .position(|worker| worker.id == id)
+                .map(|index| workers.remove(index))
+        };
+
+        if let Some(thread) = retired.and_then(|mut worker| worker.thread.take()) {
+            thread
+                .join()
+                .unwrap_or_else(|_| log_error!("Error dropping {id}"));
+        }
+    }
+
+    /// Put the job in the queue, spawning a Worker for it if needed.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to put in the queue.
+    /// - `priority` is the lane of the queue to put it in.
+    /// - `backpressure` is what to do if the queue is bounded and full.
+    /// - `deadline` is when the job should be dropped if it hasn't started.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Disconnected` if every Worker has died and none will
+    ///   be spawned to replace them.
+    /// - Any error returned by `JobQueue::push`.
+    fn submit(
+        &self,
+        job: Job,
+        priority: Priority,
+        backpressure: Backpressure,
+        deadline: Option<Instant>,
+    ) -> Result<(), ExecuteError> {
+        self.submit_guarded(job, None, priority, backpressure, deadline)
+    }
+
+    /// Put the job in the queue next to its PendingGuard, spawning a Worker
+    /// for it if needed.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to put in the queue.
+    /// - `guard` is the PendingGuard to queue next to it, or `None` if the
+    ///   Job carries its own.
+    /// - `priority` is the lane of the queue to put it in.
+    /// - `backpressure` is what to do if the queue is bounded and full.
+    /// - `deadline` is when the job should be dropped if it hasn't started.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `submit`.
+    fn submit_guarded(
+        &self,
+        job: Job,
+        guard: Option<PendingGuard>,
+        priority: Priority,
+        backpressure: Backpressure,
+        deadline: Option<Instant>,
+    ) -> Result<(), ExecuteError> {
+        self.check_connected()?;
+        self.queue
+            .push(job, guard, priority, backpressure, deadline)?;
+        self.mark_started();
+        self.spawn_lazily();
+
+        Ok(())
+    }
+
+    /// Record that a job has been submitted, moving the ThreadPool from
+    /// `PoolState::Created` to `PoolState::Running`.
+    fn mark_started(&self) {
+        // Checking first keeps every submission from writing to the same
+        // cache line.
+        if !self.started.load(Ordering::Relaxed) {
+            self.started.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Check that there is, or will be, a Worker to run submitted jobs.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Disconnected` if every Worker has died and none will be
+    /// spawned to replace them.
+    fn check_connected(&self) -> Result<(), ExecuteError> {
+        // Jobs sent to external threads find out for themselves.
+        if self.queue.is_external() {
+            return Ok(());
+        }
+
+        if !self.on_demand && self.live_count() == 0 && !self.queue.is_closed() {
+            return Err(ExecuteError::Disconnected);
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterating over `&pool` yields the ids of the Workers that are alive, in
+/// the order they were spawned. The ids are read when the iteration starts.
+impl IntoIterator for &ThreadPool {
+    type Item = usize;
+    type IntoIter = vec::IntoIter<usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner
+            .lock_workers()
+            .iter()
+            .filter(|worker| worker.is_alive())
+            .map(|worker| worker.id)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl Pool for ThreadPool {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        ThreadPool::execute_boxed(self, job)
+    }
+}
+
+impl Default for ThreadPool {
+    /// Create a ThreadPool with a Worker per logical CPU, or 4 Workers if
+    /// the number of CPUs can't be determined.
+    ///
+    /// The number of CPUs is read once, here. The ThreadPool doesn't grow or
+    /// shrink if it changes later.
+    ///
+    /// # Panics
+    ///
+    /// If a worker thread could not be spawned.
+    fn default() -> Self {
+        ThreadPoolBuilder::new()
+            .build()
+            .expect("Unable to create the ThreadPool")
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("size", &self.size())
+            .field("active", &self.active_count())
+            .field("queued", &self.queued_count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ThreadPool {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for ThreadPool {}
+
+impl Hash for ThreadPool {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.inner).hash(state);
+    }
+}
+
+impl Clone for ThreadPool {
+    fn clone(&self) -> Self {
+        self.inner.handles.fetch_add(1, Ordering::Relaxed);
+
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        if self.inner.handles.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.release();
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Only does anything if no handle was ever made, e.g. when building
+        // the ThreadPool failed after spawning some Workers.
+        self.release();
     }
 }
@@ -0,0 +1,46 @@
+/// Log a message at the given level.
+///
+/// Messages go to `tracing` when the `tracing` feature is enabled, to `log`
+/// when the `logging` feature is enabled, and nowhere otherwise.
+macro_rules! log_at {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::$level!($($arg)+);
+
+        #[cfg(all(feature = "logging", not(feature = "tracing")))]
+        ::log::$level!($($arg)+);
+
+        #[cfg(not(any(feature = "logging", feature = "tracing")))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+/// Log a message about routine Worker activity.
+macro_rules! log_debug {
+    ($($arg:tt)+) => {
+        $crate::logging::log_at!(debug, $($arg)+)
+    };
+}
+
+/// Log a message about a Worker starting or stopping.
+macro_rules! log_info {
+    ($($arg:tt)+) => {
+        $crate::logging::log_at!(info, $($arg)+)
+    };
+}
+
+/// Log a message about something that went wrong but was recovered from.
+macro_rules! log_warn {
+    ($($arg:tt)+) => {
+        $crate::logging::log_at!(warn, $($arg)+)
+    };
+}
+
+/// Log a message about something that went wrong and wasn't recovered from.
+macro_rules! log_error {
+    ($($arg:tt)+) => {
+        $crate::logging::log_at!(error, $($arg)+)
+    };
+}
+
+pub(crate) use {log_at, log_debug, log_error, log_info, log_warn};
@@ -52,9 +52,11 @@ fn main() {
             Err(e) => panic!("Error listening to 127.0.0.1:7878 - {}", e),
         };
 
-        pool.execute(|| {
+        if let Err(e) = pool.execute(|| {
             handle_connection(stream);
-        });
+        }) {
+            eprintln!("Unable to handle connection - {}", e);
+        }
     }
 
     println!("Shutting down.");
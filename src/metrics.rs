@@ -0,0 +1,470 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "metrics")]
+use crate::queue::JobQueue;
+#[cfg(feature = "metrics")]
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    IntCounter, IntGauge, Opts,
+};
+#[cfg(feature = "metrics")]
+use std::sync::Mutex;
+
+/// Counters struct
+///
+/// Best-effort counters shared between a ThreadPool and its Workers. Each
+/// counter is updated independently, so a set of reads is not guaranteed to
+/// be consistent with each other.
+///
+/// # Members
+///
+/// - `active` The number of Workers currently executing a job.
+/// - `live` The number of Worker threads that are running.
+/// - `expired` The number of jobs dropped because their deadline passed.
+/// - `completed` The number of jobs that ran without panicking.
+/// - `panicked` The number of jobs that panicked.
+/// - `queue_wait` How long the jobs that were executed waited in the queue.
+/// - `labels` The counters of each label given to `execute_with_label`.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    pub(crate) active: AtomicUsize,
+    pub(crate) live: AtomicUsize,
+    pub(crate) expired: AtomicUsize,
+    pub(crate) completed: AtomicU64,
+    pub(crate) panicked: AtomicU64,
+    pub(crate) queue_wait: AtomicHistogram,
+    pub(crate) labels: RwLock<HashMap<&'static str, Arc<LabelCounters>>>,
+}
+
+impl Counters {
+    /// The counters of a label, added the first time it is used.
+    ///
+    /// # Arguments
+    ///
+    /// - `label` is the label given to `execute_with_label`.
+    pub(crate) fn label(&self, label: &'static str) -> Arc<LabelCounters> {
+        let labels = self.labels.read().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(counters) = labels.get(label) {
+            return Arc::clone(counters);
+        }
+
+        drop(labels);
+
+        let mut labels = self.labels.write().unwrap_or_else(PoisonError::into_inner);
+
+        Arc::clone(labels.entry(label).or_default())
+    }
+
+    /// The LabelStats of every label used so far.
+    pub(crate) fn label_stats(&self) -> HashMap<&'static str, LabelStats> {
+        self.labels
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(&label, counters)| (label, counters.snapshot()))
+            .collect()
+    }
+}
+
+/// LabelCounters struct
+///
+/// The counters behind a label's LabelStats.
+///
+/// # Members
+///
+/// - `started` The number of jobs with the label that have started.
+/// - `completed` The number that ran without panicking.
+/// - `panicked` The number that panicked.
+/// - `run_ns` The nanoseconds the finished ones took altogether.
+#[derive(Debug, Default)]
+pub(crate) struct LabelCounters {
+    pub(crate) started: AtomicU64,
+    completed: AtomicU64,
+    panicked: AtomicU64,
+    run_ns: AtomicU64,
+}
+
+impl LabelCounters {
+    /// Count a job with the label that has finished.
+    ///
+    /// # Arguments
+    ///
+    /// - `run_duration` is how long the job ran.
+    /// - `panicked` is whether it panicked.
+    pub(crate) fn finish(&self, run_duration: Duration, panicked: bool) {
+        let outcome = if panicked {
+            &self.panicked
+        } else {
+            &self.completed
+        };
+
+        outcome.fetch_add(1, Ordering::Relaxed);
+        self.run_ns
+            .fetch_add(nanos(run_duration), Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LabelStats {
+        LabelStats {
+            started: self.started.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            panicked: self.panicked.load(Ordering::Relaxed),
+            total_run_time: Duration::from_nanos(self.run_ns.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// LabelStats struct
+///
+/// What the jobs submitted with one label through
+/// `ThreadPool::execute_with_label` have done so far, returned by
+/// `ThreadPool::label_stats`.
+///
+/// # Members
+///
+/// - `started` The number of jobs that have started.
+/// - `completed` The number that ran without panicking.
+/// - `panicked` The number that panicked.
+/// - `total_run_time` How long the finished jobs took altogether.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LabelStats {
+    pub started: u64,
+    pub completed: u64,
+    pub panicked: u64,
+    pub total_run_time: Duration,
+}
+
+impl LabelStats {
+    /// How long the finished jobs took on average, or `None` if none have
+    /// finished yet.
+    pub fn average_run_time(&self) -> Option<Duration> {
+        let finished = u128::from(self.completed + self.panicked);
+        let average = self.total_run_time.as_nanos().checked_div(finished)?;
+
+        Some(Duration::from_nanos(
+            u64::try_from(average).unwrap_or(u64::MAX),
+        ))
+    }
+}
+
+/// The number of buckets in a Histogram.
+const HISTOGRAM_BUCKETS: usize = 32;
+
+/// AtomicHistogram struct
+///
+/// A Histogram that can be added to from many threads at once without a
+/// lock. See `Histogram` for the buckets.
+///
+/// # Members
+///
+/// - `buckets` The number of durations counted in each bucket.
+#[derive(Debug, Default)]
+pub(crate) struct AtomicHistogram {
+    buckets: [AtomicU64; HISTOGRAM_BUCKETS],
+}
+
+impl AtomicHistogram {
+    /// Count a duration in its bucket.
+    pub(crate) fn record(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+
+        self.buckets[bucket.min(HISTOGRAM_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A copy of the counts so far.
+    pub(crate) fn snapshot(&self) -> Histogram {
+        Histogram {
+            counts: self
+                .buckets
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Relaxed))
+                .collect(),
+        }
+    }
+}
+
+/// Histogram struct
+///
+/// How many durations fell into each of a set of exponentially spaced
+/// buckets, returned by `ThreadPool::queue_wait_histogram`. Bucket 0 counts
+/// durations under 1µs, and bucket `i` those from 2^(i-1)µs up to 2^iµs,
+/// so each bucket is twice as wide as the one before it. The last bucket
+/// also counts everything longer, from about 18 minutes.
+///
+/// # Members
+///
+/// - `counts` The number of durations in each bucket, shortest first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Histogram {
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// The total number of durations counted.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The end of a bucket: every duration in it is shorter.
+    ///
+    /// # Arguments
+    ///
+    /// - `bucket` is the index of the bucket. The last bucket has no end, so
+    ///   `Duration::MAX` is returned for it and anything past it.
+    pub fn upper_bound(bucket: usize) -> Duration {
+        if bucket >= HISTOGRAM_BUCKETS - 1 {
+            Duration::MAX
+        } else {
+            Duration::from_micros(1 << bucket)
+        }
+    }
+
+    /// An upper bound on the duration that a fraction `q` of the durations
+    /// are shorter than, such as `0.99` for the 99th percentile.
+    ///
+    /// # Arguments
+    ///
+    /// - `q` is the fraction, from 0 to 1. It is clamped to that range.
+    ///
+    /// # Return
+    ///
+    /// The end of the bucket the quantile falls in, so the true value is at
+    /// most this. `None` if nothing has been counted.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+
+        if count == 0 {
+            return None;
+        }
+
+        // The rank of the duration the quantile falls on, from 1.
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+
+        self.counts
+            .iter()
+            .position(|&n| {
+                seen += n;
+                seen >= rank
+            })
+            .map(Self::upper_bound)
+    }
+
+    /// The 99th percentile; see `quantile`.
+    pub fn p99(&self) -> Option<Duration> {
+        self.quantile(0.99)
+    }
+}
+
+/// WorkerTimes struct
+///
+/// How long one Worker has spent waiting for jobs and executing them, in
+/// nanoseconds. Only the Worker's own thread writes to them.
+///
+/// # Members
+///
+/// - `idle_ns` The time spent waiting for a job.
+/// - `busy_ns` The time spent executing jobs.
+#[derive(Debug, Default)]
+pub(crate) struct WorkerTimes {
+    pub(crate) idle_ns: AtomicU64,
+    pub(crate) busy_ns: AtomicU64,
+}
+
+impl WorkerTimes {
+    /// Add to the time spent waiting for a job.
+    pub(crate) fn add_idle(&self, duration: Duration) {
+        self.idle_ns.fetch_add(nanos(duration), Ordering::Relaxed);
+    }
+
+    /// Add to the time spent executing jobs.
+    pub(crate) fn add_busy(&self, duration: Duration) {
+        self.busy_ns.fetch_add(nanos(duration), Ordering::Relaxed);
+    }
+}
+
+/// A Duration in whole nanoseconds, saturating at `u64::MAX`, which is over
+/// 500 years.
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// JobMetrics struct
+///
+/// How long a single job spent waiting and running, passed to the
+/// `on_job_complete` callback.
+///
+/// # Members
+///
+/// - `worker_id` The id of the Worker that executed the job.
+/// - `queue_wait` The time between the job being submitted and a Worker
+///   starting it.
+/// - `run_duration` The time the job took to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobMetrics {
+    pub worker_id: usize,
+    pub queue_wait: Duration,
+    pub run_duration: Duration,
+}
+
+/// PoolSnapshot struct
+///
+/// A point-in-time view of a ThreadPool, returned by `ThreadPool::snapshot`.
+/// Each value is read separately, so they are not guaranteed to be
+/// consistent with each other.
+///
+/// # Members
+///
+/// - `size` The number of Workers the ThreadPool is configured to have.
+/// - `active` The number of Workers executing a job.
+/// - `idle` The number of live Workers waiting for a job.
+/// - `queued` The number of jobs waiting in the queue.
+/// - `total_completed` The number of jobs that ran without panicking.
+/// - `total_panics` The number of jobs that panicked.
+/// - `per_worker_idle_ns` The nanoseconds each live Worker has spent waiting
+///   for a job, in the order the Workers were spawned. A wait is only
+///   counted once it ends.
+/// - `per_worker_busy_ns` The nanoseconds each live Worker has spent
+///   executing jobs, in the same order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PoolSnapshot {
+    pub size: usize,
+    pub active: usize,
+    pub idle: usize,
+    pub queued: usize,
+    pub total_completed: u64,
+    pub total_panics: u64,
+    pub per_worker_idle_ns: Vec<u64>,
+    pub per_worker_busy_ns: Vec<u64>,
+}
+
+impl fmt::Display for PoolSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} workers ({} active, {} idle), {} queued, {} completed, {} panicked",
+            self.size, self.active, self.idle, self.queued, self.total_completed, self.total_panics
+        )
+    }
+}
+
+/// PoolCollector struct
+///
+/// Exports a ThreadPool's counters to Prometheus, reading them whenever the
+/// registry is gathered. See `ThreadPoolBuilder::with_metrics`.
+///
+/// # Members
+///
+/// - `counters` The counters of the ThreadPool
+/// - `queue` The queue of the ThreadPool, to count the jobs waiting in it
+/// - `active` The `thread_pool_active_threads` gauge
+/// - `queued` The `thread_pool_queued_jobs` gauge
+/// - `completed` The `thread_pool_completed_jobs_total` counter
+/// - `panics` The `thread_pool_panics_total` counter
+/// - `collecting` Held while the metrics are brought up to date, so two
+///   gathers at once don't both add the same jobs to the counters
+#[cfg(feature = "metrics")]
+#[derive(Clone)]
+pub(crate) struct PoolCollector {
+    counters: Arc<Counters>,
+    queue: Arc<JobQueue>,
+    active: IntGauge,
+    queued: IntGauge,
+    completed: IntCounter,
+    panics: IntCounter,
+    collecting: Arc<Mutex<()>>,
+}
+
+#[cfg(feature = "metrics")]
+impl PoolCollector {
+    /// Create the metrics for a ThreadPool, labelled with its name.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool_name` is the value of the `pool` label.
+    /// - `counters` are the counters of the ThreadPool.
+    /// - `queue` is the queue of the ThreadPool.
+    ///
+    /// # Errors
+    ///
+    /// A `prometheus::Error` if `pool_name` isn't a valid label value.
+    pub(crate) fn new(
+        pool_name: &str,
+        counters: Arc<Counters>,
+        queue: Arc<JobQueue>,
+    ) -> prometheus::Result<Self> {
+        let opts = |name: &str, help: &str| Opts::new(name, help).const_label("pool", pool_name);
+
+        Ok(Self {
+            counters,
+            queue,
+            active: IntGauge::with_opts(opts(
+                "thread_pool_active_threads",
+                "Number of workers executing a job.",
+            ))?,
+            queued: IntGauge::with_opts(opts(
+                "thread_pool_queued_jobs",
+                "Number of jobs waiting in the queue.",
+            ))?,
+            completed: IntCounter::with_opts(opts(
+                "thread_pool_completed_jobs_total",
+                "Number of jobs that ran without panicking.",
+            ))?,
+            panics: IntCounter::with_opts(opts(
+                "thread_pool_panics_total",
+                "Number of jobs that panicked.",
+            ))?,
+            collecting: Arc::new(Mutex::new(())),
+        })
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Collector for PoolCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        [
+            self.active.desc(),
+            self.queued.desc(),
+            self.completed.desc(),
+            self.panics.desc(),
+        ]
+        .concat()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let _collecting = self
+            .collecting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let active = self.counters.active.load(Ordering::Relaxed);
+        let completed = self.counters.completed.load(Ordering::Relaxed);
+        let panicked = self.counters.panicked.load(Ordering::Relaxed);
+
+        self.active.set(active as i64);
+        self.queued.set(self.queue.len() as i64);
+        self.completed.inc_by(completed - self.completed.get());
+        self.panics.inc_by(panicked - self.panics.get());
+
+        [
+            self.active.collect(),
+            self.queued.collect(),
+            self.completed.collect(),
+            self.panics.collect(),
+        ]
+        .concat()
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{
+    logging::log_warn,
+    pending::{Pending, PendingGuard},
+    ExecuteError, ThreadPool,
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    task::Poll,
+    thread,
+};
+
+/// A small piece of work that makes progress a step at a time, so many of
+/// them can share one thread. See `MicroTaskPool`.
+///
+/// Implemented for every `FnMut() -> Poll<()>` closure, which keeps its
+/// state between steps in the variables it captures.
+pub trait MicroTask: Send {
+    /// Run the next step. A step should be short, since every other task
+    /// on the same thread waits for it to return.
+    ///
+    /// # Return
+    ///
+    /// `Poll::Ready(())` once the task is done, or `Poll::Pending` to be
+    /// stepped again after the other tasks have had a turn.
+    fn step(&mut self) -> Poll<()>;
+}
+
+impl<F> MicroTask for F
+where
+    F: FnMut() -> Poll<()> + Send,
+{
+    fn step(&mut self) -> Poll<()> {
+        self()
+    }
+}
+
+/// MicroTaskPool struct
+///
+/// Runs thousands of MicroTasks on a few Workers of a ThreadPool, without a
+/// thread or a job per task. Each scheduler is one long-running job that
+/// steps its tasks round-robin, taking new ones from a shared queue between
+/// rounds. Unlike `FuturePool`, tasks are stepped whether or not they are
+/// ready to make progress, so a scheduler with tasks keeps its Worker busy
+/// and only sleeps once all of its tasks are done.
+///
+/// Dropping the MicroTaskPool stops new tasks from being accepted; the
+/// schedulers finish the tasks they have and then give their Workers back.
+/// Shutting the ThreadPool down waits for the schedulers like for any other
+/// job, so drop the MicroTaskPool first.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the schedulers run on
+/// - `shared` The tasks waiting for a scheduler, shared with the schedulers
+/// - `pending` The number of tasks that haven't finished yet
+pub struct MicroTaskPool {
+    pool: ThreadPool,
+    shared: Arc<Shared>,
+    pending: Arc<Pending>,
+}
+
+/// Shared struct
+///
+/// # Members
+///
+/// - `state` The tasks no scheduler has taken yet, and whether the
+///   MicroTaskPool is still accepting tasks
+/// - `available` Notified when a task is added or the MicroTaskPool closes
+/// - `schedulers` The number of schedulers, to share new tasks between
+struct Shared {
+    state: Mutex<Incoming>,
+    available: Condvar,
+    schedulers: usize,
+}
+
+/// Incoming struct
+///
+/// # Members
+///
+/// - `tasks` The tasks no scheduler has taken yet
+/// - `closed` Whether the MicroTaskPool has been dropped
+struct Incoming {
+    tasks: VecDeque<Entry>,
+    closed: bool,
+}
+
+/// A MicroTask, which counts as pending until it is dropped.
+type Entry = (Box<dyn MicroTask>, PendingGuard);
+
+impl MicroTaskPool {
+    /// Start `schedulers` schedulers on the ThreadPool.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to run the schedulers on. Each scheduler
+    ///   takes up one of its Workers for as long as the MicroTaskPool is
+    ///   alive.
+    /// - `schedulers` is the number of schedulers. It is raised to 1 if it
+    ///   is 0.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `ThreadPool::execute`, if a scheduler couldn't be
+    /// submitted. The schedulers submitted before it exit again.
+    pub fn new(pool: ThreadPool, schedulers: usize) -> Result<Self, ExecuteError> {
+        let micro = Self {
+            pool,
+            shared: Arc::new(Shared {
+                state: Mutex::new(Incoming {
+                    tasks: VecDeque::new(),
+                    closed: false,
+                }),
+                available: Condvar::new(),
+                schedulers: schedulers.max(1),
+            }),
+            pending: Arc::new(Pending::new()),
+        };
+
+        for _ in 0..micro.shared.schedulers {
+            let shared = Arc::clone(&micro.shared);
+
+            micro.pool.execute(move || shared.run())?;
+        }
+
+        Ok(micro)
+    }
+
+    /// Hand a task to the next scheduler with room for it.
+    ///
+    /// The task is stepped until it returns `Poll::Ready`. If a step
+    /// panics, the panic is logged and the task is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// - `task` is the MicroTask to run.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the ThreadPool has been shut down, in
+    /// which case the schedulers are gone too.
+    pub fn execute<T>(&self, task: T) -> Result<(), ExecuteError>
+    where
+        T: MicroTask + 'static,
+    {
+        if self.pool.inner.queue.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        self.shared
+            .lock()
+            .tasks
+            .push_back((Box::new(task), self.pending.guard()));
+        self.shared.available.notify_one();
+
+        Ok(())
+    }
+
+    /// Block the current thread until every task has finished.
+    pub fn join(&self) {
+        self.pending.wait();
+    }
+
+    /// The number of tasks that haven't finished yet.
+    pub fn task_count(&self) -> usize {
+        self.pending.count()
+    }
+
+    /// The ThreadPool the schedulers run on.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+}
+
+impl Shared {
+    /// The loop of a scheduler, which returns once the MicroTaskPool has
+    /// been dropped and the scheduler's tasks are done.
+    fn run(&self) {
+        let mut tasks = VecDeque::new();
+
+        while self.refill(&mut tasks) {
+            for _ in 0..tasks.len() {
+                let Some((mut task, guard)) = tasks.pop_front() else {
+                    break;
+                };
+
+                match panic::catch_unwind(AssertUnwindSafe(|| task.step())) {
+                    Ok(Poll::Pending) => tasks.push_back((task, guard)),
+                    Ok(Poll::Ready(())) => {}
+                    Err(_) => log_warn!("A micro-task panicked; dropping it."),
+                }
+            }
+
+            // Give other threads on the same core a turn between rounds.
+            thread::yield_now();
+        }
+    }
+
+    /// Take this scheduler's share of the new tasks, waiting for some if it
+    /// has none.
+    ///
+    /// # Arguments
+    ///
+    /// - `tasks` are the scheduler's tasks.
+    ///
+    /// # Return
+    ///
+    /// Whether the scheduler has any tasks to step. `false` means it should
+    /// exit.
+    fn refill(&self, tasks: &mut VecDeque<Entry>) -> bool {
+        let mut incoming = self.lock();
+
+        while tasks.is_empty() && incoming.tasks.is_empty() {
+            if incoming.closed {
+                return false;
+            }
+
+            incoming = self
+                .available
+                .wait(incoming)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        // An even share, rounded up, so the other schedulers get some too.
+        let share = incoming.tasks.len().div_ceil(self.schedulers);
+
+        tasks.extend(incoming.tasks.drain(..share));
+
+        true
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Incoming> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MicroTaskPool {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.available.notify_all();
+    }
+}
+
+impl fmt::Debug for MicroTaskPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MicroTaskPool")
+            .field("schedulers", &self.shared.schedulers)
+            .field("tasks", &self.task_count())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    ExecuteError, Job, Pool, ShutdownError, ThreadPool, ThreadPoolBuilder, ThreadPoolError,
+};
+use std::{fmt, thread};
+
+/// The distance Linux gives between a NUMA node and itself, used when the
+/// real distances aren't known.
+const LOCAL_DISTANCE: u32 = 10;
+
+/// The distance Linux gives between neighbouring NUMA nodes, used when the
+/// real distances aren't known.
+const REMOTE_DISTANCE: u32 = 20;
+
+/// Node struct
+///
+/// One NUMA node, as found by `topology`.
+///
+/// # Members
+///
+/// - `cpus` The ids of the CPUs on the node
+/// - `distances` The relative cost of reaching each node's memory from this
+///   node, indexed like the nodes themselves
+#[derive(Debug, Clone)]
+struct Node {
+    cpus: Vec<usize>,
+    distances: Vec<u32>,
+}
+
+/// NUMAPoolBuilder struct
+///
+/// Builds a NUMAPool, with one ThreadPool per NUMA node whose Workers are
+/// pinned to that node's CPUs. The topology is read from
+/// `/sys/devices/system/node` on Linux. Anywhere else, or if it can't be
+/// read, the machine is treated as a single node.
+///
+/// # Members
+///
+/// - `builder` The settings every node's ThreadPool starts from
+/// - `nodes` The NUMA nodes that were found
+/// - `threads` The number of Workers to give each node, where set
+#[derive(Debug, Clone)]
+pub struct NUMAPoolBuilder {
+    builder: ThreadPoolBuilder,
+    nodes: Vec<Node>,
+    threads: Vec<Option<usize>>,
+}
+
+/// NUMAPool struct
+///
+/// A ThreadPool per NUMA node, which `submit_local` picks between so a job
+/// runs on the same node as the thread that submitted it. Jobs that work on
+/// memory the submitting thread allocated then avoid the cost of reaching
+/// across to another node, as long as the OS placed that memory on the node
+/// it was first touched from, as Linux does by default.
+///
+/// # Members
+///
+/// - `pools` Each node's ThreadPool, or `None` for nodes without Workers
+/// - `nodes` The NUMA nodes the ThreadPools belong to
+/// - `node_of_cpu` The node each CPU is on, indexed by CPU id
+pub struct NUMAPool {
+    pools: Vec<Option<ThreadPool>>,
+    nodes: Vec<Node>,
+    node_of_cpu: Vec<usize>,
+}
+
+impl NUMAPoolBuilder {
+    /// Create a new NUMAPoolBuilder for the NUMA nodes of this machine.
+    ///
+    /// Each node gets one Worker per CPU on it by default.
+    pub fn new() -> Self {
+        let nodes = topology();
+
+        Self {
+            builder: ThreadPoolBuilder::new(),
+            threads: vec![None; nodes.len()],
+            nodes,
+        }
+    }
+
+    /// The number of NUMA nodes that were found.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Set the number of Workers on one NUMA node, e.g. to give the node
+    /// the network card is attached to more of them.
+    ///
+    /// # Arguments
+    ///
+    /// - `node` is the index of the node. Nodes that don't exist are
+    ///   ignored.
+    /// - `num_threads` is the number of Workers. With 0, the node gets no
+    ///   ThreadPool and its jobs go to the nearest node that has one.
+    pub fn node_threads(mut self, node: usize, num_threads: usize) -> Self {
+        if let Some(threads) = self.threads.get_mut(node) {
+            *threads = Some(num_threads);
+        }
+
+        self
+    }
+
+    /// Set the settings every node's ThreadPool is built with.
+    ///
+    /// The number of Workers and the cores they are pinned to are replaced
+    /// for each node.
+    ///
+    /// # Arguments
+    ///
+    /// - `builder` is the ThreadPoolBuilder to start from.
+    pub fn pool_builder(mut self, builder: ThreadPoolBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Build a ThreadPool for each NUMA node that has Workers.
+    ///
+    /// # Errors
+    ///
+    /// - `ThreadPoolError::InvalidSize` if no node has any Workers.
+    /// - The same errors as `ThreadPoolBuilder::build`, for any node's
+    ///   ThreadPool. The ThreadPools built before it are shut down.
+    pub fn build(self) -> Result<NUMAPool, ThreadPoolError> {
+        let mut pools = Vec::with_capacity(self.nodes.len());
+
+        for (node, threads) in self.nodes.iter().zip(&self.threads) {
+            let num_threads = threads.unwrap_or(node.cpus.len());
+
+            if num_threads == 0 {
+                pools.push(None);
+                continue;
+            }
+
+            let pool = self
+                .builder
+                .clone()
+                .num_threads(num_threads)
+                .pin_workers_to_cores(node.cpus.clone())
+                .build()?;
+
+            pools.push(Some(pool));
+        }
+
+        if pools.iter().all(Option::is_none) {
+            return Err(ThreadPoolError::InvalidSize);
+        }
+
+        let cpus = self.nodes.iter().flat_map(|node| &node.cpus);
+        let mut node_of_cpu = vec![0; cpus.max().map_or(0, |cpu| cpu + 1)];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            for &cpu in &node.cpus {
+                node_of_cpu[cpu] = index;
+            }
+        }
+
+        Ok(NUMAPool {
+            pools,
+            nodes: self.nodes,
+            node_of_cpu,
+        })
+    }
+}
+
+impl Default for NUMAPoolBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NUMAPool {
+    /// Puts the job in the queue of the ThreadPool on the current thread's
+    /// NUMA node, or of the nearest node with a ThreadPool if that one has
+    /// none.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `ThreadPool::execute`.
+    pub fn submit_local<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.nearest_pool(self.current_node()).execute(f)
+    }
+
+    /// The NUMA node the current thread is running on, or 0 if that can't
+    /// be told on this platform.
+    pub fn current_node(&self) -> usize {
+        current_cpu()
+            .and_then(|cpu| self.node_of_cpu.get(cpu).copied())
+            .unwrap_or(0)
+    }
+
+    /// The number of NUMA nodes, including those without a ThreadPool.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The ThreadPool on a NUMA node.
+    ///
+    /// # Arguments
+    ///
+    /// - `node` is the index of the node.
+    ///
+    /// # Return
+    ///
+    /// The node's ThreadPool, or `None` if the node doesn't exist or was
+    /// given no Workers.
+    pub fn pool(&self, node: usize) -> Option<&ThreadPool> {
+        self.pools.get(node)?.as_ref()
+    }
+
+    /// Block the current thread until every node's ThreadPool has run out
+    /// of jobs.
+    pub fn join(&self) {
+        for pool in self.pools.iter().flatten() {
+            pool.join();
+        }
+    }
+
+    /// Shut every node's ThreadPool down. See `ThreadPool::shutdown`.
+    ///
+    /// # Errors
+    ///
+    /// The first error returned by a node's ThreadPool. The rest are still
+    /// shut down.
+    pub fn shutdown(self) -> Result<(), ShutdownError> {
+        self.pools
+            .into_iter()
+            .flatten()
+            .map(ThreadPool::shutdown)
+            .fold(Ok(()), Result::and)
+    }
+
+    /// The ThreadPool of the nearest node to `node` that has one.
+    fn nearest_pool(&self, node: usize) -> &ThreadPool {
+        let distances = self.nodes.get(node).map(|node| &node.distances);
+
+        self.pools
+            .iter()
+            .enumerate()
+            .filter_map(|(index, pool)| Some((index, pool.as_ref()?)))
+            .min_by_key(|&(index, _)| {
+                distances
+                    .and_then(|distances| distances.get(index).copied())
+                    .unwrap_or(u32::MAX)
+            })
+            .map(|(_, pool)| pool)
+            .expect("a NUMAPool has at least one ThreadPool")
+    }
+}
+
+impl Pool for NUMAPool {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        self.submit_local(job)
+    }
+}
+
+impl fmt::Debug for NUMAPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let threads: Vec<usize> = self
+            .pools
+            .iter()
+            .map(|pool| pool.as_ref().map_or(0, ThreadPool::size))
+            .collect();
+
+        f.debug_struct("NUMAPool")
+            .field("node_threads", &threads)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Find the NUMA nodes of this machine, falling back to a single node with
+/// every CPU on it.
+fn topology() -> Vec<Node> {
+    #[cfg(target_os = "linux")]
+    if let Some(nodes) = linux::topology() {
+        return nodes;
+    }
+
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+
+    vec![Node {
+        cpus: (0..cpus).collect(),
+        distances: vec![LOCAL_DISTANCE],
+    }]
+}
+
+/// The id of the CPU the current thread is running on, if the platform can
+/// tell.
+fn current_cpu() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: sched_getcpu has no preconditions, and reports failure
+        // as -1.
+        let cpu = unsafe { libc::sched_getcpu() };
+
+        usize::try_from(cpu).ok()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{Node, LOCAL_DISTANCE, REMOTE_DISTANCE};
+    use std::fs;
+
+    /// Read the NUMA nodes from sysfs.
+    ///
+    /// # Return
+    ///
+    /// The nodes in order of their id, or `None` if there are none to read,
+    /// e.g. because the kernel was built without NUMA support.
+    pub(super) fn topology() -> Option<Vec<Node>> {
+        let mut ids: Vec<usize> = fs::read_dir("/sys/devices/system/node")
+            .ok()?
+            .filter_map(|entry| {
+                entry
+                    .ok()?
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("node")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+
+        ids.sort_unstable();
+
+        let count = ids.len();
+        let nodes: Vec<Node> = ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, id)| {
+                let path = format!("/sys/devices/system/node/node{id}");
+                let cpus = fs::read_to_string(format!("{path}/cpulist"))
+                    .map(|list| parse_cpu_list(&list))
+                    .unwrap_or_default();
+                let distances = fs::read_to_string(format!("{path}/distance"))
+                    .ok()
+                    .and_then(|line| {
+                        line.split_whitespace()
+                            .map(|d| d.parse().ok())
+                            .collect::<Option<Vec<u32>>>()
+                    })
+                    .filter(|distances| distances.len() == count)
+                    .unwrap_or_else(|| {
+                        (0..count)
+                            .map(|other| {
+                                if other == index {
+                                    LOCAL_DISTANCE
+                                } else {
+                                    REMOTE_DISTANCE
+                                }
+                            })
+                            .collect()
+                    });
+
+                Node { cpus, distances }
+            })
+            .collect();
+
+        (!nodes.is_empty()).then_some(nodes)
+    }
+
+    /// Parse a list of CPU ids like `0-3,8-11`.
+    fn parse_cpu_list(list: &str) -> Vec<usize> {
+        list.trim()
+            .split(',')
+            .filter(|range| !range.is_empty())
+            .filter_map(|range| match range.split_once('-') {
+                Some((first, last)) => Some(first.parse().ok()?..=last.parse().ok()?),
+                None => {
+                    let cpu = range.parse().ok()?;
+
+                    Some(cpu..=cpu)
+                }
+            })
+            .flatten()
+            .collect()
+    }
+}
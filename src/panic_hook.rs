@@ -0,0 +1,54 @@
+use std::{
+    cell::Cell,
+    panic::{self, PanicHookInfo},
+    sync::Once,
+};
+
+thread_local! {
+    /// The id of the Worker running a job on this thread and how many jobs
+    /// that thread has started, while the job runs.
+    static CURRENT_JOB: Cell<Option<(usize, u64)>> = const { Cell::new(None) };
+
+    /// How many jobs have been started on this thread.
+    static JOBS_STARTED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Only one hook is installed, however many ThreadPools ask for it.
+static INSTALL: Once = Once::new();
+
+/// Install a panic hook that says which Worker and job a panic came from,
+/// before handing the panic on to the hook that was installed before. See
+/// `ThreadPoolBuilder::install_panic_hook`.
+pub(crate) fn install() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+            if let Some((id, n)) = CURRENT_JOB.get() {
+                eprintln!("[ThreadPool worker {id} executing job #{n}]");
+            }
+
+            previous(info);
+        }));
+    });
+}
+
+/// Run a job on the current thread, recording which Worker runs it for the
+/// panic hook.
+///
+/// # Arguments
+///
+/// - `id` is the id of the Worker running the job.
+/// - `job` runs the job.
+pub(crate) fn with_job<T>(id: usize, job: impl FnOnce() -> T) -> T {
+    let n = JOBS_STARTED.get() + 1;
+
+    JOBS_STARTED.set(n);
+    CURRENT_JOB.set(Some((id, n)));
+
+    let result = job();
+
+    CURRENT_JOB.set(None);
+
+    result
+}
@@ -0,0 +1,326 @@
+use crate::{propagate::Propagate, Job};
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, Thread},
+};
+
+thread_local! {
+    /// The Pendings of the jobs that finished while the current thread's
+    /// Worker was executing them, or `None` outside `defer`.
+    static DEFERRED: RefCell<Option<Vec<Arc<Pending>>>> = const { RefCell::new(None) };
+}
+
+/// Pending struct
+///
+/// Counts the jobs that have been submitted to a ThreadPool but have not
+/// finished yet, so callers can wait for the pool to run out of work.
+///
+/// # Members
+///
+/// - `count` The number of outstanding jobs.
+/// - `limit` The number of outstanding jobs `wait_for_slot` waits to drop
+///   below.
+/// - `waiting` The number of threads in `waiters`, so finishing a job only
+///   takes the lock when someone is waiting
+/// - `waiters` The threads parked in `wait_while_at_least`, unparked
+///   whenever `count` drops below `limit`
+/// - `propagator` Carries the submitting thread's trace context into every
+///   job made by `job`, if set.
+pub(crate) struct Pending {
+    count: AtomicUsize,
+    limit: usize,
+    waiting: AtomicUsize,
+    waiters: Mutex<Vec<Thread>>,
+    propagator: Option<Arc<dyn Propagate>>,
+}
+
+/// PendingGuard struct
+///
+/// Marks a single job as outstanding for as long as it is alive. Dropping the
+/// guard, whether the job ran, panicked, or was discarded, marks the job as
+/// finished.
+///
+/// # Members
+///
+/// - `pending` The counter this guard was taken from.
+/// - `deferrable` Whether the guard belongs to a Job made by `Pending::job`
+///   or `Pending::boxed`, whose Worker marks it as finished only once it has
+///   recorded the job.
+pub(crate) struct PendingGuard {
+    pending: Arc<Pending>,
+    deferrable: bool,
+}
+
+/// Deferred struct
+///
+/// Returned by `defer`. Until it is dropped, Jobs made by `Pending::job`
+/// that finish on the current thread stay outstanding, so a Worker can
+/// update its counters and run its hooks before `join` returns.
+///
+/// # Members
+///
+/// - `outer` The jobs deferred by an enclosing `defer`, such as the job
+///   that is running another job with `WorkerHandle::run_one`
+pub(crate) struct Deferred {
+    outer: Option<Vec<Arc<Pending>>>,
+}
+
+/// Keep the Jobs that finish on the current thread outstanding until the
+/// returned Deferred is dropped.
+pub(crate) fn defer() -> Deferred {
+    Deferred {
+        outer: DEFERRED.with(|deferred| deferred.replace(Some(Vec::new()))),
+    }
+}
+
+/// Mark the Jobs deferred on the current thread so far as finished, so a
+/// job waiting for other jobs doesn't wait for ones that ran inline.
+fn finish_deferred() {
+    let finished = DEFERRED.try_with(|deferred| deferred.borrow_mut().as_mut().map(std::mem::take));
+
+    for pending in finished.ok().flatten().into_iter().flatten() {
+        pending.finish();
+    }
+}
+
+impl Pending {
+    /// Create a new Pending with no outstanding jobs.
+    pub(crate) fn new() -> Self {
+        Self::with_limit(1)
+    }
+
+    /// Create a new Pending with no outstanding jobs, whose `wait_for_slot`
+    /// returns while fewer than `limit` jobs are outstanding.
+    ///
+    /// # Arguments
+    ///
+    /// - `limit` is the number of jobs that may be outstanding at once. It
+    ///   is raised to 1 if it is 0.
+    pub(crate) fn with_limit(limit: usize) -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            limit: limit.max(1),
+            waiting: AtomicUsize::new(0),
+            waiters: Mutex::new(Vec::new()),
+            propagator: None,
+        }
+    }
+
+    /// Carry the submitting thread's trace context into every job made by
+    /// `job`.
+    ///
+    /// # Arguments
+    ///
+    /// - `propagator` is the propagator to use, or `None` for none.
+    pub(crate) fn propagating(mut self, propagator: Option<Arc<dyn Propagate>>) -> Self {
+        self.propagator = propagator;
+        self
+    }
+
+    /// Mark a new job as outstanding.
+    ///
+    /// # Return
+    ///
+    /// A PendingGuard that should be moved into the job.
+    pub(crate) fn guard(self: &Arc<Self>) -> PendingGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+
+        PendingGuard {
+            pending: Arc::clone(self),
+            deferrable: false,
+        }
+    }
+
+    /// Box the function into a Job that counts as outstanding until it
+    /// finishes or is dropped. When a Worker executes it, it counts until
+    /// the Worker has recorded it as well.
+    ///
+    /// With the `tracing` feature, the span that is current when the job is
+    /// created is entered again on the Worker while the job runs, inside the
+    /// Worker's `task` span. So is the trace context of the propagator, if
+    /// there is one, which costs one more allocation per job.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    pub(crate) fn job<F>(self: &Arc<Self>, f: F) -> Job
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut guard = self.guard();
+
+        guard.deferrable = true;
+
+        if let Some(propagator) = &self.propagator {
+            let job = Arc::clone(propagator).wrap(Box::new(f));
+
+            return self.wrap(guard, job);
+        }
+
+        self.wrap(guard, f)
+    }
+
+    /// Count an already boxed Job as outstanding without boxing it again,
+    /// unless it has a span or trace context to carry.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to be executed.
+    ///
+    /// # Return
+    ///
+    /// The Job, and the PendingGuard to queue next to it. With the `tracing`
+    /// feature or a propagator, the Job is wrapped like by `job` instead, and
+    /// carries its own PendingGuard.
+    pub(crate) fn boxed(self: &Arc<Self>, job: Job) -> (Job, Option<PendingGuard>) {
+        if cfg!(feature = "tracing") || self.propagator.is_some() {
+            return (self.job(job), None);
+        }
+
+        let mut guard = self.guard();
+
+        guard.deferrable = true;
+
+        (job, Some(guard))
+    }
+
+    /// Box a function, together with its PendingGuard and the current span,
+    /// into a Job.
+    ///
+    /// # Arguments
+    ///
+    /// - `guard` marks the job as outstanding until it is dropped.
+    /// - `f` is the function to be executed.
+    fn wrap<F>(&self, guard: PendingGuard, f: F) -> Job
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        #[cfg(feature = "tracing")]
+        let span = tracing::Span::current();
+
+        Box::new(move || {
+            let _guard = guard;
+
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+
+            f();
+        })
+    }
+
+    /// The number of outstanding jobs.
+    pub(crate) fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Block the current thread until there are no outstanding jobs.
+    pub(crate) fn wait(&self) {
+        self.wait_while_at_least(1);
+    }
+
+    /// Block the current thread until fewer than `limit` jobs are
+    /// outstanding.
+    pub(crate) fn wait_for_slot(&self) {
+        self.wait_while_at_least(self.limit);
+    }
+
+    /// Block the current thread while `count` is at least `n`.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` is at most `limit`, so the waiting thread is always notified.
+    fn wait_while_at_least(&self, n: usize) {
+        finish_deferred();
+
+        while self.count.load(Ordering::SeqCst) >= n {
+            let current = thread::current();
+
+            let mut waiters = self.lock_waiters();
+
+            waiters.push(current.clone());
+            self.waiting.fetch_add(1, Ordering::SeqCst);
+
+            drop(waiters);
+
+            // Checked again now that finishing jobs know to unpark us.
+            if self.count.load(Ordering::SeqCst) >= n {
+                thread::park();
+            }
+
+            let mut waiters = self.lock_waiters();
+
+            // Gone already if a finishing job unparked us.
+            if let Some(index) = waiters.iter().position(|t| t.id() == current.id()) {
+                waiters.swap_remove(index);
+                self.waiting.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Mark one outstanding job as finished.
+    ///
+    /// Only an atomic decrement, unless a thread is waiting.
+    fn finish(&self) {
+        let count = self.count.fetch_sub(1, Ordering::SeqCst) - 1;
+
+        if count < self.limit && self.waiting.load(Ordering::SeqCst) > 0 {
+            let mut waiters = self.lock_waiters();
+            let unparked = std::mem::take(&mut *waiters);
+
+            self.waiting.fetch_sub(unparked.len(), Ordering::SeqCst);
+
+            drop(waiters);
+
+            for waiter in unparked {
+                waiter.unpark();
+            }
+        }
+    }
+
+    /// Lock the waiting threads.
+    ///
+    /// Recovers from poisoning, since every change made under the lock is a
+    /// single push or removal.
+    fn lock_waiters(&self) -> MutexGuard<'_, Vec<Thread>> {
+        self.waiters.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if self.deferrable {
+            // Fails while the thread's locals are being destroyed, in which
+            // case there is no Worker left to wait for.
+            let deferred = DEFERRED.try_with(|deferred| match deferred.borrow_mut().as_mut() {
+                Some(deferred) => {
+                    deferred.push(Arc::clone(&self.pending));
+                    true
+                }
+                None => false,
+            });
+
+            if deferred == Ok(true) {
+                return;
+            }
+        }
+
+        self.pending.finish();
+    }
+}
+
+impl Drop for Deferred {
+    fn drop(&mut self) {
+        let finished = DEFERRED
+            .try_with(|deferred| deferred.replace(self.outer.take()))
+            .ok()
+            .flatten();
+
+        for pending in finished.into_iter().flatten() {
+            pending.finish();
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{ExecuteError, Job};
+use std::sync::Arc;
+
+/// The ability to run jobs, implemented by ThreadPool and TestPool.
+///
+/// Code that takes `&impl Pool` instead of a ThreadPool can be handed a
+/// TestPool in its tests, to check which jobs it submits without running
+/// them on other threads. Only `execute_boxed` has to be implemented, which
+/// keeps the trait usable as `dyn Pool`.
+///
+/// References, `Arc`s and `Box`es of a Pool are Pools as well, so a
+/// `&dyn Pool` or `Box<dyn Pool>` can be passed wherever an `impl Pool` is
+/// expected and still has the generic `execute`.
+pub trait Pool: Send + Sync {
+    /// Submit an already boxed job.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to be executed.
+    ///
+    /// # Errors
+    ///
+    /// Whatever the implementation can't accept the job for, e.g. the same
+    /// errors as `ThreadPool::execute`.
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError>;
+
+    /// Submit a job.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `execute_boxed`.
+    fn execute<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+        Self: Sized,
+    {
+        self.execute_boxed(Box::new(f))
+    }
+}
+
+impl<P: Pool + ?Sized> Pool for &P {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        (**self).execute_boxed(job)
+    }
+}
+
+impl<P: Pool + ?Sized> Pool for Arc<P> {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        (**self).execute_boxed(job)
+    }
+}
+
+impl<P: Pool + ?Sized> Pool for Box<P> {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        (**self).execute_boxed(job)
+    }
+}
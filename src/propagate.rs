@@ -0,0 +1,71 @@
+use crate::Job;
+use std::sync::Arc;
+
+/// Carries a trace context, such as a W3C `traceparent` or an OpenTelemetry
+/// `Context`, from the thread that submits a job to the Worker that runs
+/// it. See `ThreadPoolBuilder::with_trace_propagator`.
+///
+/// Implemented for every pair of closures `(extract, inject)` with the same
+/// signatures as the methods.
+pub trait TraceContextPropagator: Send + Sync + 'static {
+    /// The trace context, as it is moved between threads.
+    type Context: Send + 'static;
+
+    /// What keeps the trace context current on the Worker until it is
+    /// dropped, e.g. OpenTelemetry's `ContextGuard`.
+    type Guard;
+
+    /// Capture the trace context of the current thread. Called on the
+    /// submitting thread, whenever a job is submitted.
+    fn extract(&self) -> Self::Context;
+
+    /// Make a trace context current on the Worker. Called just before the
+    /// job runs; the Guard is dropped just after it returns or panics.
+    ///
+    /// # Arguments
+    ///
+    /// - `context` is what `extract` captured when the job was submitted.
+    fn inject(&self, context: Self::Context) -> Self::Guard;
+}
+
+impl<E, I, C, G> TraceContextPropagator for (E, I)
+where
+    E: Fn() -> C + Send + Sync + 'static,
+    I: Fn(C) -> G + Send + Sync + 'static,
+    C: Send + 'static,
+{
+    type Context = C;
+    type Guard = G;
+
+    fn extract(&self) -> C {
+        (self.0)()
+    }
+
+    fn inject(&self, context: C) -> G {
+        (self.1)(context)
+    }
+}
+
+/// A TraceContextPropagator with its types erased, so a ThreadPool can hold
+/// any of them.
+pub(crate) trait Propagate: Send + Sync {
+    /// Capture the current trace context into a job, to be injected around
+    /// it when it runs.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to run with the trace context.
+    fn wrap(self: Arc<Self>, job: Job) -> Job;
+}
+
+impl<P: TraceContextPropagator> Propagate for P {
+    fn wrap(self: Arc<Self>, job: Job) -> Job {
+        let context = self.extract();
+
+        Box::new(move || {
+            let _guard = self.inject(context);
+
+            job();
+        })
+    }
+}
@@ -0,0 +1,1699 @@
+use crate::{channel::Sender, external::ExternalThreads, pending::PendingGuard, ExecuteError, Job};
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use crossbeam_utils::Backoff;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    iter, mem,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{RecvTimeoutError, SyncSender},
+        Condvar, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "tower")]
+use std::task::{Poll, Waker};
+
+/// How urgently a job should be executed.
+///
+/// Workers always look for a job of the highest priority first, so `Low`
+/// jobs only run once there are no `High` or `Normal` jobs left to take.
+///
+/// # Variants
+///
+/// - `High` Runs before any waiting `Normal` or `Low` job.
+/// - `Normal` The priority used by `ThreadPool::execute`.
+/// - `Low` Runs only when no `High` or `Normal` job is waiting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+/// What to do with new jobs once a bounded queue is full.
+///
+/// # Variants
+///
+/// - `RejectNew` Fail `execute` with `ExecuteError::QueueFull`, or make
+///   `execute_blocking` wait for space.
+/// - `DropNewest` Accept the new job but drop it without running it.
+/// - `DropOldest` Drop the job that has been waiting longest to make room for
+///   the new one. `Low` jobs are dropped before `Normal` ones, and `Normal`
+///   jobs before `High` ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ShedPolicy {
+    #[default]
+    RejectNew,
+    DropNewest,
+    DropOldest,
+}
+
+/// What to do when a job is pushed onto a bounded queue that is full.
+///
+/// # Variants
+///
+/// - `Reject` Fail immediately with `ExecuteError::QueueFull`.
+/// - `Block` Wait until a Worker frees up space.
+/// - `Until` Wait until a Worker frees up space or the deadline passes, then
+///   fail with `ExecuteError::Timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Backpressure {
+    Reject,
+    Block,
+    Until(Instant),
+}
+
+/// Envelope struct
+///
+/// A Job waiting in the queue, along with when it was submitted.
+///
+/// # Members
+///
+/// - `job` The Job to be executed
+/// - `submitted_at` When the Job was put on the queue, or `None` for a job
+///   from `ThreadPool::execute_unchecked`, which doesn't read the clock
+/// - `deadline` When the Job goes stale and should be dropped instead of
+///   executed, or `None` if it never does
+/// - `producer` Which thread put the Job on the queue, as a `producer_id`
+/// - `guard` Counts the Job as pending, for a Job queued as it is by
+///   `ThreadPool::execute_boxed` rather than with its PendingGuard inside it
+pub(crate) struct Envelope {
+    pub(crate) job: Job,
+    pub(crate) submitted_at: Option<Instant>,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) producer: u64,
+    pub(crate) guard: Option<PendingGuard>,
+}
+
+impl Envelope {
+    /// Take the Job out of the Envelope, to be run somewhere other than a
+    /// Worker. See `with_guard`.
+    pub(crate) fn into_job(self) -> Job {
+        with_guard(self.job, self.guard)
+    }
+}
+
+/// Box a Job together with the PendingGuard that was queued next to it, so
+/// it still counts as pending wherever it ends up.
+///
+/// # Arguments
+///
+/// - `job` is the Job.
+/// - `guard` is its PendingGuard, or `None` if it carries its own.
+fn with_guard(job: Job, guard: Option<PendingGuard>) -> Job {
+    match guard {
+        Some(guard) => Box::new(move || {
+            let _guard = guard;
+
+            job();
+        }),
+        None => job,
+    }
+}
+
+/// The `producer` of every job from `ThreadPool::execute_unchecked`, which
+/// no thread is given.
+const UNCHECKED_PRODUCER: u64 = u64::MAX;
+
+/// The id given to the next thread that submits a job.
+static NEXT_PRODUCER: AtomicU64 = AtomicU64::new(0);
+
+/// An id for the current thread as a submitter of jobs, which unlike a
+/// ThreadId is cheap to get and compare.
+fn producer_id() -> u64 {
+    thread_local! {
+        static PRODUCER: u64 = NEXT_PRODUCER.fetch_add(1, Ordering::Relaxed);
+    }
+
+    PRODUCER.with(|id| *id)
+}
+
+/// A message taken off the queue by a Worker.
+///
+/// # Variants
+///
+/// - `Job` A Job to be executed.
+/// - `Terminate` Tells whichever Worker receives it to exit. The Worker sends
+///   its id through the enclosed Sender just before it does.
+pub(crate) enum Message {
+    Job(Envelope),
+    Terminate(Sender<usize>),
+}
+
+/// JobQueue struct
+///
+/// The queue shared by a ThreadPool and its Workers.
+///
+/// Submitted jobs go into a global Injector per Priority. Each Worker moves
+/// batches of jobs from there into its own LocalQueue and works through them
+/// without taking any lock. Idle Workers steal from the LocalQueues of busy
+/// Workers, so a batch taken by one Worker never waits behind a long job.
+/// Jobs sent to one specific Worker wait in its inbox instead, and jobs
+/// deferred until a later time wait in `state` until they become due.
+///
+/// A queue created with `JobQueue::external` has no Workers of its own and
+/// sends every job straight on to its ExternalThreads instead.
+///
+/// The Mutex is only taken by Workers that have run out of jobs and spun
+/// for a while without finding one, by pushers that need to wake them or are waiting for
+/// space, and for the rarely used inboxes and terminate messages.
+///
+/// # Members
+///
+/// - `injectors` The submitted jobs, one Injector per Priority in priority
+///   order
+/// - `stealers` The Stealers of every registered Worker's LocalQueue, keyed
+///   by its id
+/// - `generation` Bumped whenever `stealers` changes, so Workers only lock
+///   it to refresh their own copy
+/// - `len` The number of jobs waiting in the Injectors and LocalQueues,
+///   counted before a job is pushed and after it is taken
+/// - `capacity` The maximum number of waiting jobs, or `None` if unbounded
+/// - `shed` What to do with new jobs once `capacity` is reached
+/// - `shed_jobs` The number of jobs dropped because of `shed`
+/// - `batch_size` The most jobs a Worker takes from the shared queue at
+///   once, or `None` to leave it to crossbeam
+/// - `hold_batches` Whether a Worker keeps the batches it takes to itself,
+///   running them before it looks at the queue again
+/// - `closed` Whether the queue has stopped accepting jobs
+/// - `paused` Whether Workers have been told to stop taking jobs
+/// - `sleepers` The number of Workers waiting on `available`
+/// - `blocked` The number of pushers waiting on `space`
+/// - `control` The number of messages waiting in `state`, so Workers only
+///   take the lock when there is something there
+/// - `epoch` The Instant `next_due` is measured from
+/// - `next_due` When the earliest deferred job becomes due, in nanoseconds
+///   since `epoch`, or `u64::MAX` if there are none
+/// - `deferred` The number of deferred jobs that aren't due yet
+/// - `state` The inboxes, terminate messages and deferred jobs
+/// - `available` Notified when a message is pushed, the queue is resumed or
+///   the queue is closed
+/// - `space` Notified when a job is taken off a bounded queue or it is closed
+/// - `external` The threads jobs are sent to instead, if the queue was
+///   created with `JobQueue::external`
+pub(crate) struct JobQueue {
+    injectors: [Injector<Envelope>; 3],
+    stealers: RwLock<HashMap<usize, [Stealer<Envelope>; 3]>>,
+    generation: AtomicUsize,
+    len: AtomicUsize,
+    capacity: Option<usize>,
+    shed: ShedPolicy,
+    shed_jobs: AtomicUsize,
+    batch_size: Option<usize>,
+    hold_batches: bool,
+    closed: AtomicBool,
+    paused: AtomicBool,
+    sleepers: AtomicUsize,
+    blocked: AtomicUsize,
+    control: AtomicUsize,
+    epoch: Instant,
+    next_due: AtomicU64,
+    deferred: AtomicUsize,
+    state: Mutex<QueueState>,
+    available: Condvar,
+    space: Condvar,
+    external: Option<ExternalThreads>,
+}
+
+/// QueueState struct
+///
+/// # Members
+///
+/// - `inboxes` The jobs sent to one specific Worker, keyed by its id
+/// - `slots` The id of the Worker in each slot, ordered by slot so a group
+///   key always picks the same slot, even once its Worker is replaced
+/// - `terminate` Terminate messages waiting for an idle Worker
+/// - `terminate_one` Terminate messages for one specific Worker, keyed by
+///   its id
+/// - `deferred` Jobs that must not run before a given time, ordered by that
+///   time and then by when they were deferred
+/// - `next_seq` Breaks ties between deferred jobs due at the same time
+/// - `space_wakers` The tasks waiting in `poll_space`, with the `tower`
+///   feature
+struct QueueState {
+    inboxes: HashMap<usize, VecDeque<Envelope>>,
+    slots: BTreeMap<usize, usize>,
+    terminate: VecDeque<Sender<usize>>,
+    terminate_one: HashMap<usize, Sender<usize>>,
+    deferred: BTreeMap<(Instant, u64), Envelope>,
+    next_seq: u64,
+    #[cfg(feature = "tower")]
+    space_wakers: Vec<Waker>,
+}
+
+/// LocalQueue struct
+///
+/// The jobs a single Worker has taken from the Injectors but not started
+/// yet. Only the Worker that owns it can pop from it; every other Worker can
+/// only steal from it.
+///
+/// # Members
+///
+/// - `id` The id of the Worker that owns the LocalQueue
+/// - `deques` The jobs, one deque per Priority in priority order
+/// - `peers` The `generation` of the JobQueue's Stealers this Worker last
+///   copied, and the copy
+/// - `held` The rest of the batch the Worker took from a JobQueue that holds
+///   batches, along with the index of each job's Priority. No other Worker
+///   can steal these, and they no longer count towards the queue's length.
+pub(crate) struct LocalQueue {
+    id: usize,
+    deques: [Worker<Envelope>; 3],
+    peers: RefCell<(usize, Vec<[Stealer<Envelope>; 3]>)>,
+    held: RefCell<VecDeque<(usize, Envelope)>>,
+}
+
+impl QueueState {
+    /// Whether the Worker with the given id has a message to take.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker.
+    /// - `paused` is whether the queue is paused, in which case only
+    ///   terminate messages count.
+    fn has_message(&self, id: usize, paused: bool) -> bool {
+        let inbox = !paused && self.inboxes.get(&id).is_some_and(|inbox| !inbox.is_empty());
+
+        inbox || !self.terminate.is_empty() || self.terminate_one.contains_key(&id)
+    }
+}
+
+impl JobQueue {
+    /// Create a new, empty JobQueue.
+    ///
+    /// # Arguments
+    ///
+    /// - `capacity` is the maximum number of jobs that can wait in the queue,
+    ///   or `None` for no limit.
+    /// - `shed` is what to do with new jobs once the queue is full.
+    /// - `batch_size` is the most jobs a Worker takes at once, or `None`
+    ///   for crossbeam's default.
+    /// - `hold_batches` is whether a Worker runs the whole batch it took
+    ///   before it looks at the queue again.
+    pub(crate) fn new(
+        capacity: Option<usize>,
+        shed: ShedPolicy,
+        batch_size: Option<usize>,
+        hold_batches: bool,
+    ) -> Self {
+        Self {
+            injectors: Default::default(),
+            stealers: RwLock::new(HashMap::new()),
+            generation: AtomicUsize::new(0),
+            len: AtomicUsize::new(0),
+            capacity,
+            shed,
+            shed_jobs: AtomicUsize::new(0),
+            batch_size,
+            hold_batches,
+            closed: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            sleepers: AtomicUsize::new(0),
+            blocked: AtomicUsize::new(0),
+            control: AtomicUsize::new(0),
+            epoch: Instant::now(),
+            next_due: AtomicU64::new(u64::MAX),
+            deferred: AtomicUsize::new(0),
+            state: Mutex::new(QueueState {
+                inboxes: HashMap::new(),
+                slots: BTreeMap::new(),
+                terminate: VecDeque::new(),
+                terminate_one: HashMap::new(),
+                deferred: BTreeMap::new(),
+                next_seq: 0,
+                #[cfg(feature = "tower")]
+                space_wakers: Vec::new(),
+            }),
+            available: Condvar::new(),
+            space: Condvar::new(),
+            external: None,
+        }
+    }
+
+    /// Create a JobQueue that sends every job on to threads it doesn't own.
+    ///
+    /// Jobs never wait in the queue itself, so there is nothing for `pop`
+    /// to return and deferred jobs are rejected.
+    ///
+    /// # Arguments
+    ///
+    /// - `senders` are the SyncSenders the threads receive jobs from.
+    pub(crate) fn external(senders: Vec<SyncSender<Job>>) -> Self {
+        Self {
+            external: Some(ExternalThreads::new(senders)),
+            ..Self::new(None, ShedPolicy::RejectNew, None, false)
+        }
+    }
+
+    /// The number of jobs dropped because the queue was full, under
+    /// `ShedPolicy::DropNewest` or `ShedPolicy::DropOldest`.
+    pub(crate) fn shed_count(&self) -> usize {
+        self.shed_jobs.load(Ordering::Relaxed)
+    }
+
+    /// The capacity, ShedPolicy, batch size and whether batches are held
+    /// that the queue was created with.
+    pub(crate) fn limits(&self) -> (Option<usize>, ShedPolicy, Option<usize>, bool) {
+        (self.capacity, self.shed, self.batch_size, self.hold_batches)
+    }
+
+    /// Whether the queue was created with `JobQueue::external`.
+    pub(crate) fn is_external(&self) -> bool {
+        self.external.is_some()
+    }
+
+    /// Put the job at the back of its priority's Injector.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to put on the queue.
+    /// - `guard` is the PendingGuard to queue next to the Job, or `None` if
+    ///   the Job carries its own.
+    /// - `priority` is the Injector to put it in.
+    /// - `backpressure` is what to do if the queue is bounded and full.
+    /// - `deadline` is when the job should be dropped if it hasn't started,
+    ///   or `None` to always execute it.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the queue has been closed.
+    /// - `ExecuteError::Disconnected` if every external thread has stopped
+    ///   receiving.
+    /// - `ExecuteError::QueueFull` if the queue is full, the ShedPolicy is
+    ///   `ShedPolicy::RejectNew` and `backpressure` is
+    ///   `Backpressure::Reject`.
+    /// - `ExecuteError::Timeout` if the queue is still full at the deadline
+    ///   given by `Backpressure::Until`.
+    pub(crate) fn push(
+        &self,
+        job: Job,
+        guard: Option<PendingGuard>,
+        priority: Priority,
+        backpressure: Backpressure,
+        deadline: Option<Instant>,
+    ) -> Result<(), ExecuteError> {
+        if let Some(external) = &self.external {
+            if self.is_closed() {
+                return Err(ExecuteError::Shutdown);
+            }
+
+            // There is no Worker to check the deadline, so the job checks it
+            // itself.
+            let job = with_guard(job, guard);
+            let job: Job = match deadline {
+                Some(deadline) => Box::new(move || {
+                    if Instant::now() <= deadline {
+                        job();
+                    }
+                }),
+                None => job,
+            };
+
+            return external.send(job, backpressure);
+        }
+
+        let submitted_at = Instant::now();
+
+        if !self.reserve() {
+            match self.shed {
+                ShedPolicy::RejectNew => self.wait_for_space(backpressure)?,
+                // The oldest job's place in the queue goes to the new one.
+                ShedPolicy::DropOldest if self.evict_oldest() => {}
+                ShedPolicy::DropNewest | ShedPolicy::DropOldest => {
+                    self.shed_jobs.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+            }
+        }
+
+        // Checked after reserving so a closing Worker either sees the job
+        // counted or we see the queue closed.
+        if self.is_closed() {
+            self.release();
+            return Err(ExecuteError::Shutdown);
+        }
+
+        self.injectors[priority as usize].push(Envelope {
+            job,
+            submitted_at: Some(submitted_at),
+            deadline,
+            producer: producer_id(),
+            guard,
+        });
+        self.wake_one();
+
+        Ok(())
+    }
+
+    /// Put the job at the back of the `Normal` Injector without checking
+    /// whether the queue is external, full or closed, or recording when or
+    /// from where it was submitted. See `ThreadPool::execute_unchecked`.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to be executed.
+    pub(crate) fn push_unchecked(&self, job: Job) {
+        self.len.fetch_add(1, Ordering::SeqCst);
+        self.injectors[Priority::Normal as usize].push(Envelope {
+            job,
+            submitted_at: None,
+            deadline: None,
+            producer: UNCHECKED_PRODUCER,
+            guard: None,
+        });
+        self.wake_one();
+    }
+
+    /// Put as many of the jobs as fit at the back of their priority's
+    /// Injector, waking the Workers once for the whole batch.
+    ///
+    /// Stops pulling jobs from the iterator as soon as the queue is full, so
+    /// jobs that don't fit are never taken from it. Under
+    /// `ShedPolicy::DropOldest`, older jobs are dropped to make room instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `jobs` are the Jobs to put on the queue.
+    /// - `priority` is the Injector to put them in.
+    ///
+    /// # Return
+    ///
+    /// The number of jobs put on the queue.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the queue has been closed.
+    pub(crate) fn push_batch(
+        &self,
+        jobs: impl IntoIterator<Item = Job>,
+        priority: Priority,
+    ) -> Result<usize, ExecuteError> {
+        let mut jobs = jobs.into_iter().peekable();
+        let mut count = 0;
+
+        if let Some(external) = &self.external {
+            if self.is_closed() {
+                return Err(ExecuteError::Shutdown);
+            }
+
+            for job in jobs {
+                match external.send(job, Backpressure::Reject) {
+                    Ok(()) => count += 1,
+                    Err(ExecuteError::QueueFull) => break,
+                    Err(e) if count == 0 => return Err(e),
+                    Err(_) => break,
+                }
+            }
+
+            return Ok(count);
+        }
+
+        let submitted_at = Instant::now();
+
+        // Only evict a job once we know there is another one to replace it.
+        while self.reserve()
+            || (self.shed == ShedPolicy::DropOldest && jobs.peek().is_some() && self.evict_oldest())
+        {
+            if self.is_closed() {
+                self.release();
+                return Err(ExecuteError::Shutdown);
+            }
+
+            match jobs.next() {
+                Some(job) => self.injectors[priority as usize].push(Envelope {
+                    job,
+                    submitted_at: Some(submitted_at),
+                    deadline: None,
+                    producer: producer_id(),
+                    guard: None,
+                }),
+                None => {
+                    self.release();
+                    break;
+                }
+            }
+
+            count += 1;
+        }
+
+        if count == 0 && self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        self.wake_all();
+
+        Ok(count)
+    }
+
+    /// Put as many of a known number of jobs as fit at the back of their
+    /// priority's Injector, making room for all of them at once rather than
+    /// one at a time like `push_batch`.
+    ///
+    /// Jobs that don't fit are dropped. With `ShedPolicy::DropOldest`, or on
+    /// an external queue, this is `push_batch`.
+    ///
+    /// # Arguments
+    ///
+    /// - `jobs` are the Jobs to put on the queue.
+    /// - `priority` is the Injector to put them in.
+    ///
+    /// # Return
+    ///
+    /// The number of jobs put on the queue.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the queue has been closed.
+    pub(crate) fn push_many(
+        &self,
+        jobs: Vec<Job>,
+        priority: Priority,
+    ) -> Result<usize, ExecuteError> {
+        if self.external.is_some() || self.shed == ShedPolicy::DropOldest {
+            return self.push_batch(jobs, priority);
+        }
+
+        let count = self.reserve_up_to(jobs.len());
+
+        // Checked after reserving, like in `push`.
+        if self.is_closed() {
+            self.release_many(count);
+            return Err(ExecuteError::Shutdown);
+        }
+
+        let submitted_at = Instant::now();
+
+        for job in jobs.into_iter().take(count) {
+            self.injectors[priority as usize].push(Envelope {
+                job,
+                submitted_at: Some(submitted_at),
+                deadline: None,
+                producer: producer_id(),
+                guard: None,
+            });
+        }
+
+        self.wake_all();
+
+        Ok(count)
+    }
+
+    /// Put a job that was already accepted back at the back of the `Normal`
+    /// Injector.
+    ///
+    /// Meant to be called from a running job, so it neither waits for space
+    /// nor checks whether the queue is closed: the Worker running the job is
+    /// still around to take it.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to put back on the queue.
+    pub(crate) fn requeue(&self, job: Job) {
+        if let Some(external) = &self.external {
+            // The job is dropped if every thread has stopped receiving.
+            let _ = external.send(job, Backpressure::Block);
+            return;
+        }
+
+        self.len.fetch_add(1, Ordering::SeqCst);
+        self.injectors[Priority::Normal as usize].push(Envelope {
+            job,
+            submitted_at: Some(Instant::now()),
+            deadline: None,
+            producer: producer_id(),
+            guard: None,
+        });
+        self.wake_one();
+    }
+
+    /// Hold on to a job until `not_before`, then put it at the back of the
+    /// `Normal` Injector.
+    ///
+    /// Deferred jobs don't count towards the capacity of the queue until
+    /// they become due, so they may briefly push a bounded queue past it.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is the Job to put on the queue.
+    /// - `not_before` is the earliest time the job may be handed out.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the queue has been closed.
+    /// - `ExecuteError::Disconnected` if the queue was created with
+    ///   `JobQueue::external`, since no Worker would ever hand the job out.
+    pub(crate) fn push_after(&self, job: Job, not_before: Instant) -> Result<(), ExecuteError> {
+        if self.external.is_some() {
+            return Err(ExecuteError::Disconnected);
+        }
+
+        let mut state = self.lock();
+
+        if self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        let seq = state.next_seq;
+
+        state.next_seq += 1;
+        state.deferred.insert(
+            (not_before, seq),
+            Envelope {
+                job,
+                submitted_at: Some(not_before),
+                deadline: None,
+                producer: producer_id(),
+                guard: None,
+            },
+        );
+
+        self.deferred.fetch_add(1, Ordering::SeqCst);
+        self.next_due
+            .fetch_min(self.nanos_since_epoch(not_before), Ordering::SeqCst);
+
+        // A sleeping Worker may need to wake up sooner than it planned to.
+        self.available.notify_one();
+
+        Ok(())
+    }
+
+    /// Put a job in the inbox of every registered Worker, or send one to
+    /// every external thread.
+    ///
+    /// # Arguments
+    ///
+    /// - `job` is called once per Worker to create the Job sent to it.
+    ///
+    /// # Return
+    ///
+    /// The number of Workers a job was sent to.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the queue has been closed.
+    pub(crate) fn broadcast(&self, mut job: impl FnMut() -> Job) -> Result<usize, ExecuteError> {
+        let mut state = self.lock();
+
+        if self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        if let Some(external) = &self.external {
+            drop(state);
+            return Ok(external.broadcast(job));
+        }
+
+        let submitted_at = Instant::now();
+
+        for inbox in state.inboxes.values_mut() {
+            inbox.push_back(Envelope {
+                job: job(),
+                submitted_at: Some(submitted_at),
+                deadline: None,
+                producer: producer_id(),
+                guard: None,
+            });
+        }
+
+        let count = state.inboxes.len();
+
+        self.control.fetch_add(count, Ordering::SeqCst);
+        self.available.notify_all();
+
+        Ok(count)
+    }
+
+    /// Put a job in the inbox of the Worker chosen by `key`, so every job
+    /// pushed with the same key runs on the same Worker, in the order they
+    /// were pushed.
+    ///
+    /// The Worker is the one in the slot picked by `key` modulo the number of
+    /// registered Workers, so the mapping only changes when Workers come or
+    /// go, and a replacement Worker takes back the slot of the Worker it
+    /// replaces. Like
+    /// broadcast jobs, these don't count towards the capacity of the queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `key` picks the Worker.
+    /// - `job` is the Job to put on the queue.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the queue has been closed.
+    /// - `ExecuteError::Disconnected` if there are no registered Workers, or
+    ///   the external thread picked has stopped receiving.
+    pub(crate) fn push_ordered(&self, key: u64, job: Job) -> Result<(), ExecuteError> {
+        if let Some(external) = &self.external {
+            if self.is_closed() {
+                return Err(ExecuteError::Shutdown);
+            }
+
+            return external.send_to(key, job);
+        }
+
+        let mut state = self.lock();
+
+        if self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        let count = state.slots.len() as u64;
+        let id = key
+            .checked_rem(count)
+            .and_then(|index| state.slots.values().nth(index as usize))
+            .copied()
+            .ok_or(ExecuteError::Disconnected)?;
+        let inbox = state
+            .inboxes
+            .get_mut(&id)
+            .ok_or(ExecuteError::Disconnected)?;
+
+        inbox.push_back(Envelope {
+            job,
+            submitted_at: Some(Instant::now()),
+            deadline: None,
+            producer: producer_id(),
+            guard: None,
+        });
+
+        self.control.fetch_add(1, Ordering::SeqCst);
+
+        // Only the owner of the inbox can take the job, and there is no
+        // telling which sleeping Worker `notify_one` would wake.
+        self.available.notify_all();
+
+        Ok(())
+    }
+
+    /// Give the Worker with the given id an inbox and a LocalQueue.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker.
+    /// - `slot` is the slot the Worker takes, which decides the group keys
+    ///   it is sent jobs for by `push_ordered`.
+    ///
+    /// # Return
+    ///
+    /// The Worker's LocalQueue, to be passed to `pop`.
+    pub(crate) fn register(&self, id: usize, slot: usize) -> LocalQueue {
+        let local = LocalQueue {
+            id,
+            deques: [Worker::new_fifo(), Worker::new_fifo(), Worker::new_fifo()],
+            // Never a real generation, so the first steal makes a copy.
+            peers: RefCell::new((usize::MAX, Vec::new())),
+            held: RefCell::new(VecDeque::new()),
+        };
+
+        self.write_stealers()
+            .insert(id, local.deques.each_ref().map(Worker::stealer));
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        let mut state = self.lock();
+
+        state.inboxes.insert(id, VecDeque::new());
+        state.slots.insert(slot, id);
+
+        drop(state);
+
+        local
+    }
+
+    /// Remove the Worker that owns the LocalQueue, handing the jobs it had
+    /// taken but not started back to the Injectors.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the Worker's LocalQueue.
+    ///
+    /// # Return
+    ///
+    /// The jobs that were still waiting in the Worker's inbox.
+    pub(crate) fn unregister(&self, local: LocalQueue) -> Vec<Envelope> {
+        let inbox = self.forget(local.id);
+        let mut requeued = false;
+
+        for (priority, envelope) in local.held.take() {
+            // Counted again, since it stopped counting when it was held.
+            self.len.fetch_add(1, Ordering::SeqCst);
+            self.injectors[priority].push(envelope);
+            requeued = true;
+        }
+
+        for (deque, injector) in local.deques.iter().zip(&self.injectors) {
+            while let Some(envelope) = deque.pop() {
+                injector.push(envelope);
+                requeued = true;
+            }
+        }
+
+        if requeued {
+            self.wake_all();
+        }
+
+        inbox
+    }
+
+    /// Remove the inbox and Stealers of the Worker with the given id.
+    ///
+    /// Called on its own when the Worker's thread could not be spawned, since
+    /// it never took any jobs.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker.
+    ///
+    /// # Return
+    ///
+    /// The jobs that were still waiting in the Worker's inbox.
+    pub(crate) fn forget(&self, id: usize) -> Vec<Envelope> {
+        self.write_stealers().remove(&id);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        let mut state = self.lock();
+        let inbox: Vec<Envelope> = state.inboxes.remove(&id).map(Vec::from).unwrap_or_default();
+
+        state.slots.retain(|_, worker| *worker != id);
+
+        // Dropping the reply Sender tells whoever is waiting for it that the
+        // Worker is already gone.
+        let terminate = usize::from(state.terminate_one.remove(&id).is_some());
+
+        self.control
+            .fetch_sub(inbox.len() + terminate, Ordering::SeqCst);
+
+        inbox
+    }
+
+    /// Take the next message for the Worker that owns the LocalQueue,
+    /// blocking until there is one.
+    ///
+    /// Jobs in the Worker's inbox are handed out first, then terminate
+    /// messages, so that only Workers that have come back to the queue, i.e.
+    /// idle ones, are told to exit. Jobs are looked for in priority order,
+    /// first in the Worker's own LocalQueue, then in the Injector, then in
+    /// the other Workers' LocalQueues. After the queue is closed, the
+    /// remaining jobs are still handed out before Workers are told the queue
+    /// is disconnected.
+    ///
+    /// Deferred jobs are moved into the Injector as they become due. A
+    /// Worker is never timed out while there are deferred jobs left, so
+    /// there is always a Worker around to run them.
+    ///
+    /// A Worker that finds no job spins with exponential backoff for a
+    /// moment before it goes to sleep.
+    ///
+    /// If the queue holds batches, the rest of the Worker's batch is handed
+    /// out first, without looking for messages or whether the queue is
+    /// paused.
+    ///
+    /// While the queue is paused, only terminate messages are handed out and
+    /// `timeout` is not counted, so a Worker is never evicted for being idle
+    /// while it isn't allowed to work.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the LocalQueue of the Worker taking the message.
+    /// - `timeout` is how long to wait for a message, or `None` to wait
+    ///   forever.
+    ///
+    /// # Errors
+    ///
+    /// - `RecvTimeoutError::Timeout` if no message arrived within `timeout`.
+    /// - `RecvTimeoutError::Disconnected` if the queue is closed and empty.
+    pub(crate) fn pop(
+        &self,
+        local: &LocalQueue,
+        timeout: Option<Duration>,
+    ) -> Result<Message, RecvTimeoutError> {
+        if let Some((_, envelope)) = local.held.borrow_mut().pop_front() {
+            return Ok(Message::Job(envelope));
+        }
+
+        let mut deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let backoff = Backoff::new();
+
+        loop {
+            let paused = self.is_paused();
+
+            if self.next_due.load(Ordering::SeqCst) <= self.nanos_since_epoch(Instant::now()) {
+                self.promote_due();
+            }
+
+            if self.control.load(Ordering::SeqCst) > 0 {
+                if let Some(message) = self.take_message(local.id, paused) {
+                    return Ok(message);
+                }
+            }
+
+            if !paused {
+                if let Some(envelope) = self.find_job(local) {
+                    self.release();
+                    return Ok(Message::Job(envelope));
+                }
+
+                // Spinning a little before going to sleep saves a busy
+                // queue's pushers from taking the lock to wake us up.
+                if !backoff.is_completed() {
+                    backoff.snooze();
+                    continue;
+                }
+            }
+
+            let state = self.lock();
+
+            // Read again under the lock so a `resume` can't slip in before
+            // we start waiting.
+            if self.is_paused() {
+                if state.has_message(local.id, true) {
+                    continue;
+                }
+
+                self.sleepers.fetch_add(1, Ordering::SeqCst);
+
+                drop(
+                    self.available
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner),
+                );
+
+                self.sleepers.fetch_sub(1, Ordering::SeqCst);
+
+                backoff.reset();
+                deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+                continue;
+            }
+
+            if state.has_message(local.id, false) {
+                continue;
+            }
+
+            // Read before `len` so a job counted after we saw the queue empty
+            // is always rejected by `push`.
+            let closed = self.is_closed();
+
+            // A job that has been counted but not pushed yet, or that is
+            // being moved between queues, will show up shortly.
+            if self.len.load(Ordering::SeqCst) > 0 {
+                drop(state);
+                thread::yield_now();
+                continue;
+            }
+
+            if closed {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            self.sleepers.fetch_add(1, Ordering::SeqCst);
+
+            // Checked again now that pushers know to wake us up.
+            if self.len.load(Ordering::SeqCst) > 0 {
+                self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+
+            let next_due = state.deferred.keys().next().map(|&(at, _)| at);
+
+            let wake_at = match (deadline, next_due) {
+                (Some(deadline), Some(next_due)) => Some(deadline.min(next_due)),
+                (deadline, next_due) => deadline.or(next_due),
+            };
+
+            let timed_out = match wake_at {
+                Some(wake_at) => {
+                    let remaining = wake_at.saturating_duration_since(Instant::now());
+
+                    remaining.is_zero()
+                        || self
+                            .available
+                            .wait_timeout(state, remaining)
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .1
+                            .timed_out()
+                }
+                None => {
+                    drop(
+                        self.available
+                            .wait(state)
+                            .unwrap_or_else(PoisonError::into_inner),
+                    );
+
+                    false
+                }
+            };
+
+            self.sleepers.fetch_sub(1, Ordering::SeqCst);
+            backoff.reset();
+
+            if timed_out {
+                if next_due.is_none() {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+
+                deadline = timeout.map(|timeout| Instant::now() + timeout);
+            }
+        }
+    }
+
+    /// Take a job off the queue without waiting, for a thread that isn't
+    /// one of the Workers. Jobs in a Worker's inbox are left alone.
+    ///
+    /// # Return
+    ///
+    /// The highest priority job, or `None` if there isn't one or the queue
+    /// is paused.
+    pub(crate) fn try_pop(&self) -> Option<Envelope> {
+        if self.is_paused() {
+            return None;
+        }
+
+        if self.next_due.load(Ordering::SeqCst) <= self.nanos_since_epoch(Instant::now()) {
+            self.promote_due();
+        }
+
+        let stealers = self.read_stealers();
+
+        for (priority, injector) in self.injectors.iter().enumerate() {
+            let stolen = iter::repeat_with(|| {
+                injector.steal().or_else(|| {
+                    stealers
+                        .values()
+                        .map(|stealers| stealers[priority].steal())
+                        .collect()
+                })
+            })
+            .find(|steal| !steal.is_retry())
+            .and_then(Steal::success);
+
+            if stolen.is_some() {
+                drop(stealers);
+                self.release();
+
+                return stolen;
+            }
+        }
+
+        None
+    }
+
+    /// Tell the next idle Worker to exit.
+    ///
+    /// # Arguments
+    ///
+    /// - `reply` is where the Worker sends its id just before it exits.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the queue has been closed.
+    pub(crate) fn terminate(&self, reply: Sender<usize>) -> Result<(), ExecuteError> {
+        let mut state = self.lock();
+
+        if self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        state.terminate.push_back(reply);
+
+        self.control.fetch_add(1, Ordering::SeqCst);
+        self.available.notify_one();
+
+        Ok(())
+    }
+
+    /// Tell the Worker with the given id to exit once it is idle.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker.
+    /// - `reply` is where the Worker sends its id just before it exits.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the queue has been closed.
+    /// - `ExecuteError::Disconnected` if the Worker isn't registered, e.g.
+    ///   because it has already exited.
+    pub(crate) fn terminate_worker(
+        &self,
+        id: usize,
+        reply: Sender<usize>,
+    ) -> Result<(), ExecuteError> {
+        let mut state = self.lock();
+
+        if self.is_closed() {
+            return Err(ExecuteError::Shutdown);
+        }
+
+        if !state.inboxes.contains_key(&id) {
+            return Err(ExecuteError::Disconnected);
+        }
+
+        if state.terminate_one.insert(id, reply).is_none() {
+            self.control.fetch_add(1, Ordering::SeqCst);
+        }
+
+        self.available.notify_all();
+
+        Ok(())
+    }
+
+    /// Stop accepting jobs and wake everyone waiting on the queue.
+    ///
+    /// Jobs already in the queue are still handed out to Workers.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+
+        if let Some(external) = &self.external {
+            external.close();
+        }
+
+        let state = self.lock();
+
+        self.available.notify_all();
+        self.notify_space(state, true);
+    }
+
+    /// Whether the queue has been closed.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Stop handing jobs out to Workers until `resume` is called.
+    ///
+    /// Jobs can still be pushed while the queue is paused.
+    pub(crate) fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Start handing jobs out to Workers again and wake every one of them.
+    pub(crate) fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+
+        let _state = self.lock();
+
+        self.available.notify_all();
+    }
+
+    /// Whether Workers are currently not allowed to take jobs.
+    ///
+    /// Closing the queue overrides a pause, so the remaining jobs can drain.
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst) && !self.is_closed()
+    }
+
+    /// Take every waiting job off the queue without executing them.
+    ///
+    /// # Return
+    ///
+    /// The jobs, highest priority first.
+    pub(crate) fn drain(&self) -> Vec<Job> {
+        let stealers = self.read_stealers();
+        let mut jobs = Vec::new();
+
+        for (priority, injector) in self.injectors.iter().enumerate() {
+            Self::steal_all(|| injector.steal(), &mut jobs);
+
+            for stealer in stealers.values() {
+                Self::steal_all(|| stealer[priority].steal(), &mut jobs);
+            }
+        }
+
+        drop(stealers);
+
+        self.len.fetch_sub(jobs.len(), Ordering::SeqCst);
+
+        let mut state = self.lock();
+        let deferred = mem::take(&mut state.deferred);
+
+        self.deferred.fetch_sub(deferred.len(), Ordering::SeqCst);
+        self.next_due.store(u64::MAX, Ordering::SeqCst);
+        self.notify_space(state, true);
+
+        jobs.extend(deferred.into_values().map(Envelope::into_job));
+
+        jobs
+    }
+
+    /// The number of jobs waiting in the queue.
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// The number of deferred jobs that aren't due yet.
+    pub(crate) fn deferred_len(&self) -> usize {
+        self.deferred.load(Ordering::Relaxed)
+    }
+
+    /// Move every deferred job that has become due into the `Normal`
+    /// Injector, and wake the Workers if any were moved.
+    fn promote_due(&self) {
+        let mut state = self.lock();
+        let now = Instant::now();
+        let mut promoted = 0;
+
+        while let Some(entry) = state.deferred.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+
+            // Counted without reserving, since the job was already accepted.
+            self.len.fetch_add(1, Ordering::SeqCst);
+            self.injectors[Priority::Normal as usize].push(entry.remove());
+
+            promoted += 1;
+        }
+
+        let next_due = match state.deferred.keys().next() {
+            Some(&(at, _)) => self.nanos_since_epoch(at),
+            None => u64::MAX,
+        };
+
+        self.deferred.fetch_sub(promoted, Ordering::SeqCst);
+        self.next_due.store(next_due, Ordering::SeqCst);
+
+        if promoted > 0 {
+            self.available.notify_all();
+        }
+    }
+
+    /// The time between `epoch` and the given Instant, in nanoseconds.
+    ///
+    /// # Arguments
+    ///
+    /// - `instant` is the Instant to measure.
+    fn nanos_since_epoch(&self, instant: Instant) -> u64 {
+        let nanos = instant.saturating_duration_since(self.epoch).as_nanos();
+
+        u64::try_from(nanos).unwrap_or(u64::MAX - 1)
+    }
+
+    /// Count a job that is about to be pushed, if there is space for it.
+    ///
+    /// # Return
+    ///
+    /// Whether the job was counted. Always true for an unbounded queue.
+    fn reserve(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self
+                .len
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                    (len < capacity).then_some(len + 1)
+                })
+                .is_ok(),
+            None => {
+                self.len.fetch_add(1, Ordering::SeqCst);
+                true
+            }
+        }
+    }
+
+    /// Take up to `n` places in the queue at once. See `reserve`.
+    ///
+    /// # Return
+    ///
+    /// The number of places taken, which is less than `n` if the queue is
+    /// bounded and doesn't have room for all of them.
+    fn reserve_up_to(&self, n: usize) -> usize {
+        match self.capacity {
+            Some(capacity) => self
+                .len
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                    (len < capacity).then(|| len + n.min(capacity - len))
+                })
+                .map_or(0, |len| n.min(capacity - len)),
+            None => {
+                self.len.fetch_add(n, Ordering::SeqCst);
+                n
+            }
+        }
+    }
+
+    /// Drop the job that has been waiting longest, starting with the lowest
+    /// priority, without releasing its place in the queue.
+    ///
+    /// # Return
+    ///
+    /// Whether a job was dropped. Jobs that have been counted but not pushed
+    /// yet can't be dropped, so this may fail even though the queue is full.
+    fn evict_oldest(&self) -> bool {
+        let stealers = self.read_stealers();
+
+        for (priority, injector) in self.injectors.iter().enumerate().rev() {
+            let stolen = iter::repeat_with(|| {
+                injector.steal().or_else(|| {
+                    stealers
+                        .values()
+                        .map(|stealers| stealers[priority].steal())
+                        .collect()
+                })
+            })
+            .find(|steal| !steal.is_retry())
+            .and_then(Steal::success);
+
+            if let Some(envelope) = stolen {
+                drop(stealers);
+                drop(envelope);
+                self.shed_jobs.fetch_add(1, Ordering::Relaxed);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Stop counting a job that was taken off the queue or never pushed, and
+    /// wake a pusher waiting for space.
+    fn release(&self) {
+        self.release_many(1);
+    }
+
+    /// Give back `n` places in the queue at once. See `release`.
+    fn release_many(&self, n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        self.len.fetch_sub(n, Ordering::SeqCst);
+
+        if self.blocked.load(Ordering::SeqCst) > 0 {
+            self.notify_space(self.lock(), false);
+        }
+    }
+
+    /// Wake pushers waiting for space, after unlocking the queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `state` is the locked queue.
+    /// - `all` is whether to wake every pusher blocked in `wait_for_space`
+    ///   rather than just one. Tasks waiting in `poll_space` are always all
+    ///   woken, since they don't take the space until they are polled.
+    fn notify_space(&self, state: MutexGuard<'_, QueueState>, all: bool) {
+        if all {
+            self.space.notify_all();
+        } else {
+            self.space.notify_one();
+        }
+
+        #[cfg(feature = "tower")]
+        {
+            let mut state = state;
+            let wakers = mem::take(&mut state.space_wakers);
+
+            self.blocked.fetch_sub(wakers.len(), Ordering::SeqCst);
+            drop(state);
+            wakers.into_iter().for_each(Waker::wake);
+        }
+
+        #[cfg(not(feature = "tower"))]
+        drop(state);
+    }
+
+    /// Whether a job pushed now would be accepted without waiting, waking
+    /// the task once it would be if not.
+    ///
+    /// A job is only ever turned away by a bounded queue under
+    /// `ShedPolicy::RejectNew` whose capacity is reached, or a closed queue.
+    /// The space isn't reserved, so the push may still find the queue full.
+    ///
+    /// # Arguments
+    ///
+    /// - `waker` is woken once a job is taken off the queue.
+    ///
+    /// # Errors
+    ///
+    /// `ExecuteError::Shutdown` if the queue has been closed.
+    #[cfg(feature = "tower")]
+    pub(crate) fn poll_space(&self, waker: &Waker) -> Poll<Result<(), ExecuteError>> {
+        let has_space = || {
+            self.shed != ShedPolicy::RejectNew
+                || self
+                    .capacity
+                    .is_none_or(|capacity| self.len.load(Ordering::SeqCst) < capacity)
+        };
+
+        if self.is_closed() {
+            return Poll::Ready(Err(ExecuteError::Shutdown));
+        }
+
+        if has_space() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut state = self.lock();
+
+        if !state.space_wakers.iter().any(|w| w.will_wake(waker)) {
+            state.space_wakers.push(waker.clone());
+            self.blocked.fetch_add(1, Ordering::SeqCst);
+        }
+
+        // Checked again now that `release` knows to wake us up.
+        if self.is_closed() {
+            Poll::Ready(Err(ExecuteError::Shutdown))
+        } else if has_space() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Wait until a job can be counted on a full queue.
+    ///
+    /// # Arguments
+    ///
+    /// - `backpressure` is what to do while the queue is full.
+    ///
+    /// # Errors
+    ///
+    /// - `ExecuteError::Shutdown` if the queue is closed while waiting.
+    /// - `ExecuteError::QueueFull` if `backpressure` is `Backpressure::Reject`.
+    /// - `ExecuteError::Timeout` if the queue is still full at the deadline
+    ///   given by `Backpressure::Until`.
+    fn wait_for_space(&self, backpressure: Backpressure) -> Result<(), ExecuteError> {
+        let deadline = match backpressure {
+            Backpressure::Reject => return Err(ExecuteError::QueueFull),
+            Backpressure::Block => None,
+            Backpressure::Until(deadline) => Some(deadline),
+        };
+
+        let mut state = self.lock();
+
+        loop {
+            if self.is_closed() {
+                return Err(ExecuteError::Shutdown);
+            }
+
+            self.blocked.fetch_add(1, Ordering::SeqCst);
+
+            // Checked again now that Workers know to wake us up.
+            if self.reserve() {
+                self.blocked.fetch_sub(1, Ordering::SeqCst);
+                return Ok(());
+            }
+
+            state = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+
+                    if remaining.is_zero() {
+                        self.blocked.fetch_sub(1, Ordering::SeqCst);
+                        return Err(ExecuteError::Timeout);
+                    }
+
+                    self.space
+                        .wait_timeout(state, remaining)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .space
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+
+            self.blocked.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Take the next message waiting in `state` for the Worker with the
+    /// given id.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the Worker taking the message.
+    /// - `paused` is whether the queue is paused, in which case only
+    ///   terminate messages are taken.
+    fn take_message(&self, id: usize, paused: bool) -> Option<Message> {
+        let mut state = self.lock();
+
+        let envelope = if paused {
+            None
+        } else {
+            state.inboxes.get_mut(&id).and_then(VecDeque::pop_front)
+        };
+
+        let message = match envelope {
+            Some(envelope) => Message::Job(envelope),
+            None => Message::Terminate(
+                state
+                    .terminate_one
+                    .remove(&id)
+                    .or_else(|| state.terminate.pop_front())?,
+            ),
+        };
+
+        self.control.fetch_sub(1, Ordering::SeqCst);
+
+        Some(message)
+    }
+
+    /// Find a job for the Worker that owns the LocalQueue.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the LocalQueue of the Worker looking for a job.
+    fn find_job(&self, local: &LocalQueue) -> Option<Envelope> {
+        for (priority, (deque, injector)) in local.deques.iter().zip(&self.injectors).enumerate() {
+            if let Some(envelope) = deque.pop() {
+                return Some(envelope);
+            }
+
+            let stolen = iter::repeat_with(|| {
+                let stolen = match self.batch_size {
+                    Some(limit) => injector.steal_batch_with_limit_and_pop(deque, limit),
+                    None => injector.steal_batch_and_pop(deque),
+                };
+
+                stolen.or_else(|| self.steal_from_peers(local, priority))
+            })
+            .find(|steal| !steal.is_retry())
+            .and_then(Steal::success);
+
+            if stolen.is_some() {
+                if self.hold_batches {
+                    self.hold_batch(local, priority);
+                }
+
+                return stolen;
+            }
+        }
+
+        None
+    }
+
+    /// Move what is left in the Worker's deque of the given priority into
+    /// the jobs it holds, and stop counting them towards the queue's length.
+    ///
+    /// Another Worker may steal some of the batch before it is moved. Those
+    /// jobs are counted when they are taken, as usual.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the LocalQueue of the Worker that took the batch.
+    /// - `priority` is the index of the deque the batch went into.
+    fn hold_batch(&self, local: &LocalQueue, priority: usize) {
+        let mut held = local.held.borrow_mut();
+        let before = held.len();
+
+        while let Some(envelope) = local.deques[priority].pop() {
+            held.push_back((priority, envelope));
+        }
+
+        let moved = held.len() - before;
+
+        drop(held);
+        self.release_many(moved);
+    }
+
+    /// Steal a job of the given priority from any Worker's LocalQueue.
+    ///
+    /// Works from the LocalQueue's copy of the Stealers, which is refreshed
+    /// only when a Worker has been registered or forgotten since it was
+    /// made, so stealing takes no lock. A copied Stealer of a Worker that
+    /// has gone since finds its deque empty.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the LocalQueue of the Worker that is stealing.
+    /// - `priority` is the index of the deque to steal from.
+    fn steal_from_peers(&self, local: &LocalQueue, priority: usize) -> Steal<Envelope> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        let mut peers = local.peers.borrow_mut();
+
+        if peers.0 != generation {
+            *peers = (generation, self.read_stealers().values().cloned().collect());
+        }
+
+        peers
+            .1
+            .iter()
+            .map(|stealers| stealers[priority].steal())
+            .collect()
+    }
+
+    /// Wake one sleeping Worker, if there are any.
+    fn wake_one(&self) {
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _state = self.lock();
+
+            self.available.notify_one();
+        }
+    }
+
+    /// Wake every sleeping Worker.
+    fn wake_all(&self) {
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _state = self.lock();
+
+            self.available.notify_all();
+        }
+    }
+
+    /// Steal jobs until there are none left to steal.
+    ///
+    /// # Arguments
+    ///
+    /// - `steal` steals a single job.
+    /// - `jobs` is where the stolen jobs are collected.
+    fn steal_all(mut steal: impl FnMut() -> Steal<Envelope>, jobs: &mut Vec<Job>) {
+        loop {
+            match steal() {
+                Steal::Success(envelope) => jobs.push(envelope.into_job()),
+                Steal::Retry => continue,
+                Steal::Empty => break,
+            }
+        }
+    }
+
+    /// Lock the inboxes and terminate messages.
+    ///
+    /// If another thread panicked while holding the lock, the lock is
+    /// recovered instead of panicking too. Every change made under it is a
+    /// single push or pop, so the state can't be left half-updated, and one
+    /// bad thread must not take every Worker down with it.
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the Stealers for reading.
+    ///
+    /// Recovers from poisoning the same way `lock` does.
+    fn read_stealers(&self) -> RwLockReadGuard<'_, HashMap<usize, [Stealer<Envelope>; 3]>> {
+        self.stealers.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the Stealers for writing.
+    ///
+    /// Recovers from poisoning the same way `lock` does.
+    fn write_stealers(&self) -> RwLockWriteGuard<'_, HashMap<usize, [Stealer<Envelope>; 3]>> {
+        self.stealers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
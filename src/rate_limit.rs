@@ -0,0 +1,132 @@
+use crate::{ExecuteError, ThreadPool};
+use std::{
+    sync::{Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// RateLimitedPool struct
+///
+/// A ThreadPool that accepts at most a given number of jobs per second.
+/// `execute` sleeps the submitting thread for as long as it takes to stay
+/// under the limit, so jobs are spread out evenly instead of hitting a rate
+/// limited service in bursts. Up to one second's worth of jobs can be
+/// submitted at once after the pool has been idle.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the jobs are submitted to
+/// - `rate` The maximum number of jobs submitted per second
+/// - `bucket` The jobs that can be submitted right now
+#[derive(Debug)]
+pub struct RateLimitedPool {
+    pool: ThreadPool,
+    rate: f64,
+    bucket: Mutex<Bucket>,
+}
+
+/// Bucket struct
+///
+/// A token bucket, where each job submitted takes a token.
+///
+/// # Members
+///
+/// - `tokens` The number of tokens left. Negative when submitters are
+///   sleeping until their token is due.
+/// - `refilled_at` When `tokens` was last brought up to date
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimitedPool {
+    /// Wrap a ThreadPool so no more than `max_jobs_per_second` jobs are
+    /// submitted to it each second.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to submit the jobs to.
+    /// - `max_jobs_per_second` is the maximum submission rate.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if `max_jobs_per_second` is not a
+    /// positive, finite number.
+    pub fn new(pool: ThreadPool, max_jobs_per_second: f64) -> Self {
+        assert!(
+            max_jobs_per_second.is_finite() && max_jobs_per_second > 0.0,
+            "Unable to create the RateLimitedPool"
+        );
+
+        let burst = max_jobs_per_second.max(1.0);
+
+        Self {
+            pool,
+            rate: max_jobs_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Puts the job in the queue for a Worker to execute, once the rate
+    /// limit allows it.
+    ///
+    /// Callers are let through in the order they call this, even from
+    /// different threads.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `ThreadPool::execute`. The job still counts
+    /// towards the rate limit.
+    pub fn execute<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        thread::sleep(self.take_token());
+        self.pool.execute(f)
+    }
+
+    /// The ThreadPool the jobs are submitted to, e.g. to `join` it.
+    ///
+    /// Jobs submitted to it directly don't count towards the rate limit.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+
+    /// The maximum number of jobs submitted per second.
+    pub fn max_jobs_per_second(&self) -> f64 {
+        self.rate
+    }
+
+    /// Unwrap the ThreadPool.
+    pub fn into_inner(self) -> ThreadPool {
+        self.pool
+    }
+
+    /// Take a token from the bucket, even if it is empty.
+    ///
+    /// # Return
+    ///
+    /// How long to wait until the token is due.
+    fn take_token(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.rate;
+
+        bucket.tokens = (bucket.tokens + refill).min(self.rate.max(1.0)) - 1.0;
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
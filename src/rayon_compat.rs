@@ -0,0 +1,142 @@
+use crate::{global, JoinError, JoinHandle, ThreadPool};
+use std::{
+    panic,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+impl ThreadPool {
+    /// Run `f` on one of the Workers and wait for its result, like
+    /// `rayon::ThreadPool::install`.
+    ///
+    /// Called from one of this ThreadPool's own Workers, `f` runs straight
+    /// away on the current thread instead, since waiting for another Worker
+    /// could deadlock. So does it if it can't be submitted, e.g. because the
+    /// ThreadPool has been shut down or its queue is full.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to run.
+    ///
+    /// # Return
+    ///
+    /// The value returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic carries on on the current thread.
+    pub fn install<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if self.is_worker_thread() {
+            return f();
+        }
+
+        // Kept here rather than moved into the job, so it can still run if
+        // the job can't be submitted or is dropped without running.
+        let slot = Arc::new(Mutex::new(Some(f)));
+        let job = Arc::clone(&slot);
+        let handle = self.spawn(move || take(&job).map(|f| f()));
+
+        match handle.map(JoinHandle::join) {
+            Ok(Ok(Some(value))) => value,
+            Ok(Err(JoinError::Panicked(e))) => panic::resume_unwind(e.into_payload()),
+            _ => take(&slot).expect("the job took the function, so it ran")(),
+        }
+    }
+
+    /// Run `a` and `b` at the same time, like `rayon::join`, and wait for
+    /// both.
+    ///
+    /// `a` runs on the current thread while `b` waits for a Worker. If no
+    /// Worker has started `b` by the time `a` returns, the current thread
+    /// runs `b` as well, so calling this from inside a job never deadlocks.
+    /// This is `ThreadPool::join_pair` rather than `join`, which waits for
+    /// every job; `mtserver::join` runs it on the `global` ThreadPool.
+    ///
+    /// # Arguments
+    ///
+    /// - `a` is the function to run on the current thread.
+    /// - `b` is the function to run on a Worker.
+    ///
+    /// # Return
+    ///
+    /// The values returned by `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// If either function panics, the panic carries on on the current
+    /// thread, once `b` is no longer running.
+    pub fn join_pair<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send + 'static,
+        RA: Send,
+        RB: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Some(b)));
+        let job = Arc::clone(&slot);
+        let handle = self.spawn(move || take(&job).map(|b| b()));
+        let ra = panic::catch_unwind(panic::AssertUnwindSafe(a));
+
+        // If no Worker has taken `b` yet, none needs to.
+        let rb = match take(&slot) {
+            Some(b) => b(),
+            None => match handle.map(JoinHandle::join) {
+                Ok(Ok(Some(value))) => value,
+                Ok(Err(JoinError::Panicked(e))) => panic::resume_unwind(e.into_payload()),
+                _ => unreachable!("the job took the function, so it ran"),
+            },
+        };
+
+        match ra {
+            Ok(ra) => (ra, rb),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
+    /// Whether the current thread is one of the ThreadPool's Workers.
+    fn is_worker_thread(&self) -> bool {
+        let current = thread::current().id();
+
+        self.inner.lock_workers().iter().any(|worker| {
+            worker
+                .thread
+                .as_ref()
+                .is_some_and(|t| t.thread().id() == current)
+        })
+    }
+}
+
+/// Run `a` and `b` at the same time on the `global` ThreadPool, like
+/// `rayon::join`. See `ThreadPool::join_pair`.
+///
+/// # Arguments
+///
+/// - `a` is the function to run on the current thread.
+/// - `b` is the function to run on a Worker.
+///
+/// # Return
+///
+/// The values returned by `a` and `b`.
+///
+/// # Panics
+///
+/// If either function panics, or the global ThreadPool could not be
+/// created.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send + 'static,
+    RA: Send,
+    RB: Send + 'static,
+{
+    global().join_pair(a, b)
+}
+
+/// Take the function out of its slot, if nobody has yet.
+fn take<F>(slot: &Mutex<Option<F>>) -> Option<F> {
+    slot.lock().unwrap_or_else(PoisonError::into_inner).take()
+}
@@ -0,0 +1,79 @@
+use crate::{logging::log_warn, pending::Pending, queue::JobQueue};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+/// RetryJob struct
+///
+/// A job that puts itself back on the queue each time it panics, until it
+/// runs out of retries. See `ThreadPool::execute_with_retry`.
+///
+/// # Members
+///
+/// - `f` The function to be executed, shared between attempts
+/// - `retries_left` How many more times the job is retried if it panics
+/// - `queue` The queue the job is put back on
+/// - `pending` The ThreadPool's count of outstanding jobs, so a retry is
+///   waited on like any other job
+pub(crate) struct RetryJob {
+    f: Arc<dyn Fn() + Send + Sync>,
+    retries_left: u32,
+    queue: Arc<JobQueue>,
+    pending: Arc<Pending>,
+}
+
+impl RetryJob {
+    /// Create a new RetryJob.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    /// - `max_retries` is how many times the job is retried if it panics.
+    /// - `queue` is the queue to put the job back on.
+    /// - `pending` is the ThreadPool's count of outstanding jobs.
+    pub(crate) fn new(
+        f: Arc<dyn Fn() + Send + Sync>,
+        max_retries: u32,
+        queue: Arc<JobQueue>,
+        pending: Arc<Pending>,
+    ) -> Self {
+        Self {
+            f,
+            retries_left: max_retries,
+            queue,
+            pending,
+        }
+    }
+
+    /// Execute the job, putting it back on the queue if it panics and has
+    /// retries left.
+    ///
+    /// # Panics
+    ///
+    /// With the job's own panic payload once it has panicked on its last
+    /// attempt, so the Worker reports it like any other panicking job.
+    pub(crate) fn run(self) {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| (self.f)())) else {
+            return;
+        };
+
+        if self.retries_left == 0 {
+            panic::resume_unwind(payload);
+        }
+
+        log_warn!(
+            "Job panicked; retrying ({} retries left).",
+            self.retries_left - 1
+        );
+
+        let queue = Arc::clone(&self.queue);
+        let pending = Arc::clone(&self.pending);
+        let retry = Self {
+            retries_left: self.retries_left - 1,
+            ..self
+        };
+
+        queue.requeue(pending.job(move || retry.run()));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{JobSender, ThreadPool};
+use async_task::{Runnable, Task};
+use std::future::Future;
+
+impl ThreadPool {
+    /// A scheduling function for `async_task::spawn`, with the `async-task`
+    /// feature, so tasks from the async-task ecosystem can be run on the
+    /// Workers without an async runtime.
+    ///
+    /// async-task's `Schedule` trait is sealed, so a ThreadPool can't
+    /// implement it itself; the function returned here does through
+    /// async-task's impl for closures. Each time the task is woken, it is
+    /// submitted as a job that polls it once. Like a JobSender, the function
+    /// doesn't keep the ThreadPool alive.
+    ///
+    /// # Return
+    ///
+    /// The function. If a poll can't be submitted, e.g. because the
+    /// ThreadPool has been shut down or its queue is full, the task is
+    /// cancelled, which makes awaiting its Task panic; await
+    /// `Task::fallible` to get `None` instead.
+    pub fn scheduler(&self) -> impl Fn(Runnable) + Send + Sync + 'static {
+        let sender = JobSender::new(&self.inner);
+
+        move |runnable: Runnable| {
+            // Dropping the Runnable cancels the task.
+            let _ = sender.execute(move || {
+                runnable.run();
+            });
+        }
+    }
+
+    /// Spawn a future on the Workers with async-task, with the `async-task`
+    /// feature. See `scheduler`.
+    ///
+    /// `join` only waits for polls that have been submitted, not for the
+    /// future to complete.
+    ///
+    /// # Arguments
+    ///
+    /// - `future` is the future to run.
+    ///
+    /// # Return
+    ///
+    /// The Task, which resolves to the output of `future`. Dropping it
+    /// cancels the future; call `Task::detach` to let it run on.
+    pub fn spawn_task<F>(&self, future: F) -> Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (runnable, task) = async_task::spawn(future, self.scheduler());
+
+        runnable.schedule();
+        task
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    pending::{Pending, PendingGuard},
+    ExecuteError, Job, ThreadPool,
+};
+use std::{
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Scope struct
+///
+/// A handle for submitting jobs that may borrow from outside the scope. See
+/// `ThreadPool::scope`.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool the jobs are submitted to
+/// - `pending` The number of jobs submitted through this Scope that haven't
+///   finished yet
+/// - `panicked` Whether any of the jobs has panicked
+/// - `scope` Makes the Scope invariant over `'scope`
+/// - `env` Makes the Scope invariant over `'env`
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    pending: Arc<Pending>,
+    panicked: Arc<AtomicBool>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// ScopedJob struct
+///
+/// A lifetime-erased job along with the guard that keeps its Scope open.
+///
+/// # Members
+///
+/// - `job` The job itself. Declared first so it is always dropped before
+///   the guard, even if it never runs.
+/// - `panicked` Set if the job panics
+/// - `_guard` Marks the job as outstanding in its Scope
+struct ScopedJob {
+    job: Job,
+    panicked: Arc<AtomicBool>,
+    _guard: PendingGuard,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Create a new Scope with no outstanding jobs.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to submit the jobs to.
+    pub(crate) fn new(pool: &'scope ThreadPool) -> Self {
+        Self {
+            pool,
+            pending: Arc::new(Pending::new()),
+            panicked: Arc::new(AtomicBool::new(false)),
+            scope: PhantomData,
+            env: PhantomData,
+        }
+    }
+
+    /// Puts the job in the ThreadPool's queue for a Worker to execute.
+    ///
+    /// Unlike `ThreadPool::execute`, the job may borrow anything that
+    /// outlives the scope.
+    ///
+    /// # Arguments
+    ///
+    /// - `f` is the function to be executed.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `ThreadPool::execute`.
+    pub fn execute<F>(&'scope self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(f);
+
+        // SAFETY: `ThreadPool::scope` doesn't return until every guard taken
+        // from `pending` has been dropped. The guard is dropped after the job,
+        // whether it ran or not, so the job can't outlive `'scope`.
+        let job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+
+        let scoped = ScopedJob {
+            job,
+            panicked: Arc::clone(&self.panicked),
+            _guard: self.pending.guard(),
+        };
+
+        self.pool.execute(move || scoped.run())
+    }
+
+    /// Block the current thread until every job submitted through this Scope
+    /// has finished.
+    ///
+    /// # Return
+    ///
+    /// Whether any of the jobs panicked.
+    pub(crate) fn wait(&self) -> bool {
+        self.pending.wait();
+        self.panicked.load(Ordering::Relaxed)
+    }
+}
+
+impl ScopedJob {
+    /// Execute the job, recording whether it panicked.
+    fn run(self) {
+        if panic::catch_unwind(AssertUnwindSafe(self.job)).is_err() {
+            self.panicked.store(true, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{ExecuteError, Job, ThreadPool};
+use std::{
+    future::{self, Ready},
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// With the `tower` feature, a ThreadPool is a Service that executes Jobs.
+///
+/// `poll_ready` is pending while a bounded queue under
+/// `ShedPolicy::RejectNew` is full, and `call` submits the job with
+/// `execute_boxed`. The returned future is ready as soon as the job is in
+/// the queue; it doesn't wait for the job to run.
+impl Service<Job> for ThreadPool {
+    type Response = ();
+    type Error = ExecuteError;
+    type Future = Ready<Result<(), ExecuteError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ExecuteError>> {
+        self.inner.queue.poll_space(cx.waker())
+    }
+
+    fn call(&mut self, job: Job) -> Self::Future {
+        future::ready(self.execute_boxed(job))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{JoinError, PanicError};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// SpawnHandle struct
+///
+/// A future that resolves to the result of a job submitted with
+/// `ThreadPool::spawn_async`, for awaiting a job from async code instead of
+/// blocking in `JoinHandle::join`. The job runs whether or not the
+/// SpawnHandle is polled or dropped.
+///
+/// # Members
+///
+/// - `slot` Where the job leaves its result, shared with the job
+pub struct SpawnHandle<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+/// Completer struct
+///
+/// The job's end of a SpawnHandle. Dropping it, whether or not a result was
+/// left, wakes the task awaiting the SpawnHandle.
+///
+/// # Members
+///
+/// - `slot` Where to leave the job's result
+pub(crate) struct Completer<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+/// Slot struct
+///
+/// # Members
+///
+/// - `result` The job's result, or its panic, until the SpawnHandle takes it
+/// - `closed` Whether the Completer has been dropped, so no result is coming
+///   after `result`
+/// - `waker` The task to wake once the job is done
+struct Slot<T> {
+    result: Option<thread::Result<T>>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Create a SpawnHandle and the Completer that resolves it.
+pub(crate) fn spawn_handle<T>() -> (Completer<T>, SpawnHandle<T>) {
+    let slot = Arc::new(Mutex::new(Slot {
+        result: None,
+        closed: false,
+        waker: None,
+    }));
+
+    (
+        Completer {
+            slot: Arc::clone(&slot),
+        },
+        SpawnHandle { slot },
+    )
+}
+
+impl<T> Completer<T> {
+    /// Leave the job's result for the SpawnHandle.
+    ///
+    /// # Arguments
+    ///
+    /// - `result` is the value returned by the job, or its panic.
+    pub(crate) fn complete(self, result: thread::Result<T>) {
+        lock(&self.slot).result = Some(result);
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        let mut slot = lock(&self.slot);
+
+        slot.closed = true;
+
+        let waker = slot.waker.take();
+
+        drop(slot);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for SpawnHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = lock(&self.slot);
+
+        match slot.result.take() {
+            Some(Ok(value)) => Poll::Ready(Ok(value)),
+            Some(Err(payload)) => Poll::Ready(Err(JoinError::Panicked(PanicError(payload)))),
+            None if slot.closed => Poll::Ready(Err(JoinError::Disconnected)),
+            None => {
+                match &mut slot.waker {
+                    Some(waker) => waker.clone_from(cx.waker()),
+                    waker => *waker = Some(cx.waker().clone()),
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for SpawnHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpawnHandle").finish_non_exhaustive()
+    }
+}
+
+fn lock<T>(slot: &Mutex<Slot<T>>) -> MutexGuard<'_, Slot<T>> {
+    slot.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,23 @@
+/// The stage of its life a ThreadPool is in. See `ThreadPool::state`.
+///
+/// A ThreadPool starts out `Created` and becomes `Running` once the first
+/// job is submitted. From then on `pause` and `resume` move it between
+/// `Running` and `Paused`, and shutting it down moves it to `Shutdown` from
+/// any other state. `Shutdown` is final.
+///
+/// # Variants
+///
+/// - `Created` No job has been submitted yet.
+/// - `Running` Workers are taking jobs off the queue.
+/// - `Paused` Workers have been told to stop taking jobs. Jobs are still
+///   accepted and wait in the queue.
+/// - `Shutdown` No more jobs are accepted; submitting one fails with
+///   `ExecuteError::Shutdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PoolState {
+    Created,
+    Running,
+    Paused,
+    Shutdown,
+}
@@ -0,0 +1,136 @@
+use crate::Job;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+/// JobId struct
+///
+/// An opaque identifier for a job submitted with
+/// `ThreadPool::execute_with_id`, used to cancel it with
+/// `ThreadPool::cancel_job`. Ids are never reused by the same ThreadPool.
+///
+/// # Members
+///
+/// - `0` The number of jobs that were given an id before this one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobId(u64);
+
+/// JobRegistry struct
+///
+/// The jobs submitted with an id that haven't started yet.
+///
+/// # Members
+///
+/// - `next_id` The id given to the next job
+/// - `jobs` The cancellation flag of every job that hasn't started, keyed by
+///   its id
+#[derive(Debug, Default)]
+pub(crate) struct JobRegistry {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<JobId, Arc<AtomicBool>>>,
+}
+
+/// TaggedJob struct
+///
+/// A job that can be cancelled by id until a Worker starts it. Its entry in
+/// the JobRegistry is removed when it starts, or when it is dropped without
+/// being run.
+///
+/// # Members
+///
+/// - `id` The id the job was submitted with
+/// - `cancelled` Set by `JobRegistry::cancel`
+/// - `registry` The JobRegistry the job is listed in
+/// - `job` The job itself
+pub(crate) struct TaggedJob {
+    id: JobId,
+    cancelled: Arc<AtomicBool>,
+    registry: Arc<JobRegistry>,
+    job: Option<Job>,
+}
+
+impl JobRegistry {
+    /// List a new job in the registry.
+    ///
+    /// # Arguments
+    ///
+    /// - `registry` is the JobRegistry to list the job in.
+    /// - `job` is the job to list.
+    ///
+    /// # Return
+    ///
+    /// The job's id, and the job wrapped so it checks for cancellation
+    /// before running.
+    pub(crate) fn register(registry: &Arc<Self>, job: Job) -> (JobId, TaggedJob) {
+        let id = JobId(registry.next_id.fetch_add(1, Ordering::Relaxed));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        registry.lock().insert(id, Arc::clone(&cancelled));
+
+        let tagged = TaggedJob {
+            id,
+            cancelled,
+            registry: Arc::clone(registry),
+            job: Some(job),
+        };
+
+        (id, tagged)
+    }
+
+    /// Cancel the job with the given id, if it hasn't started yet.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the id of the job to cancel.
+    ///
+    /// # Return
+    ///
+    /// Whether the job was found and cancelled.
+    pub(crate) fn cancel(&self, id: JobId) -> bool {
+        match self.lock().remove(&id) {
+            Some(cancelled) => {
+                cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lock the map of jobs that haven't started.
+    ///
+    /// A poisoned lock is recovered, since the map is only ever changed by a
+    /// single insert or remove.
+    fn lock(&self) -> MutexGuard<'_, HashMap<JobId, Arc<AtomicBool>>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TaggedJob {
+    /// Execute the job unless it has been cancelled.
+    ///
+    /// The job is removed from the registry first, under the same lock
+    /// `cancel` takes, so a job is either cancelled before it starts or
+    /// can no longer be cancelled at all.
+    pub(crate) fn run(mut self) {
+        self.registry.lock().remove(&self.id);
+
+        if !self.cancelled.load(Ordering::Relaxed) {
+            if let Some(job) = self.job.take() {
+                job();
+            }
+        }
+    }
+}
+
+impl Drop for TaggedJob {
+    fn drop(&mut self) {
+        // The job was discarded without running, e.g. by `shutdown_now`.
+        if self.job.is_some() {
+            self.registry.lock().remove(&self.id);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{ExecuteError, Job, Pool};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// TestPool struct
+///
+/// A Pool for tests, which keeps the jobs submitted to it instead of
+/// running them. The test can then check how many were submitted with
+/// `job_count`, and run them on the current thread with `run_all`.
+///
+/// # Members
+///
+/// - `jobs` The jobs submitted and not run yet, in the order they came in
+#[derive(Default)]
+pub struct TestPool {
+    jobs: Mutex<VecDeque<Job>>,
+}
+
+impl TestPool {
+    /// Create a new TestPool with no jobs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run every job on the current thread, in the order they were
+    /// submitted.
+    ///
+    /// Jobs submitted by the jobs being run are run too, after the ones
+    /// already waiting, until there are none left. A panic in a job
+    /// unwinds out of `run_all`, leaving the jobs after it waiting.
+    pub fn run_all(&self) {
+        loop {
+            // Unlocked before the job runs, so it can submit more jobs.
+            let Some(job) = self.lock().pop_front() else {
+                return;
+            };
+
+            job();
+        }
+    }
+
+    /// The number of jobs submitted and not run yet.
+    pub fn job_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Lock the list of jobs.
+    ///
+    /// A poisoned lock is recovered, since a job only panics while the lock
+    /// isn't held.
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Job>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Pool for TestPool {
+    fn execute_boxed(&self, job: Job) -> Result<(), ExecuteError> {
+        self.lock().push_back(job);
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TestPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestPool")
+            .field("jobs", &self.job_count())
+            .finish()
+    }
+}
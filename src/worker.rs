@@ -0,0 +1,437 @@
+use crate::{
+    audit::Outcome,
+    channel::Sender,
+    hooks::Hooks,
+    logging::{log_debug, log_error, log_info, log_warn},
+    metrics::{Counters, WorkerTimes},
+    panic_hook, pending,
+    queue::{Envelope, JobQueue, LocalQueue, Message},
+    JobMetrics, PanicError,
+};
+use std::{
+    fmt, io,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::RecvTimeoutError,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The id of the next job to run on any ThreadPool, for the `task.id` of its
+/// span.
+#[cfg(feature = "tracing")]
+static NEXT_TASK_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Worker struct
+///
+/// # Members
+///
+/// - `id` The id representing this Worker
+/// - `thread` The thread running the Job
+/// - `evicted` Set by the thread when it exits for being idle, which unlike
+///   any other early exit isn't a failure
+/// - `times` How long the Worker has spent idle and busy
+/// - `slot` Decides which jobs submitted with `execute_ordered` the Worker
+///   runs, and is passed on to the Worker that replaces it
+pub(crate) struct Worker {
+    pub(crate) id: usize,
+    pub(crate) slot: usize,
+    pub(crate) thread: Option<thread::JoinHandle<()>>,
+    evicted: Arc<AtomicBool>,
+    pub(crate) times: Arc<WorkerTimes>,
+}
+
+/// WorkerConfig struct
+///
+/// The settings used to spawn every Worker's thread.
+///
+/// # Members
+///
+/// - `thread_name_prefix` The prefix of each thread's name, `{prefix}-{id}`
+/// - `stack_size` The stack size of each thread, or `None` for the OS default
+/// - `max_restarts` The number of times a crashed Worker loop is restarted
+/// - `keep_alive` How long a Worker waits for a job before exiting, or `None`
+///   to wait forever
+/// - `min_threads` The number of Workers that never exit for being idle
+/// - `max_consecutive_jobs` How many jobs in a row from the same thread a
+///   Worker runs before yielding, or `None` to never yield
+/// - `hooks` The callbacks run by every Worker
+/// - `cores` The CPU cores the Workers are pinned to, by id, or empty to
+///   leave them unpinned
+#[derive(Debug, Clone)]
+pub(crate) struct WorkerConfig {
+    pub(crate) thread_name_prefix: String,
+    pub(crate) stack_size: Option<usize>,
+    pub(crate) max_restarts: usize,
+    pub(crate) keep_alive: Option<Duration>,
+    pub(crate) min_threads: usize,
+    pub(crate) max_consecutive_jobs: Option<usize>,
+    pub(crate) hooks: Hooks,
+    #[cfg(feature = "affinity")]
+    pub(crate) cores: Vec<usize>,
+}
+
+/// Why a Worker's thread is exiting.
+enum Exit {
+    Disconnected,
+    Terminated(Sender<usize>),
+    Evicted,
+    Crashed,
+}
+
+impl Worker {
+    /// Create a new worker.
+    ///
+    /// Panics inside a job are caught so the Worker keeps processing jobs.
+    /// If the Worker's loop itself panics, the loop is restarted with the
+    /// same id until it has been restarted `max_restarts` times, at which
+    /// point the Worker gives up and its thread exits. Jobs still waiting in
+    /// the Worker's inbox are executed before the thread exits.
+    ///
+    /// The `thread_local_init` and `on_thread_start` hooks run once before
+    /// the first job, and the `on_thread_stop` hook runs once just before the
+    /// thread exits. None of them runs again when the loop is restarted.
+    ///
+    /// The caller is expected to have already counted the Worker in the
+    /// `live` counter; the Worker removes itself from it when it exits.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the ID corresponding to this Worker.
+    /// - `slot` is the slot the Worker takes in the queue.
+    /// - `config` are the settings used to spawn the Worker's thread.
+    /// - `queue` is the queue for the Worker to get it's Job from.
+    /// - `counters` are the utilization counters shared with the ThreadPool.
+    ///
+    /// # Return
+    ///
+    /// A new Worker struct, or the error returned by the OS if the thread
+    /// could not be spawned.
+    pub(crate) fn new(
+        id: usize,
+        slot: usize,
+        config: &WorkerConfig,
+        queue: Arc<JobQueue>,
+        counters: Arc<Counters>,
+    ) -> io::Result<Self> {
+        let builder = match &config.hooks.thread_builder {
+            Some(thread_builder) => thread_builder(id),
+            None => {
+                let builder =
+                    thread::Builder::new().name(format!("{}-{}", config.thread_name_prefix, id));
+
+                match config.stack_size {
+                    Some(bytes) => builder.stack_size(bytes),
+                    None => builder,
+                }
+            }
+        };
+
+        let local = queue.register(id, slot);
+
+        let config = config.clone();
+        let max_restarts = config.max_restarts;
+        let thread_queue = Arc::clone(&queue);
+        let evicted = Arc::new(AtomicBool::new(false));
+        let thread_evicted = Arc::clone(&evicted);
+        let times = Arc::new(WorkerTimes::default());
+        let thread_times = Arc::clone(&times);
+
+        let thread = builder
+            .spawn(move || {
+                let queue = thread_queue;
+                let mut restarts = 0;
+
+                #[cfg(feature = "affinity")]
+                Self::pin(id, &config.cores);
+
+                config.hooks.thread_local_init(id);
+                config.hooks.thread_start(id);
+
+                let exit = loop {
+                    match panic::catch_unwind(AssertUnwindSafe(|| {
+                        Self::run(&local, id, &queue, &counters, &thread_times, &config)
+                    })) {
+                        Ok(exit) => break exit,
+                        Err(_) if restarts == max_restarts => {
+                            log_error!("Worker {id} crashed {restarts} times; giving up.");
+                            break Exit::Crashed;
+                        }
+                        Err(_) => {
+                            restarts += 1;
+                            log_warn!(
+                                "Worker {id} crashed; restarting ({restarts}/{max_restarts})."
+                            );
+                        }
+                    }
+                };
+
+                // Jobs sent to this Worker must run on its thread, even if
+                // they arrived just as it was leaving.
+                for envelope in queue.unregister(local) {
+                    Self::execute(id, envelope, &counters, &thread_times, &config.hooks);
+                }
+
+                config.hooks.thread_stop(id);
+
+                match exit {
+                    // Eviction already removed the Worker from the live count.
+                    Exit::Evicted => thread_evicted.store(true, Ordering::Relaxed),
+                    Exit::Disconnected | Exit::Crashed => {
+                        counters.live.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    Exit::Terminated(reply) => {
+                        counters.live.fetch_sub(1, Ordering::Relaxed);
+
+                        // The ThreadPool may have stopped waiting, which is fine.
+                        let _ = reply.send(id);
+                    }
+                }
+            })
+            .inspect_err(|_| {
+                queue.forget(id);
+            })?;
+
+        Ok(Self {
+            id,
+            slot,
+            thread: Some(thread),
+            evicted,
+            times,
+        })
+    }
+
+    /// Whether the Worker's thread has stopped for any reason other than
+    /// being evicted for being idle, e.g. because its loop crashed too many
+    /// times.
+    ///
+    /// A Worker whose thread has already been joined counts as dead too.
+    pub(crate) fn is_dead(&self) -> bool {
+        let finished = self.thread.as_ref().is_none_or(|t| t.is_finished());
+
+        finished && !self.evicted.load(Ordering::Relaxed)
+    }
+
+    /// Pin the current thread to the core the Worker with the given id is
+    /// assigned, wrapping around `cores`.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the ID corresponding to this Worker.
+    /// - `cores` are the ids of the cores to pin the Workers to.
+    #[cfg(feature = "affinity")]
+    fn pin(id: usize, cores: &[usize]) {
+        if cores.is_empty() {
+            return;
+        }
+
+        let core = cores[id % cores.len()];
+
+        if core_affinity::set_for_current(core_affinity::CoreId { id: core }) {
+            log_debug!("Worker {id} pinned to core {core}.");
+        } else {
+            log_warn!("Worker {id} could not be pinned to core {core}; running unpinned.");
+        }
+    }
+
+    /// Receive and execute jobs until the queue is closed and empty, the
+    /// Worker is told to terminate, or the Worker is evicted for being idle.
+    ///
+    /// # Arguments
+    ///
+    /// - `local` is the Worker's own queue of jobs taken from `queue`.
+    /// - `id` is the ID corresponding to this Worker.
+    /// - `queue` is the queue for the Worker to get it's Job from.
+    /// - `counters` are the utilization counters shared with the ThreadPool.
+    /// - `times` are where the Worker records how long it is idle and busy.
+    /// - `config` are the settings the Worker was spawned with.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the `on_job_complete` hook panics.
+    fn run(
+        local: &LocalQueue,
+        id: usize,
+        queue: &JobQueue,
+        counters: &Counters,
+        times: &WorkerTimes,
+        config: &WorkerConfig,
+    ) -> Exit {
+        // The thread the last jobs came from, and how many of them in a row.
+        let mut streak = None;
+
+        loop {
+            let waiting = Instant::now();
+            let message = queue.pop(local, config.keep_alive);
+
+            times.add_idle(waiting.elapsed());
+
+            match message {
+                Ok(Message::Job(envelope)) => {
+                    let producer = envelope.producer;
+
+                    Self::execute(id, envelope, counters, times, &config.hooks);
+
+                    if let Some(max) = config.max_consecutive_jobs {
+                        let n = match streak {
+                            Some((last, n)) if last == producer => n + 1,
+                            _ => 1,
+                        };
+
+                        streak = Some((producer, n));
+
+                        if n >= max {
+                            thread::yield_now();
+                            streak = None;
+                        }
+                    }
+                }
+                Ok(Message::Terminate(reply)) => {
+                    log_info!("Worker {id} told to terminate; shutting down.");
+                    return Exit::Terminated(reply);
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let evicted = counters
+                        .live
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |live| {
+                            (live > config.min_threads).then(|| live - 1)
+                        })
+                        .is_ok();
+
+                    if evicted {
+                        log_info!("Worker {id} idle for too long; shutting down.");
+                        return Exit::Evicted;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    log_info!("Worker {id} disconnected; shutting down.");
+                    return Exit::Disconnected;
+                }
+            }
+        }
+    }
+
+    /// Whether the Worker's thread is still running.
+    pub(crate) fn is_alive(&self) -> bool {
+        self.thread.as_ref().is_some_and(|t| !t.is_finished())
+    }
+
+    /// Execute a job taken off the queue and report how long it took.
+    ///
+    /// A job whose deadline has already passed is dropped without being
+    /// executed.
+    ///
+    /// # Arguments
+    ///
+    /// - `id` is the ID corresponding to this Worker.
+    /// - `envelope` is the job along with when it was submitted.
+    /// - `counters` are the utilization counters shared with the ThreadPool.
+    /// - `times` are where the Worker records how long the job ran.
+    /// - `hooks` are the callbacks to report the job to.
+    pub(crate) fn execute(
+        id: usize,
+        envelope: Envelope,
+        counters: &Counters,
+        times: &WorkerTimes,
+        hooks: &Hooks,
+    ) {
+        // Declared first so it is dropped last, once the job has been
+        // recorded and the hooks have run.
+        let _deferred = pending::defer();
+        let _guard = envelope.guard;
+
+        if envelope
+            .deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+        {
+            log_debug!("Worker {id} got a job past its deadline; dropping.");
+            counters.expired.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        log_debug!("Worker {id} got a job; executing.");
+
+        counters.active.fetch_add(1, Ordering::Relaxed);
+
+        let started = Instant::now();
+        let queue_wait = envelope
+            .submitted_at
+            .map(|submitted_at| started.duration_since(submitted_at));
+
+        if let Some(queue_wait) = queue_wait {
+            counters.queue_wait.record(queue_wait);
+        }
+
+        // Named and shaped like the spans tokio-console shows for
+        // `spawn_blocking`, so the jobs show up there as blocking tasks.
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!(
+            "task",
+            task.id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed),
+            task.kind = "blocking",
+            task.worker = id,
+            task.poll_duration = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let entered = span.enter();
+        #[cfg(feature = "tracing")]
+        tracing::trace!("job started");
+
+        let audit = hooks.audit_log.as_ref().map(|log| log.start());
+        let result =
+            panic_hook::with_job(id, || panic::catch_unwind(AssertUnwindSafe(envelope.job)));
+        let run_duration = started.elapsed();
+
+        if let (Some(log), Some(audit)) = (&hooks.audit_log, audit) {
+            let outcome = match result {
+                Ok(()) => Outcome::Completed,
+                Err(_) => Outcome::Panicked,
+            };
+
+            log.finish(audit, id, run_duration, outcome);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("task.poll_duration", tracing::field::debug(run_duration));
+            tracing::trace!(task.poll_duration = ?run_duration, "job completed");
+            drop(entered);
+        }
+
+        match result {
+            Ok(()) => {
+                counters.completed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(payload) => {
+                log_warn!("Worker {id} job panicked; continuing.");
+                counters.panicked.fetch_add(1, Ordering::Relaxed);
+                hooks.job_panic(id, &PanicError(payload));
+            }
+        }
+
+        counters.active.fetch_sub(1, Ordering::Relaxed);
+        times.add_busy(run_duration);
+
+        if let Some(on_job_complete) = &hooks.on_job_complete {
+            on_job_complete(JobMetrics {
+                worker_id: id,
+                queue_wait: queue_wait.unwrap_or_default(),
+                run_duration,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for Worker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alive = self.is_alive();
+
+        f.debug_struct("Worker")
+            .field("id", &self.id)
+            .field("alive", &alive)
+            .finish()
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{metrics::WorkerTimes, worker::Worker, ThreadPool};
+use std::sync::Arc;
+
+/// WorkerHandle struct
+///
+/// Lets a thread the ThreadPool doesn't own take jobs off its queue, e.g. an
+/// event loop or game engine thread with spare time between frames. See
+/// `ThreadPool::receiver_handle`.
+///
+/// Jobs are run the way a Worker runs them: panics are caught, the hooks
+/// see the handle's id as the Worker id, and the job counts towards
+/// `active_count` and the completed and panicked totals. Jobs sent to a
+/// particular Worker, such as those from `execute_ordered` or `broadcast`,
+/// are left to the Workers. Clones share the same id.
+///
+/// # Members
+///
+/// - `pool` The ThreadPool whose queue jobs are taken from
+/// - `id` The id reported to the hooks, which no Worker has
+/// - `times` How long the handle has spent running jobs
+#[derive(Debug, Clone)]
+pub struct WorkerHandle {
+    pool: ThreadPool,
+    id: usize,
+    times: Arc<WorkerTimes>,
+}
+
+impl WorkerHandle {
+    /// Create a WorkerHandle for the ThreadPool.
+    ///
+    /// # Arguments
+    ///
+    /// - `pool` is the ThreadPool to take jobs from.
+    /// - `id` is an id no Worker has.
+    pub(crate) fn new(pool: ThreadPool, id: usize) -> Self {
+        Self {
+            pool,
+            id,
+            times: Arc::default(),
+        }
+    }
+
+    /// Take the next job off the queue, if there is one, and run it on the
+    /// current thread. Never waits for a job to arrive.
+    ///
+    /// # Return
+    ///
+    /// `Some(())` if a job was taken, or `None` if the queue is empty or the
+    /// ThreadPool is paused.
+    pub fn run_one(&self) -> Option<()> {
+        let envelope = self.pool.inner.queue.try_pop()?;
+        let inner = &self.pool.inner;
+
+        Worker::execute(
+            self.id,
+            envelope,
+            &inner.counters,
+            &self.times,
+            &inner.config.hooks,
+        );
+
+        Some(())
+    }
+
+    /// The id the hooks see for jobs run through this WorkerHandle.
+    pub fn worker_id(&self) -> usize {
+        self.id
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg(all(feature = "affinity", target_os = "linux"))]
+
+use mtserver::ThreadPoolBuilder;
+use std::thread;
+
+#[test]
+fn a_pinned_worker_can_only_run_on_its_core() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .pin_workers_to_cores(vec![0])
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let cores = pool
+            .spawn(|| thread::available_parallelism().unwrap().get())
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(cores, 1);
+    }
+}
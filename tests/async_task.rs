@@ -0,0 +1,77 @@
+#![cfg(feature = "async-task")]
+
+mod common;
+
+use common::block_on;
+use mtserver::ThreadPool;
+use std::{
+    future::{self, Future},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+    thread,
+};
+
+/// A future that is pending the first `count` times it is polled, waking
+/// itself each time.
+fn yield_times(count: usize) -> impl Future<Output = ()> {
+    let mut left = count;
+
+    future::poll_fn(move |cx| {
+        if left == 0 {
+            return Poll::Ready(());
+        }
+
+        left -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+}
+
+#[test]
+fn spawned_task_runs_on_a_worker() {
+    let pool = ThreadPool::new(2);
+    let task = pool.spawn_task(async { thread::current().name().map(str::to_owned) });
+
+    let name = block_on(task).unwrap();
+
+    assert!(name.starts_with("worker-"), "ran on {name}");
+}
+
+#[test]
+fn woken_task_is_polled_again() {
+    let pool = ThreadPool::new(2);
+    let polls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&polls);
+    let task = pool.spawn_task(async move {
+        yield_times(3).await;
+        counter.fetch_add(1, Ordering::SeqCst);
+        6 * 7
+    });
+
+    assert_eq!(block_on(task), 42);
+    assert_eq!(polls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn scheduler_works_with_async_task_spawn() {
+    let pool = ThreadPool::new(1);
+    let (runnable, task) = async_task::spawn(async { 1 + 1 }, pool.scheduler());
+
+    runnable.schedule();
+
+    assert_eq!(block_on(task), 2);
+}
+
+#[test]
+fn task_is_cancelled_once_the_pool_is_shut_down() {
+    let pool = ThreadPool::new(1);
+    let (runnable, task) = async_task::spawn(async { 1 + 1 }, pool.scheduler());
+
+    pool.shutdown().unwrap();
+    runnable.schedule();
+
+    assert_eq!(block_on(task.fallible()), None);
+}
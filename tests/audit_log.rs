@@ -0,0 +1,72 @@
+use mtserver::{Outcome, ThreadPool, ThreadPoolBuilder};
+use std::{thread, time::Duration};
+
+/// Wait for the Workers to record every job, which they do just after the
+/// job's pending count drops.
+fn wait_for_entries(pool: &ThreadPool, n: usize) {
+    while pool.audit_log().len() < n {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn records_each_job_and_its_outcome() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .with_audit_log(8)
+        .build()
+        .unwrap();
+    let worker_id = pool.into_iter().next().unwrap();
+
+    pool.execute(|| {}).unwrap();
+    pool.execute(|| panic!("oops")).unwrap();
+    pool.join();
+    wait_for_entries(&pool, 2);
+
+    let log = pool.audit_log();
+
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].job_id, 0);
+    assert_eq!(log[0].outcome, Outcome::Completed);
+    assert_eq!(log[1].job_id, 1);
+    assert_eq!(log[1].outcome, Outcome::Panicked);
+
+    for entry in &log {
+        assert_eq!(entry.worker_id, worker_id);
+        assert!(entry.started_at <= entry.finished_at);
+    }
+}
+
+#[test]
+fn keeps_only_the_last_capacity_jobs() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .with_audit_log(3)
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        pool.execute(|| {}).unwrap();
+    }
+
+    pool.join();
+
+    // The last entry is in once the tenth job's id shows up.
+    while pool.audit_log().last().map(|entry| entry.job_id) != Some(9) {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let ids: Vec<u64> = pool.audit_log().iter().map(|entry| entry.job_id).collect();
+
+    assert_eq!(ids, [7, 8, 9]);
+}
+
+#[test]
+fn no_audit_log_by_default() {
+    let pool = ThreadPool::new(1);
+
+    pool.execute(|| {}).unwrap();
+    pool.join();
+
+    assert!(pool.audit_log().is_empty());
+}
@@ -0,0 +1,155 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+fn parallelism() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[test]
+fn resize_to_parallelism_matches_available_parallelism() {
+    let pool = ThreadPool::new(1);
+
+    assert_eq!(pool.resize_to_parallelism().unwrap(), parallelism());
+    assert_eq!(pool.size(), parallelism());
+}
+
+#[test]
+fn autoscale_resizes_in_the_background() {
+    let target = parallelism();
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(target + 2)
+        .autoscale(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while pool.size() != target {
+        assert!(Instant::now() < deadline, "pool was never resized");
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute(move || tx.send(()).unwrap()).unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn autoscale_does_not_keep_the_pool_alive() {
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&stopped);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .autoscale(Duration::from_secs(60))
+        .on_thread_stop(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+    drop(pool);
+
+    assert_eq!(stopped.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn the_last_handle_shuts_the_workers_down_while_autoscaling() {
+    let size = parallelism();
+
+    for i in 0..50 {
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&stopped);
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(size)
+            .autoscale(Duration::from_micros(100))
+            .on_thread_stop(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+
+        thread::sleep(Duration::from_micros(37 * i));
+        drop(pool);
+
+        assert_eq!(stopped.load(Ordering::SeqCst), size);
+    }
+}
+
+#[test]
+fn jobs_can_drop_pool_clones_while_autoscale_shrinks_the_pool() {
+    let size = parallelism() + 1;
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(size)
+        .autoscale(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    // Every Worker is busy when the shrink starts, so it waits for one of
+    // them to finish its job, which drops its handle first.
+    for _ in 0..size {
+        let clone = pool.clone();
+        let tx = tx.clone();
+
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(30));
+            drop(clone);
+            tx.send(()).unwrap();
+        })
+        .unwrap();
+    }
+
+    drop(tx);
+
+    for _ in 0..size {
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("a job never finished dropping its handle");
+    }
+
+    pool.join();
+
+    assert_eq!(pool.size(), parallelism());
+}
+
+/// Whether a thread with the given name is running in this process.
+#[cfg(target_os = "linux")]
+fn thread_running(name: &str) -> bool {
+    std::fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter_map(|task| std::fs::read_to_string(task.path().join("comm")).ok())
+        .any(|comm| comm.trim_end() == name)
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn dropping_the_pool_stops_the_autoscale_thread() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .thread_name_prefix("drp")
+        .autoscale(Duration::from_millis(5))
+        .build()
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    // The thread names itself once it starts.
+    while !thread_running("drp-autoscale") {
+        assert!(Instant::now() < deadline, "autoscale thread never started");
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    drop(pool);
+
+    while thread_running("drp-autoscale") {
+        assert!(Instant::now() < deadline, "autoscale thread never exited");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,109 @@
+use mtserver::{ExecuteError, ThreadPoolBuilder};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+
+#[test]
+fn every_job_runs_whatever_the_batch_size() {
+    for n in [0, 1, 3, 1000] {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(4)
+            .worker_batch_size(n)
+            .build()
+            .unwrap();
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..500 {
+            let done = Arc::clone(&done);
+
+            pool.execute(move || {
+                done.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        }
+
+        pool.join();
+
+        assert_eq!(done.load(Ordering::Relaxed), 500);
+    }
+}
+
+#[test]
+fn every_job_runs_whatever_the_channel_batch_size() {
+    for n in [0, 1, 8, 1000] {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(4)
+            .channel_capacity(16)
+            .channel_batch_size(n)
+            .build()
+            .unwrap();
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..500 {
+            let done = Arc::clone(&done);
+
+            pool.execute_blocking(move || {
+                done.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        }
+
+        pool.shutdown().unwrap();
+
+        assert_eq!(done.load(Ordering::Relaxed), 500);
+    }
+}
+
+#[test]
+fn a_held_batch_makes_room_in_a_bounded_queue() {
+    for (hold, room) in [(false, 1), (true, 2)] {
+        let builder = ThreadPoolBuilder::new().num_threads(1).channel_capacity(4);
+        let pool = match hold {
+            true => builder.channel_batch_size(4),
+            false => builder.worker_batch_size(4),
+        }
+        .build()
+        .unwrap();
+        let (busy_tx, busy) = mpsc::channel();
+        let (free, free_rx) = mpsc::channel::<()>();
+        let (started_tx, started) = mpsc::channel();
+        let (finish, finish_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            busy_tx.send(()).unwrap();
+            let _ = free_rx.recv();
+        })
+        .unwrap();
+
+        busy.recv().unwrap();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            let _ = finish_rx.recv();
+        })
+        .unwrap();
+
+        for _ in 0..3 {
+            pool.execute(|| {}).unwrap();
+        }
+
+        assert_eq!(pool.execute(|| {}), Err(ExecuteError::QueueFull));
+
+        // The Worker takes half of the 4 waiting jobs: the blocking one to
+        // run, and one more that stays queued unless the batch is held.
+        drop(free);
+        started.recv().unwrap();
+
+        assert_eq!(pool.queued_count(), 4 - room);
+
+        for _ in 0..room {
+            pool.execute(|| {}).unwrap();
+        }
+
+        assert_eq!(pool.execute(|| {}), Err(ExecuteError::QueueFull));
+
+        drop(finish);
+        pool.join();
+    }
+}
@@ -0,0 +1,80 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const CAPACITY: usize = 4;
+
+/// A single-Worker ThreadPool with a queue of `CAPACITY` jobs, whose Worker
+/// is stuck on a job until the returned Sender is dropped.
+fn blocked_pool() -> (ThreadPool, mpsc::Sender<()>) {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .channel_capacity(CAPACITY)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    (pool, tx)
+}
+
+#[test]
+fn execute_on_a_full_queue_returns_queue_full() {
+    let (pool, unblock) = blocked_pool();
+
+    for _ in 0..CAPACITY {
+        pool.execute(|| {}).unwrap();
+    }
+
+    assert_eq!(pool.execute(|| {}), Err(ExecuteError::QueueFull));
+    assert_eq!(pool.queued_count(), CAPACITY);
+
+    drop(unblock);
+    pool.join();
+
+    assert_eq!(pool.queued_count(), 0);
+    assert!(pool.execute(|| {}).is_ok());
+}
+
+#[test]
+fn execute_blocking_waits_for_space() {
+    let (pool, unblock) = blocked_pool();
+    let submitted = AtomicBool::new(false);
+    let ran = Arc::new(AtomicBool::new(false));
+
+    for _ in 0..CAPACITY {
+        pool.execute(|| {}).unwrap();
+    }
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            let ran = Arc::clone(&ran);
+
+            pool.execute_blocking(move || ran.store(true, Ordering::SeqCst))
+                .unwrap();
+            submitted.store(true, Ordering::SeqCst);
+        });
+
+        thread::sleep(Duration::from_millis(100));
+
+        assert!(!submitted.load(Ordering::SeqCst));
+
+        drop(unblock);
+    });
+
+    pool.join();
+
+    assert!(submitted.load(Ordering::SeqCst));
+    assert!(ran.load(Ordering::SeqCst));
+}
@@ -0,0 +1,45 @@
+use mtserver::{BroadcastError, ThreadPool};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[test]
+fn broadcast_runs_once_on_every_worker() {
+    let pool = ThreadPool::new(4);
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&threads);
+
+    pool.broadcast(move || seen.lock().unwrap().push(thread::current().id()))
+        .unwrap();
+
+    let threads = threads.lock().unwrap();
+    let distinct: HashSet<_> = threads.iter().collect();
+
+    assert_eq!(threads.len(), 4);
+    assert_eq!(distinct.len(), 4);
+}
+
+#[test]
+fn broadcast_counts_the_workers_it_panicked_on() {
+    let pool = ThreadPool::new(3);
+
+    assert_eq!(
+        pool.broadcast(|| panic!("broadcast panicked")),
+        Err(BroadcastError::Panicked(3))
+    );
+
+    // The Workers survive the panics.
+    assert_eq!(pool.spawn(|| 1).unwrap().join().unwrap(), 1);
+}
+
+#[test]
+fn broadcast_after_shutdown_is_an_error() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert_eq!(handle.broadcast(|| {}), Err(BroadcastError::Shutdown));
+}
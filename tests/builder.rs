@@ -0,0 +1,41 @@
+use mtserver::{ThreadPoolBuilder, ThreadPoolError};
+use std::thread;
+
+#[test]
+fn the_builder_applies_its_settings() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .thread_name_prefix("built")
+        .stack_size(1 << 20)
+        .channel_capacity(8)
+        .build()
+        .unwrap();
+
+    let name = pool
+        .spawn(|| thread::current().name().map(str::to_string))
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(pool.size(), 3);
+    assert_eq!(pool.capacity(), Some(8));
+    assert!(name.starts_with("built-"), "{name}");
+}
+
+#[test]
+fn building_with_no_threads_is_an_error() {
+    let result = ThreadPoolBuilder::new().num_threads(0).build();
+
+    assert!(matches!(result, Err(ThreadPoolError::InvalidSize)));
+}
+
+#[test]
+fn a_cloned_builder_builds_an_identical_pool() {
+    let builder = ThreadPoolBuilder::new().num_threads(2).channel_capacity(4);
+    let first = builder.clone().build().unwrap();
+    let second = builder.build().unwrap();
+
+    assert_eq!(first.size(), second.size());
+    assert_eq!(first.capacity(), second.capacity());
+}
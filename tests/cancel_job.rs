@@ -0,0 +1,49 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+#[test]
+fn a_cancelled_job_never_runs() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&ran);
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    let id = pool
+        .execute_with_id(move || flag.store(true, Ordering::SeqCst))
+        .unwrap();
+
+    assert!(pool.cancel_job(id));
+    assert!(!pool.cancel_job(id));
+
+    drop(tx);
+    pool.join();
+
+    assert!(!ran.load(Ordering::SeqCst));
+}
+
+#[test]
+fn a_finished_job_cant_be_cancelled() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel();
+    let first = pool.execute_with_id(move || tx.send(()).unwrap()).unwrap();
+    let second = pool.execute_with_id(|| {}).unwrap();
+
+    rx.recv().unwrap();
+    pool.join();
+
+    assert_ne!(first, second);
+    assert!(!pool.cancel_job(first));
+    assert!(!pool.cancel_job(second));
+}
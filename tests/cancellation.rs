@@ -0,0 +1,40 @@
+use mtserver::{CancellationToken, Cancelled, ThreadPool};
+use std::sync::mpsc;
+
+#[test]
+fn a_job_stops_once_its_token_is_cancelled() {
+    let pool = ThreadPool::new(1);
+    let (started_tx, started) = mpsc::channel();
+    let (result_tx, result) = mpsc::channel();
+
+    let (submitted, token) = pool.execute_with_token(move |token| {
+        started_tx.send(()).unwrap();
+
+        let mut steps = 0u64;
+
+        while !token.is_cancelled() {
+            steps += 1;
+        }
+
+        result_tx.send(steps).unwrap();
+    });
+
+    submitted.unwrap();
+    started.recv().unwrap();
+    token.cancel();
+
+    assert!(token.is_cancelled());
+    assert!(result.recv().is_ok());
+}
+
+#[test]
+fn check_fails_only_after_cancel() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+
+    assert_eq!(clone.check(), Ok(()));
+
+    token.cancel();
+
+    assert_eq!(clone.check(), Err(Cancelled));
+}
@@ -0,0 +1,39 @@
+//! Run with and without `--features crossbeam` to cover both backends of
+//! the result channels.
+
+use mtserver::{JoinError, ThreadPool};
+use std::thread;
+
+#[test]
+fn results_cross_the_channel_from_many_producers() {
+    let pool = ThreadPool::new(4);
+
+    thread::scope(|s| {
+        for p in 0..4u64 {
+            let pool = &pool;
+
+            s.spawn(move || {
+                let handles: Vec<_> = (0..250u64)
+                    .map(|i| pool.spawn(move || p * 1000 + i).unwrap())
+                    .collect();
+
+                for (i, handle) in handles.into_iter().enumerate() {
+                    assert_eq!(handle.join().unwrap(), p * 1000 + i as u64);
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn a_dropped_job_disconnects_its_handle() {
+    let pool = ThreadPool::new(1);
+
+    pool.pause();
+
+    let handle = pool.spawn(|| 1).unwrap();
+
+    drop(pool.drain_queue());
+
+    assert!(matches!(handle.join(), Err(JoinError::Disconnected)));
+}
@@ -0,0 +1,43 @@
+use mtserver::ThreadPool;
+use std::sync::Mutex;
+
+#[test]
+fn chunks_cover_the_data_when_the_length_is_not_a_multiple() {
+    let pool = ThreadPool::new(4);
+    let data: Vec<u32> = (0..10).collect();
+    let chunks = Mutex::new(Vec::new());
+
+    pool.execute_chunked(&data, 3, |chunk| {
+        chunks.lock().unwrap().push(chunk.to_vec());
+    })
+    .unwrap();
+
+    let mut chunks = chunks.into_inner().unwrap();
+    chunks.sort();
+
+    assert_eq!(
+        chunks,
+        [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9]]
+    );
+}
+
+#[test]
+fn a_chunk_size_of_zero_is_one_item_per_chunk() {
+    let pool = ThreadPool::new(2);
+    let sizes = Mutex::new(Vec::new());
+
+    pool.execute_chunked(&[1, 2, 3], 0, |chunk| {
+        sizes.lock().unwrap().push(chunk.len());
+    })
+    .unwrap();
+
+    assert_eq!(sizes.into_inner().unwrap(), [1, 1, 1]);
+}
+
+#[test]
+fn empty_data_submits_nothing() {
+    let pool = ThreadPool::new(2);
+
+    pool.execute_chunked(&[] as &[u8], 4, |_| panic!("no chunks"))
+        .unwrap();
+}
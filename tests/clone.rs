@@ -0,0 +1,71 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn clones_submit_to_the_same_workers() {
+    let pool = ThreadPool::new(2);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            let done = Arc::clone(&done);
+
+            thread::spawn(move || {
+                for _ in 0..25 {
+                    let done = Arc::clone(&done);
+
+                    pool.execute(move || {
+                        done.fetch_add(1, Ordering::SeqCst);
+                    })
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::SeqCst), 100);
+    assert_eq!((&pool).into_iter().count(), 2);
+}
+
+#[test]
+fn a_clone_outlives_the_original() {
+    let pool = ThreadPool::new(2);
+    let clone = pool.clone();
+
+    clone.resize(3).unwrap();
+    assert_eq!(pool.size(), 3);
+
+    drop(pool);
+
+    assert_eq!(clone.spawn(|| 9).unwrap().join().unwrap(), 9);
+}
+
+#[test]
+fn a_job_reads_a_snapshot_while_the_pool_shuts_down() {
+    let pool = ThreadPool::new(2);
+    let clone = pool.clone();
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute(move || {
+        thread::sleep(Duration::from_millis(200));
+        tx.send(clone.snapshot().size).unwrap();
+    })
+    .unwrap();
+
+    assert!(pool.shutdown().is_ok());
+    assert_eq!(rx.recv().unwrap(), 2);
+}
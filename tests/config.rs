@@ -0,0 +1,46 @@
+use mtserver::{ThreadPoolBuilder, ThreadPoolConfig};
+use std::thread;
+
+#[test]
+fn from_config_applies_the_settings_that_are_set() {
+    let config = ThreadPoolConfig {
+        num_threads: Some(3),
+        thread_name_prefix: Some("configured".to_string()),
+        channel_capacity: Some(16),
+        ..ThreadPoolConfig::default()
+    };
+    let pool = ThreadPoolBuilder::from_config(config).build().unwrap();
+    let name = pool
+        .spawn(|| thread::current().name().map(str::to_string))
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(pool.size(), 3);
+    assert_eq!(pool.capacity(), Some(16));
+    assert!(name.starts_with("configured-"), "{name}");
+}
+
+#[test]
+fn an_empty_config_keeps_the_defaults() {
+    let pool = ThreadPoolBuilder::from_config(ThreadPoolConfig::default())
+        .build()
+        .unwrap();
+    let default = ThreadPoolBuilder::new().build().unwrap();
+
+    assert_eq!(pool.size(), default.size());
+    assert_eq!(pool.capacity(), None);
+}
+
+#[test]
+fn a_lazy_config_spawns_no_workers_up_front() {
+    let config = ThreadPoolConfig {
+        num_threads: Some(2),
+        lazy: true,
+        ..ThreadPoolConfig::default()
+    };
+    let pool = ThreadPoolBuilder::from_config(config).build().unwrap();
+
+    assert_eq!((&pool).into_iter().count(), 0);
+}
@@ -0,0 +1,50 @@
+use mtserver::ThreadPool;
+use std::sync::{mpsc, Arc};
+
+struct Config {
+    greeting: String,
+}
+
+#[test]
+fn jobs_see_the_shared_context() {
+    let pool = ThreadPool::new(2);
+    let config = Arc::new(Config {
+        greeting: "hello".to_string(),
+    });
+    let (tx, rx) = mpsc::channel();
+
+    for i in 0..3 {
+        let tx = tx.clone();
+
+        pool.execute_with_context(&config, move |config| {
+            tx.send(format!("{} {i}", config.greeting)).unwrap();
+        })
+        .unwrap();
+    }
+
+    drop(tx);
+
+    let mut greetings: Vec<_> = rx.iter().collect();
+    greetings.sort();
+
+    assert_eq!(greetings, ["hello 0", "hello 1", "hello 2"]);
+
+    pool.join();
+
+    assert_eq!(Arc::strong_count(&config), 1);
+}
+
+#[test]
+fn the_default_context_is_fetched_by_type() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.clone();
+
+    assert!(pool.default_context::<Config>().is_none());
+
+    pool.set_default_context(Arc::new(Config {
+        greeting: "hi".to_string(),
+    }));
+
+    assert_eq!(handle.default_context::<Config>().unwrap().greeting, "hi");
+    assert!(handle.default_context::<String>().is_none());
+}
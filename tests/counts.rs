@@ -0,0 +1,49 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{mpsc, Arc, Barrier, Mutex},
+    thread,
+};
+
+#[test]
+fn counts_follow_busy_and_queued_jobs() {
+    let pool = ThreadPool::new(2);
+    let started = Arc::new(Barrier::new(3));
+    let (tx, rx) = mpsc::channel::<()>();
+    let rx = Arc::new(Mutex::new(rx));
+
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(pool.idle_count(), 2);
+    assert_eq!(pool.queued_count(), 0);
+
+    for _ in 0..2 {
+        let started = Arc::clone(&started);
+        let rx = Arc::clone(&rx);
+
+        pool.execute(move || {
+            started.wait();
+            let _ = rx.lock().unwrap().recv();
+        })
+        .unwrap();
+    }
+
+    started.wait();
+
+    for _ in 0..3 {
+        pool.execute(|| {}).unwrap();
+    }
+
+    assert_eq!(pool.active_count(), 2);
+    assert_eq!(pool.idle_count(), 0);
+    assert_eq!(pool.queued_count(), 3);
+
+    drop(tx);
+    pool.join();
+
+    // A Worker counts as busy until it has recorded its last job.
+    while pool.active_count() > 0 {
+        thread::yield_now();
+    }
+
+    assert_eq!(pool.idle_count(), 2);
+    assert_eq!(pool.queued_count(), 0);
+}
@@ -0,0 +1,51 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn a_job_still_queued_at_its_deadline_is_dropped() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&ran);
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    pool.execute_with_deadline(
+        move || flag.store(true, Ordering::SeqCst),
+        Instant::now() + Duration::from_millis(10),
+    )
+    .unwrap();
+
+    thread::sleep(Duration::from_millis(30));
+    drop(tx);
+    pool.join();
+
+    assert!(!ran.load(Ordering::SeqCst));
+    assert_eq!(pool.dropped_due_to_deadline(), 1);
+}
+
+#[test]
+fn a_job_taken_before_its_deadline_runs() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute_with_deadline(
+        move || tx.send(()).unwrap(),
+        Instant::now() + Duration::from_secs(60),
+    )
+    .unwrap();
+
+    assert!(rx.recv().is_ok());
+    assert_eq!(pool.dropped_due_to_deadline(), 0);
+}
@@ -0,0 +1,47 @@
+use mtserver::ThreadPool;
+use std::{sync::mpsc, thread};
+
+#[test]
+fn debug_shows_the_size_and_load() {
+    let pool = ThreadPool::new(2);
+
+    assert_eq!(
+        format!("{pool:?}"),
+        "ThreadPool { size: 2, active: 0, queued: 0, .. }"
+    );
+
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    pool.pause();
+
+    pool.execute(|| {}).unwrap();
+
+    assert_eq!(
+        format!("{pool:?}"),
+        "ThreadPool { size: 2, active: 1, queued: 1, .. }"
+    );
+
+    drop(tx);
+    pool.resume();
+}
+
+#[test]
+fn a_struct_holding_a_pool_can_derive_debug() {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        pool: ThreadPool,
+    }
+
+    let server = Server {
+        pool: ThreadPool::new(1),
+    };
+
+    assert!(format!("{server:?}").contains("ThreadPool { size: 1"));
+}
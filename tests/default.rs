@@ -0,0 +1,20 @@
+use mtserver::ThreadPool;
+use std::thread;
+
+#[test]
+fn the_default_pool_has_a_worker_per_cpu() {
+    let pool = ThreadPool::default();
+    let cpus = thread::available_parallelism().map_or(4, |n| n.get());
+
+    assert!(pool.size() >= 1);
+    assert_eq!(pool.size(), cpus);
+    assert_eq!(pool.idle_count(), cpus);
+}
+
+#[test]
+fn an_elastic_pool_starts_with_its_minimum() {
+    let pool = ThreadPool::with_min_workers(2, 8);
+
+    assert_eq!(pool.size(), 8);
+    assert_eq!(pool.idle_count(), 2);
+}
@@ -0,0 +1,40 @@
+use mtserver::ThreadPool;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn drained_jobs_can_be_resubmitted_to_another_pool() {
+    let old = ThreadPool::new(2);
+    let new = ThreadPool::new(2);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    old.pause();
+
+    for _ in 0..5 {
+        let ran = Arc::clone(&ran);
+
+        old.execute(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    let drained: Vec<_> = old.drain_queue().collect();
+
+    assert_eq!(drained.len(), 5);
+    assert_eq!(old.queued_count(), 0);
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+
+    for job in drained {
+        new.execute_boxed(job).unwrap();
+    }
+
+    new.join();
+    old.resume();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 5);
+    assert_eq!((&old).into_iter().count(), 2);
+    assert_eq!(old.spawn(|| 1).unwrap().join().unwrap(), 1);
+}
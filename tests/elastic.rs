@@ -0,0 +1,60 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+const MIN: usize = 2;
+const MAX: usize = 6;
+
+#[test]
+fn an_elastic_pool_bursts_to_max_and_shrinks_back_to_min() {
+    let pool = ThreadPoolBuilder::new()
+        .min_threads(MIN)
+        .max_threads(MAX)
+        .keep_alive(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let live = || (&pool).into_iter().count();
+
+    assert_eq!(live(), MIN);
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let rx = Arc::new(Mutex::new(rx));
+
+    // One more job than there can be Workers, each holding its Worker until
+    // `tx` is dropped.
+    for _ in 0..=MAX {
+        let rx = Arc::clone(&rx);
+
+        pool.execute(move || {
+            let _ = rx.lock().map(|rx| rx.recv());
+        })
+        .unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while live() < MAX && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(live(), MAX);
+
+    drop(tx);
+    pool.join();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while live() > MIN && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(live(), MIN);
+
+    // The minimum stays alive however long it is idle.
+    thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(live(), MIN);
+}
@@ -0,0 +1,27 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::sync::mpsc;
+
+#[test]
+fn execute_returns_ok_and_runs_the_job() {
+    let pool = ThreadPool::new(2);
+    let (tx, rx) = mpsc::channel();
+
+    assert_eq!(pool.execute(move || tx.send(42).unwrap()), Ok(()));
+    assert_eq!(rx.recv().unwrap(), 42);
+}
+
+#[test]
+fn execute_after_shutdown_returns_shutdown() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    let result = handle.execute(|| {});
+
+    assert_eq!(result, Err(ExecuteError::Shutdown));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "thread pool has been shut down"
+    );
+}
@@ -0,0 +1,50 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn a_deferred_job_runs_no_sooner_than_asked() {
+    let pool = ThreadPool::new(2);
+    let (tx, rx) = mpsc::channel();
+    let not_before = Instant::now() + Duration::from_millis(50);
+
+    pool.execute_after(move || tx.send(Instant::now()).unwrap(), not_before)
+        .unwrap();
+
+    assert!(rx.recv().unwrap() >= not_before);
+}
+
+#[test]
+fn deferred_jobs_run_in_the_order_they_are_due() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel();
+    let now = Instant::now();
+
+    for (i, ms) in [(0, 60), (1, 20), (2, 40)] {
+        let tx = tx.clone();
+
+        pool.execute_after(move || tx.send(i).unwrap(), now + Duration::from_millis(ms))
+            .unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), [1, 2, 0]);
+}
+
+#[test]
+fn a_deferred_job_can_be_cancelled_before_it_is_due() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel::<()>();
+    let id = pool
+        .execute_after(
+            move || tx.send(()).unwrap(),
+            Instant::now() + Duration::from_millis(50),
+        )
+        .unwrap();
+
+    assert!(pool.cancel_job(id));
+    assert!(rx.recv().is_err());
+}
@@ -0,0 +1,57 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+
+#[test]
+fn execute_batch_runs_every_job() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+    let jobs: Vec<_> = (0..100)
+        .map(|_| {
+            let done = Arc::clone(&done);
+
+            move || {
+                done.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .collect();
+
+    assert_eq!(pool.execute_batch(jobs).unwrap(), 100);
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn execute_batch_fills_a_bounded_queue_and_drops_the_rest() {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .channel_capacity(3)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        std::thread::yield_now();
+    }
+
+    assert_eq!(pool.execute_batch((0..10).map(|_| || {})).unwrap(), 3);
+    assert_eq!(pool.queued_count(), 3);
+
+    drop(tx);
+}
+
+#[test]
+fn execute_batch_after_shutdown_is_an_error() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert_eq!(handle.execute_batch([|| {}]), Err(ExecuteError::Shutdown));
+}
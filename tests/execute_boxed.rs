@@ -0,0 +1,96 @@
+use mtserver::{Job, ThreadPool};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::mpsc,
+};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations made by each thread.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations the current thread makes while running `f`.
+#[cfg(not(feature = "tracing"))]
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.get();
+
+    f();
+
+    ALLOCATIONS.get() - before
+}
+
+#[test]
+fn already_boxed_jobs_run() {
+    let pool = ThreadPool::new(2);
+    let (tx, rx) = mpsc::channel();
+    let jobs: Vec<Job> = (0..4)
+        .map(|i| {
+            let tx = tx.clone();
+
+            Box::new(move || tx.send(i).unwrap()) as Job
+        })
+        .collect();
+
+    drop(tx);
+
+    for job in jobs {
+        pool.execute_boxed(job).unwrap();
+    }
+
+    let mut ran: Vec<_> = rx.iter().collect();
+
+    ran.sort();
+
+    assert_eq!(ran, [0, 1, 2, 3]);
+}
+
+// Without the `tracing` feature there is no span to wrap the job with.
+#[test]
+#[cfg(not(feature = "tracing"))]
+fn a_boxed_job_is_queued_without_boxing_it_again() {
+    const JOBS: usize = 1_000;
+
+    let pool = ThreadPool::new(1);
+    let jobs: Vec<Job> = (0..JOBS).map(|_| Box::new(|| {}) as Job).collect();
+
+    // Jobs pile up in the queue instead of running, so the counts only
+    // include what submitting them allocates.
+    pool.pause();
+    pool.execute(|| {}).unwrap();
+
+    let boxed = allocations(|| {
+        for job in jobs {
+            pool.execute_boxed(job).unwrap();
+        }
+    });
+    let closures = allocations(|| {
+        for _ in 0..JOBS {
+            pool.execute(|| {}).unwrap();
+        }
+    });
+
+    pool.resume();
+    pool.join();
+
+    // The queue allocates a block for every few dozen jobs either way.
+    assert!(closures >= JOBS, "{closures}");
+    assert!(boxed < JOBS / 10, "{boxed}");
+}
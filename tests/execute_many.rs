@@ -0,0 +1,79 @@
+use mtserver::{ExecuteError, Job, ThreadPool, ThreadPoolBuilder};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Barrier,
+};
+
+fn counting_jobs(n: usize, ran: &Arc<AtomicUsize>) -> Vec<Job> {
+    (0..n)
+        .map(|_| {
+            let ran = Arc::clone(ran);
+            let job: Job = Box::new(move || {
+                ran.fetch_add(1, Ordering::SeqCst);
+            });
+
+            job
+        })
+        .collect()
+}
+
+#[test]
+fn execute_many_runs_every_job() {
+    let pool = ThreadPool::new(4);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    let count = pool
+        .execute_many(counting_jobs(10_000, &ran).into_iter())
+        .unwrap();
+
+    pool.join();
+
+    assert_eq!(count, 10_000);
+    assert_eq!(ran.load(Ordering::SeqCst), 10_000);
+}
+
+#[test]
+fn execute_many_stops_once_a_bounded_queue_is_full() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .channel_capacity(4)
+        .build()
+        .unwrap();
+    let barrier = Arc::new(Barrier::new(2));
+    let blocker = Arc::clone(&barrier);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    // Keep the only Worker busy so the queue fills up.
+    pool.execute(move || {
+        blocker.wait();
+    })
+    .unwrap();
+
+    while pool.queued_count() > 0 {
+        std::thread::yield_now();
+    }
+
+    let count = pool
+        .execute_many(counting_jobs(10, &ran).into_iter())
+        .unwrap();
+
+    barrier.wait();
+    pool.join();
+
+    assert_eq!(count, 4);
+    assert_eq!(ran.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn execute_many_after_shutdown_fails() {
+    let pool = ThreadPool::new(1);
+    let clone = pool.clone();
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    pool.shutdown().unwrap();
+
+    assert!(matches!(
+        clone.execute_many(counting_jobs(3, &ran).into_iter()),
+        Err(ExecuteError::Shutdown)
+    ));
+}
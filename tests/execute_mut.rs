@@ -0,0 +1,17 @@
+use mtserver::ThreadPool;
+use std::sync::mpsc;
+
+#[test]
+fn execute_mut_calls_the_closure_once() {
+    let pool = ThreadPool::new(2);
+    let (tx, rx) = mpsc::channel();
+    let mut calls = 0;
+
+    pool.execute_mut(move || {
+        calls += 1;
+        tx.send(calls).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), [1]);
+}
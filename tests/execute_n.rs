@@ -0,0 +1,37 @@
+use mtserver::ThreadPool;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn execute_n_calls_the_closure_n_times() {
+    let pool = ThreadPool::new(4);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+
+    pool.execute_n(100, move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+    pool.join();
+
+    assert_eq!(calls.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn execute_n_and_join_waits_for_every_call() {
+    let pool = ThreadPool::new(4);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+
+    pool.execute_n_and_join(37, move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+
+    assert_eq!(calls.load(Ordering::Relaxed), 37);
+
+    pool.execute_n_and_join(0, || panic!("never called"))
+        .unwrap();
+}
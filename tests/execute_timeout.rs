@@ -0,0 +1,55 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A single-Worker ThreadPool with room for one queued job, whose Worker is
+/// stuck on a job until the returned Sender is dropped.
+fn blocked_pool() -> (ThreadPool, mpsc::Sender<()>) {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .channel_capacity(1)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    (pool, tx)
+}
+
+#[test]
+fn execute_timeout_gives_up_after_the_timeout() {
+    let (pool, _unblock) = blocked_pool();
+    let started = Instant::now();
+
+    pool.execute(|| {}).unwrap();
+
+    assert_eq!(
+        pool.execute_timeout(|| {}, Duration::from_millis(50)),
+        Err(ExecuteError::Timeout)
+    );
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn execute_timeout_succeeds_once_space_frees_up() {
+    let (pool, unblock) = blocked_pool();
+
+    pool.execute(|| {}).unwrap();
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            drop(unblock);
+        });
+
+        assert_eq!(pool.execute_timeout(|| {}, Duration::from_secs(10)), Ok(()));
+    });
+}
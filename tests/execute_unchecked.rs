@@ -0,0 +1,82 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn execute_unchecked_runs_every_job() {
+    let pool = ThreadPool::new(4);
+    let ran = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel();
+
+    for _ in 0..1000 {
+        let ran = Arc::clone(&ran);
+        let tx = tx.clone();
+
+        pool.execute_unchecked(move || {
+            ran.fetch_add(1, Ordering::Relaxed);
+            tx.send(()).unwrap();
+        });
+    }
+
+    for _ in 0..1000 {
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    assert_eq!(ran.load(Ordering::Relaxed), 1000);
+}
+
+#[test]
+fn join_does_not_wait_for_unchecked_jobs() {
+    let pool = ThreadPool::new(1);
+    let (started_tx, started_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+
+    pool.execute_unchecked(move || {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+    });
+
+    started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    // Would block forever if the job were pending.
+    pool.join();
+
+    assert_eq!(pool.active_count(), 1);
+
+    release_tx.send(()).unwrap();
+}
+
+#[test]
+fn unchecked_jobs_report_no_queue_wait() {
+    let (tx, rx) = mpsc::channel();
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .on_job_complete(move |metrics| tx.send(metrics.queue_wait).unwrap())
+        .build()
+        .unwrap();
+
+    pool.execute_unchecked(|| {});
+
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        Duration::ZERO
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the queue is bounded")]
+fn execute_unchecked_on_a_bounded_queue_is_caught_in_debug_builds() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .channel_capacity(4)
+        .build()
+        .unwrap();
+
+    pool.execute_unchecked(|| {});
+}
@@ -0,0 +1,56 @@
+use mtserver::{ExecuteError, Job, ThreadPool};
+use std::{sync::mpsc, thread};
+
+#[test]
+fn jobs_are_dealt_out_to_the_threads_in_turn() {
+    let (result_tx, results) = mpsc::channel();
+    let mut senders = Vec::new();
+    let mut threads = Vec::new();
+
+    for _ in 0..3 {
+        let (tx, rx) = mpsc::sync_channel::<Job>(4);
+
+        senders.push(tx);
+        threads.push(thread::spawn(move || {
+            for job in rx {
+                job();
+            }
+        }));
+    }
+
+    let ids: Vec<_> = threads.iter().map(|t| t.thread().id()).collect();
+    let pool = ThreadPool::from_existing_threads(senders);
+
+    for _ in 0..6 {
+        let result_tx = result_tx.clone();
+
+        pool.execute_blocking(move || result_tx.send(thread::current().id()).unwrap())
+            .unwrap();
+    }
+
+    drop(result_tx);
+    pool.shutdown().unwrap();
+
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let ran: Vec<_> = results.iter().collect();
+
+    assert_eq!(ran.len(), 6);
+    for id in ids {
+        assert_eq!(ran.iter().filter(|&&r| r == id).count(), 2);
+    }
+}
+
+#[test]
+fn execute_fails_when_every_channel_is_full() {
+    let (tx, rx) = mpsc::sync_channel::<Job>(1);
+    let pool = ThreadPool::from_existing_threads(vec![tx]);
+
+    pool.execute(|| {}).unwrap();
+
+    assert!(matches!(pool.execute(|| {}), Err(ExecuteError::QueueFull)));
+
+    rx.recv().unwrap()();
+}
@@ -0,0 +1,57 @@
+use mtserver::{MapError, ThreadPool};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn for_each_runs_every_item_before_returning() {
+    let pool = ThreadPool::new(4);
+    let sum = Arc::new(AtomicUsize::new(0));
+    let total = Arc::clone(&sum);
+
+    pool.for_each(1..=1000, move |i| {
+        total.fetch_add(i, Ordering::SeqCst);
+    })
+    .unwrap();
+
+    assert_eq!(sum.load(Ordering::SeqCst), 500_500);
+}
+
+#[test]
+fn for_each_keeps_only_a_few_items_in_flight() {
+    let pool = ThreadPool::new(2);
+    let taken = Arc::new(AtomicUsize::new(0));
+    let done = Arc::new(AtomicUsize::new(0));
+    let most_ahead = Arc::new(AtomicUsize::new(0));
+    let (counted, finished, ahead) = (
+        Arc::clone(&taken),
+        Arc::clone(&done),
+        Arc::clone(&most_ahead),
+    );
+    let items = (0..10_000).inspect(move |_| {
+        let queued = counted.fetch_add(1, Ordering::SeqCst) + 1 - finished.load(Ordering::SeqCst);
+
+        ahead.fetch_max(queued, Ordering::SeqCst);
+    });
+
+    pool.for_each(items, move |_| {
+        done.fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap();
+
+    assert_eq!(taken.load(Ordering::SeqCst), 10_000);
+    assert!(most_ahead.load(Ordering::SeqCst) < 1_000);
+}
+
+#[test]
+fn for_each_reports_a_panicking_item() {
+    let pool = ThreadPool::new(2);
+    let result = pool.for_each(0..10, |i| {
+        if i == 7 {
+            panic!("item 7 failed");
+        }
+    });
+
+    assert!(matches!(result, Err(MapError::Join(_))));
+}
@@ -0,0 +1,102 @@
+use mtserver::{ForkJoinPool, ThreadPool};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+fn fib(pool: &ForkJoinPool, n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+
+    let inner = pool.clone();
+    let a = pool.fork(move || fib(&inner, n - 1));
+    let b = fib(pool, n - 2);
+
+    a.join() + b
+}
+
+fn merge_sort(pool: &ForkJoinPool, items: Vec<u32>) -> Vec<u32> {
+    if items.len() <= 4 {
+        let mut items = items;
+        items.sort_unstable();
+        return items;
+    }
+
+    let mut left = items;
+    let right = left.split_off(left.len() / 2);
+    let inner = pool.clone();
+    let right = pool.fork(move || merge_sort(&inner, right));
+    let left = merge_sort(pool, left);
+    let right = right.join();
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut l, mut r) = (left.into_iter().peekable(), right.into_iter().peekable());
+
+    while let (Some(a), Some(b)) = (l.peek(), r.peek()) {
+        merged.push(if a <= b { l.next() } else { r.next() }.unwrap());
+    }
+
+    merged.extend(l);
+    merged.extend(r);
+    merged
+}
+
+#[test]
+fn recursive_forks_dont_deadlock_a_single_worker() {
+    let pool = ForkJoinPool::new(ThreadPool::new(1));
+
+    assert_eq!(fib(&pool, 18), 2584);
+}
+
+#[test]
+fn merge_sort_on_a_small_pool() {
+    let pool = ForkJoinPool::new(ThreadPool::new(2));
+    let items: Vec<u32> = (0..2000).map(|i| (i * 7919) % 2003).collect();
+    let mut expected = items.clone();
+
+    expected.sort_unstable();
+
+    assert_eq!(merge_sort(&pool, items), expected);
+}
+
+#[test]
+fn joins_from_inside_jobs_dont_deadlock() {
+    let pool = ForkJoinPool::new(ThreadPool::new(2));
+    let outer = Arc::new(pool.clone());
+
+    // Every outer task blocks a Worker in join while its sub-tasks queue up
+    // behind the other outer tasks.
+    let handles: Vec<_> = (0..8)
+        .map(|n| {
+            let inner = Arc::clone(&outer);
+            pool.fork(move || fib(&inner, 10 + n))
+        })
+        .collect();
+    let results: Vec<u64> = handles.into_iter().map(|h| h.join()).collect();
+
+    assert_eq!(results, [55, 89, 144, 233, 377, 610, 987, 1597]);
+}
+
+#[test]
+fn a_sub_tasks_panic_carries_on_in_join() {
+    let pool = ForkJoinPool::new(ThreadPool::new(1));
+    let handle = pool.fork(|| -> u32 { panic!("oops") });
+    let result = panic::catch_unwind(AssertUnwindSafe(|| handle.join()));
+
+    assert!(result.is_err());
+    assert_eq!(pool.fork(|| 7).join(), 7);
+}
+
+#[test]
+fn forks_after_shutdown_run_in_join() {
+    let threads = ThreadPool::new(1);
+    let pool = ForkJoinPool::new(threads.clone());
+
+    threads.shutdown().unwrap();
+
+    let handle = pool.fork(|| 3);
+
+    assert!(!handle.is_finished());
+    assert_eq!(handle.join(), 3);
+}
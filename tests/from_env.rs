@@ -0,0 +1,62 @@
+use mtserver::{ThreadPool, ThreadPoolConfig, ThreadPoolConfigError, ThreadPoolError};
+use std::env;
+
+const VARIABLES: [&str; 4] = [
+    "THREADPOOL_SIZE",
+    "THREADPOOL_STACK_SIZE",
+    "THREADPOOL_CHANNEL_CAPACITY",
+    "THREADPOOL_KEEP_ALIVE_SECS",
+];
+
+// The environment is shared by every test in the process, so this is all one
+// test.
+#[test]
+fn from_env_reads_the_threadpool_variables() {
+    for variable in VARIABLES {
+        env::remove_var(variable);
+    }
+
+    assert_eq!(
+        ThreadPoolConfig::from_env().unwrap(),
+        ThreadPoolConfig::default()
+    );
+
+    env::set_var("THREADPOOL_SIZE", "3");
+    env::set_var("THREADPOOL_STACK_SIZE", "1048576");
+    env::set_var("THREADPOOL_CHANNEL_CAPACITY", " 16 ");
+    env::set_var("THREADPOOL_KEEP_ALIVE_SECS", "2");
+
+    let config = ThreadPoolConfig::from_env().unwrap();
+
+    assert_eq!(config.num_threads, Some(3));
+    assert_eq!(config.stack_size, Some(1 << 20));
+    assert_eq!(config.channel_capacity, Some(16));
+    assert_eq!(config.keep_alive_ms, Some(2000));
+
+    let pool = ThreadPool::from_env().unwrap();
+
+    assert_eq!(pool.size(), 3);
+    assert_eq!(pool.capacity(), Some(16));
+
+    env::set_var("THREADPOOL_CHANNEL_CAPACITY", "lots");
+
+    let e = ThreadPool::from_env().unwrap_err();
+
+    assert!(matches!(
+        e,
+        ThreadPoolConfigError::Parse("THREADPOOL_CHANNEL_CAPACITY", _)
+    ));
+    assert!(e.to_string().contains("THREADPOOL_CHANNEL_CAPACITY"));
+
+    env::remove_var("THREADPOOL_CHANNEL_CAPACITY");
+    env::set_var("THREADPOOL_SIZE", "0");
+
+    assert!(matches!(
+        ThreadPool::from_env(),
+        Err(ThreadPoolConfigError::Build(ThreadPoolError::InvalidSize))
+    ));
+
+    for variable in VARIABLES {
+        env::remove_var(variable);
+    }
+}
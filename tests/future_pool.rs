@@ -0,0 +1,108 @@
+use mtserver::{FuturePool, ThreadPool};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+/// A future that is ready once another thread has set its flag, and wakes
+/// whoever polled it last when that happens.
+#[derive(Default)]
+struct Signal {
+    set: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Signal {
+    fn set(&self) {
+        self.set.store(true, Ordering::Release);
+
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Future for &Signal {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+
+        if self.set.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A future that wakes itself and returns `Pending` the given number of
+/// times before finishing.
+struct YieldTimes(usize);
+
+impl Future for YieldTimes {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            return Poll::Ready(());
+        }
+
+        self.0 -= 1;
+        cx.waker().wake_by_ref();
+
+        Poll::Pending
+    }
+}
+
+#[test]
+fn a_future_woken_from_another_thread_completes() {
+    let pool = FuturePool::new(ThreadPool::new(2));
+    let signal = Arc::new(Signal::default());
+    let (tx, rx) = mpsc::channel();
+
+    let waiting = Arc::clone(&signal);
+    pool.execute_future(async move {
+        (&*waiting).await;
+        tx.send(()).unwrap();
+    })
+    .unwrap();
+
+    // The future isn't done until it is woken.
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+    thread::spawn(move || signal.set());
+
+    rx.recv_timeout(Duration::from_secs(10)).unwrap();
+}
+
+#[test]
+fn futures_that_wake_themselves_all_complete() {
+    let pool = FuturePool::new(ThreadPool::new(4));
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..100 {
+        let done = Arc::clone(&done);
+
+        pool.execute_future(async move {
+            YieldTimes(50).await;
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    while done.load(Ordering::Relaxed) < 100 {
+        thread::yield_now();
+    }
+
+    // A finished future doesn't leave any polls behind in the queue.
+    pool.pool().join();
+    assert_eq!(pool.pool().queued_count(), 0);
+}
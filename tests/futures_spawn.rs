@@ -0,0 +1,29 @@
+#![cfg(feature = "futures")]
+
+use futures_task::{FutureObj, Spawn};
+use mtserver::ThreadPool;
+use std::sync::mpsc;
+
+#[test]
+fn spawned_futures_run_to_completion() {
+    let pool = ThreadPool::new(2);
+    let (tx, rx) = mpsc::channel();
+
+    for i in 0..4 {
+        let tx = tx.clone();
+
+        pool.spawn_obj(FutureObj::new(Box::new(async move {
+            tx.send(i).unwrap();
+        })))
+        .unwrap();
+    }
+
+    drop(tx);
+
+    let mut ran: Vec<_> = rx.iter().collect();
+
+    ran.sort();
+
+    assert_eq!(ran, [0, 1, 2, 3]);
+    assert!(pool.status().is_ok());
+}
@@ -0,0 +1,14 @@
+use mtserver::global;
+use std::sync::mpsc;
+
+#[test]
+fn global_returns_the_same_running_pool() {
+    assert!(std::ptr::eq(global(), global()));
+
+    let (tx, rx) = mpsc::channel();
+
+    global().execute(move || tx.send(42).unwrap()).unwrap();
+
+    assert_eq!(rx.recv().unwrap(), 42);
+    assert!(global().size() > 0);
+}
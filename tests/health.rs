@@ -0,0 +1,41 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+#[test]
+fn a_fresh_pool_is_healthy() {
+    let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+
+    assert!(pool.is_healthy());
+    assert!(pool.unhealthy_worker_ids().is_empty());
+}
+
+#[test]
+fn only_the_dead_worker_is_listed() {
+    let crashed = AtomicBool::new(false);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .max_restarts(0)
+        .on_job_complete(move |_| {
+            if !crashed.swap(true, Ordering::SeqCst) {
+                panic!("hook crashed the worker");
+            }
+        })
+        .build()
+        .unwrap();
+
+    pool.execute(|| {}).unwrap();
+
+    while pool.unhealthy_worker_ids().is_empty() {
+        thread::yield_now();
+    }
+
+    let dead = pool.unhealthy_worker_ids();
+    let alive: Vec<_> = (&pool).into_iter().collect();
+
+    assert_eq!(dead.len(), 1);
+    assert!(!alive.contains(&dead[0]));
+    assert!(!pool.is_healthy());
+}
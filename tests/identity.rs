@@ -0,0 +1,35 @@
+use mtserver::ThreadPool;
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn clones_are_equal_and_other_pools_are_not() {
+    let pool = ThreadPool::new(1);
+    let clone = pool.clone();
+    let other = ThreadPool::new(1);
+
+    assert_eq!(pool, clone);
+    assert_ne!(pool, other);
+}
+
+// ThreadPools hash by identity, so their interior mutability can't change
+// their hash.
+#[allow(clippy::mutable_key_type)]
+#[test]
+fn pools_can_be_hash_map_keys() {
+    let a = ThreadPool::new(1);
+    let b = ThreadPool::new(1);
+    let mut names = HashMap::new();
+
+    names.insert(a.clone(), "a");
+    names.insert(b.clone(), "b");
+    names.insert(a.clone(), "a again");
+
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[&a], "a again");
+    assert_eq!(names[&b], "b");
+
+    let set: HashSet<ThreadPool> = [a.clone(), a.clone(), b].into_iter().collect();
+
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&a));
+}
@@ -0,0 +1,21 @@
+use mtserver::ThreadPool;
+use std::{thread, time::Duration};
+
+#[test]
+fn a_snapshot_splits_each_workers_time_into_idle_and_busy() {
+    let pool = ThreadPool::new(1);
+
+    thread::sleep(Duration::from_millis(20));
+
+    pool.spawn(|| thread::sleep(Duration::from_millis(20)))
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let snapshot = pool.snapshot();
+    let at_least = Duration::from_millis(20).as_nanos() as u64;
+
+    assert_eq!(snapshot.per_worker_idle_ns.len(), 1);
+    assert!(snapshot.per_worker_idle_ns[0] >= at_least);
+    assert!(snapshot.per_worker_busy_ns[0] >= at_least);
+}
@@ -0,0 +1,31 @@
+use mtserver::{JobMetrics, ThreadPoolBuilder};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn on_job_complete_is_given_each_jobs_times() {
+    let metrics = Arc::new(Mutex::new(Vec::<JobMetrics>::new()));
+    let recorded = Arc::clone(&metrics);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .on_job_complete(move |m| recorded.lock().unwrap().push(m))
+        .build()
+        .unwrap();
+
+    // The second job waits in the queue while the first one sleeps.
+    pool.execute(|| thread::sleep(Duration::from_millis(30)))
+        .unwrap();
+    pool.execute(|| {}).unwrap();
+    pool.join();
+
+    let metrics = metrics.lock().unwrap();
+    let ids: Vec<_> = (&pool).into_iter().collect();
+
+    assert_eq!(metrics.len(), 2);
+    assert!(metrics[0].run_duration >= Duration::from_millis(30));
+    assert!(metrics[1].queue_wait >= Duration::from_millis(20));
+    assert!(metrics.iter().all(|m| m.worker_id == ids[0]));
+}
@@ -0,0 +1,45 @@
+use mtserver::ThreadPoolBuilder;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn on_job_panic_is_given_the_worker_and_the_panic() {
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&panics);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .on_job_panic(move |id, e| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((id, e.message().map(str::to_string)));
+        })
+        .build()
+        .unwrap();
+
+    pool.execute(|| {}).unwrap();
+    pool.execute(|| panic!("job went wrong")).unwrap();
+    pool.join();
+
+    let ids: Vec<_> = (&pool).into_iter().collect();
+
+    assert_eq!(
+        *panics.lock().unwrap(),
+        [(ids[0], Some("job went wrong".to_string()))]
+    );
+}
+
+#[test]
+fn a_panicking_on_job_panic_hook_doesnt_take_the_worker_down() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .max_restarts(0)
+        .on_job_panic(|_, _| panic!("hook panicked"))
+        .build()
+        .unwrap();
+
+    pool.execute(|| panic!("job went wrong")).unwrap();
+    pool.join();
+
+    assert_eq!(pool.spawn(|| 2).unwrap().join().unwrap(), 2);
+    assert!(pool.is_healthy());
+}
@@ -0,0 +1,88 @@
+use mtserver::{ExecuteError, Job, Pool, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn a_sender_submits_jobs_from_another_thread() {
+    let pool = ThreadPool::new(2);
+    let sender = pool.clone_sender().unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for i in 0..4 {
+            let tx = tx.clone();
+            let job: Job = Box::new(move || tx.send(i).unwrap());
+
+            sender.send(job).unwrap();
+        }
+    })
+    .join()
+    .unwrap();
+
+    let mut seen: Vec<i32> = rx.iter().collect();
+
+    seen.sort_unstable();
+
+    assert_eq!(seen, [0, 1, 2, 3]);
+}
+
+#[test]
+fn a_sender_is_a_pool() {
+    let pool = ThreadPool::new(1);
+    let sender = pool.clone_sender().unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    Pool::execute(&sender, move || tx.send(()).unwrap()).unwrap();
+
+    rx.recv().unwrap();
+}
+
+#[test]
+fn a_sender_doesnt_keep_the_pool_alive() {
+    let pool = ThreadPool::new(1);
+    let sender = pool.clone_sender().unwrap();
+
+    drop(pool);
+
+    assert!(matches!(sender.execute(|| {}), Err(ExecuteError::Shutdown)));
+}
+
+#[test]
+fn no_sender_after_shutdown() {
+    let pool = ThreadPool::new(1);
+    let clone = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert!(clone.clone_sender().is_none());
+}
+
+#[test]
+fn the_last_handle_shuts_the_workers_down_while_a_sender_sends() {
+    for i in 0..50 {
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&stopped);
+        let pool = ThreadPool::builder()
+            .num_threads(2)
+            .on_thread_stop(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        let sender = pool.clone_sender().unwrap();
+        let sending = thread::spawn(move || while sender.execute(|| {}).is_ok() {});
+
+        thread::sleep(Duration::from_micros(37 * i));
+        drop(pool);
+
+        assert_eq!(stopped.load(Ordering::SeqCst), 2);
+
+        sending.join().unwrap();
+    }
+}
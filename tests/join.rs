@@ -0,0 +1,87 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn join_waits_for_the_job_to_be_recorded() {
+    let hooked = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&hooked);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .on_job_complete(move |_| {
+            thread::sleep(Duration::from_millis(20));
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+    for _ in 0..4 {
+        pool.execute(|| {}).unwrap();
+        pool.execute(|| panic!("boom")).unwrap();
+    }
+
+    pool.join();
+
+    let snapshot = pool.snapshot();
+
+    assert_eq!(hooked.load(Ordering::SeqCst), 8);
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(snapshot.total_completed, 4);
+    assert_eq!(snapshot.total_panics, 4);
+}
+
+#[test]
+fn join_counts_jobs_run_inline_by_another_job() {
+    let pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+
+    let handle = pool.receiver_handle().unwrap();
+    let ran_inline = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&ran_inline);
+
+    pool.pause();
+    pool.execute(move || {
+        while handle.run_one().is_some() {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    })
+    .unwrap();
+    pool.execute(|| {}).unwrap();
+    pool.resume();
+    pool.join();
+
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(pool.snapshot().total_completed, 2);
+    assert_eq!(ran_inline.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn every_thread_waiting_on_join_is_woken() {
+    let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..1000 {
+        let ran = Arc::clone(&ran);
+
+        pool.execute(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                pool.join();
+                assert_eq!(ran.load(Ordering::SeqCst), 1000);
+            });
+        }
+    });
+
+    assert_eq!(pool.active_count(), 0);
+}
@@ -0,0 +1,32 @@
+use mtserver::{JoinError, ThreadPool};
+use std::panic::{self, AssertUnwindSafe};
+
+#[test]
+fn a_panic_is_handed_to_whoever_joins_the_handle() {
+    let pool = ThreadPool::new(1);
+    let handle = pool
+        .spawn(|| -> u32 { panic!("job {} failed", 3) })
+        .unwrap();
+
+    match handle.join() {
+        Err(JoinError::Panicked(e)) => assert_eq!(e.message(), Some("job 3 failed")),
+        _ => panic!("the job should have panicked"),
+    }
+
+    // The panic was caught, so the Worker is still there to run this.
+    assert_eq!(pool.spawn(|| 5).unwrap().join().unwrap(), 5);
+}
+
+#[test]
+fn the_panic_payload_can_be_resumed() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.spawn(|| panic::panic_any(42u8)).unwrap();
+
+    let Err(JoinError::Panicked(e)) = handle.join() else {
+        panic!("the job should have panicked");
+    };
+
+    let resumed = panic::catch_unwind(AssertUnwindSafe(|| panic::resume_unwind(e.into_payload())));
+
+    assert_eq!(resumed.unwrap_err().downcast_ref::<u8>(), Some(&42));
+}
@@ -0,0 +1,66 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn idle_workers_are_evicted_down_to_one() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .keep_alive(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let barrier = Arc::new(Barrier::new(4));
+
+    for _ in 0..4 {
+        let barrier = Arc::clone(&barrier);
+
+        pool.execute(move || {
+            barrier.wait();
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while (&pool).into_iter().count() > 1 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!((&pool).into_iter().count(), 1);
+    assert_eq!(pool.size(), 4);
+    assert!(pool.is_healthy());
+}
+
+#[test]
+fn evicted_workers_are_spawned_again_for_new_jobs() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .keep_alive(Duration::from_millis(20))
+        .build()
+        .unwrap();
+
+    while (&pool).into_iter().count() > 1 {
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let barrier = Arc::new(Barrier::new(4));
+
+    for _ in 0..3 {
+        let barrier = Arc::clone(&barrier);
+
+        pool.execute(move || {
+            barrier.wait();
+        })
+        .unwrap();
+    }
+
+    // All 3 jobs are running at once, so 3 Workers are alive.
+    barrier.wait();
+
+    assert_eq!((&pool).into_iter().count(), 3);
+}
@@ -0,0 +1,46 @@
+use mtserver::{LabelStats, ThreadPool};
+use std::{thread, time::Duration};
+
+#[test]
+fn label_stats_counts_each_label() {
+    let pool = ThreadPool::new(2);
+
+    for _ in 0..3 {
+        pool.execute_with_label("sleep", || thread::sleep(Duration::from_millis(5)))
+            .unwrap();
+    }
+
+    pool.execute_with_label("noop", || {}).unwrap();
+    pool.execute_with_label("noop", || panic!("oops")).unwrap();
+    pool.join();
+
+    let stats = pool.label_stats();
+    let sleep = stats["sleep"];
+    let noop = stats["noop"];
+
+    assert_eq!(stats.len(), 2);
+    assert_eq!((sleep.started, sleep.completed, sleep.panicked), (3, 3, 0));
+    assert!(sleep.total_run_time >= Duration::from_millis(15));
+    assert!(sleep.average_run_time().unwrap() >= Duration::from_millis(5));
+    assert_eq!((noop.started, noop.completed, noop.panicked), (2, 1, 1));
+}
+
+#[test]
+fn labelled_panics_are_counted_by_the_pool_too() {
+    let pool = ThreadPool::new(1);
+
+    pool.execute_with_label("boom", || panic!("oops")).unwrap();
+    pool.join();
+
+    while pool.snapshot().total_panics == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(pool.snapshot().total_panics, 1);
+}
+
+#[test]
+fn no_average_before_any_job_finishes() {
+    assert_eq!(LabelStats::default().average_run_time(), None);
+    assert!(ThreadPool::new(1).label_stats().is_empty());
+}
@@ -0,0 +1,47 @@
+use mtserver::ThreadPoolBuilder;
+use std::sync::{mpsc, Arc, Barrier};
+
+#[test]
+fn a_lazy_pool_spawns_no_workers_until_a_job_arrives() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .lazy_spawn()
+        .build()
+        .unwrap();
+
+    assert_eq!((&pool).into_iter().count(), 0);
+
+    assert_eq!(pool.spawn(|| 1).unwrap().join().unwrap(), 1);
+
+    let live = (&pool).into_iter().count();
+
+    assert!((1..=4).contains(&live), "{live}");
+}
+
+#[test]
+fn a_lazy_pool_spawns_up_to_its_size_for_concurrent_jobs() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .lazy_spawn()
+        .build()
+        .unwrap();
+    let barrier = Arc::new(Barrier::new(3));
+    let (tx, rx) = mpsc::channel();
+
+    // Only returns once 3 jobs are running at the same time.
+    for _ in 0..3 {
+        let barrier = Arc::clone(&barrier);
+        let tx = tx.clone();
+
+        pool.execute(move || {
+            barrier.wait();
+            tx.send(()).unwrap();
+        })
+        .unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(rx.iter().count(), 3);
+    assert_eq!((&pool).into_iter().count(), 3);
+}
@@ -0,0 +1,63 @@
+#![cfg(all(feature = "logging", not(feature = "tracing")))]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use mtserver::ThreadPoolBuilder;
+use std::sync::Mutex;
+
+/// Keeps every message logged by the crate.
+struct Messages(Mutex<Vec<(Level, String)>>);
+
+impl Log for Messages {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if record.target().starts_with("mtserver") {
+            self.0
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static MESSAGES: Messages = Messages(Mutex::new(Vec::new()));
+
+#[test]
+fn crashes_and_shutdowns_are_logged() {
+    log::set_logger(&MESSAGES).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .max_restarts(1)
+        .on_job_complete(|_| panic!("hook crashed the worker"))
+        .build()
+        .unwrap();
+    let id = (&pool).into_iter().next().unwrap();
+
+    pool.execute(|| {}).unwrap();
+    pool.execute(|| {}).unwrap();
+    pool.join();
+
+    while pool.is_healthy() {
+        std::thread::yield_now();
+    }
+
+    drop(pool);
+
+    let messages = MESSAGES.0.lock().unwrap();
+
+    assert!(messages.contains(&(
+        Level::Warn,
+        format!("Worker {id} crashed; restarting (1/1).")
+    )));
+    assert!(messages.contains(&(
+        Level::Error,
+        format!("Worker {id} crashed 1 times; giving up.")
+    )));
+    assert!(messages.contains(&(Level::Info, format!("Shutting down worker {id}"))));
+}
@@ -0,0 +1,30 @@
+use mtserver::{MapError, ThreadPool};
+
+#[test]
+fn map_returns_the_results_in_order() {
+    let pool = ThreadPool::new(4);
+    let squares = pool.map(0..1000u64, |i| i * i).unwrap();
+
+    assert_eq!(squares, (0..1000u64).map(|i| i * i).collect::<Vec<_>>());
+}
+
+#[test]
+fn map_of_nothing_is_empty() {
+    let pool = ThreadPool::new(2);
+
+    assert!(pool.map(Vec::<u8>::new(), |i| i).unwrap().is_empty());
+}
+
+#[test]
+fn a_panicking_item_makes_map_return_a_join_error() {
+    let pool = ThreadPool::new(2);
+    let result = pool.map(0..10, |i| {
+        if i == 7 {
+            panic!("item 7");
+        }
+
+        i
+    });
+
+    assert!(matches!(result, Err(MapError::Join(_))));
+}
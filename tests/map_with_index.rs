@@ -0,0 +1,27 @@
+use mtserver::ThreadPool;
+
+#[test]
+fn map_with_index_passes_each_items_position() {
+    let pool = ThreadPool::new(4);
+    let words = ["zero", "one", "two", "three"];
+    let labelled = pool
+        .map_with_index(words, |i, word| format!("{i}:{word}"))
+        .unwrap();
+
+    assert_eq!(labelled, ["0:zero", "1:one", "2:two", "3:three"]);
+}
+
+#[test]
+fn map_with_index_results_can_be_written_back_in_place() {
+    let pool = ThreadPool::new(3);
+    let mut squares = vec![0; 100];
+
+    for (i, square) in pool.map_with_index(0..100u64, |i, x| (i, x * x)).unwrap() {
+        squares[i] = square;
+    }
+
+    assert!(squares
+        .iter()
+        .enumerate()
+        .all(|(i, &s)| s == (i * i) as u64));
+}
@@ -0,0 +1,56 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+#[test]
+fn every_producers_jobs_still_run() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .max_consecutive_jobs(4)
+        .build()
+        .unwrap();
+    let ran = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+
+    thread::scope(|s| {
+        for producer in 0..2 {
+            let pool = &pool;
+            let ran = Arc::clone(&ran);
+
+            s.spawn(move || {
+                for _ in 0..500 {
+                    let ran = Arc::clone(&ran);
+
+                    pool.execute(move || {
+                        ran[producer].fetch_add(1, Ordering::Relaxed);
+                    })
+                    .unwrap();
+                }
+            });
+        }
+    });
+
+    pool.join();
+
+    assert_eq!(ran[0].load(Ordering::Relaxed), 500);
+    assert_eq!(ran[1].load(Ordering::Relaxed), 500);
+}
+
+#[test]
+fn zero_is_raised_to_one() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .max_consecutive_jobs(0)
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        pool.execute(|| {}).unwrap();
+    }
+
+    pool.join();
+}
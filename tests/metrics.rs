@@ -0,0 +1,77 @@
+#![cfg(feature = "metrics")]
+
+use mtserver::ThreadPoolBuilder;
+use prometheus::Registry;
+
+/// The value of the metric `name` labelled with `pool`.
+fn value(registry: &Registry, name: &str, pool: &str) -> f64 {
+    let family = registry
+        .gather()
+        .into_iter()
+        .find(|family| family.name() == name)
+        .unwrap();
+    let metric = family
+        .get_metric()
+        .iter()
+        .find(|m| m.get_label().iter().any(|l| l.value() == pool))
+        .unwrap();
+
+    match name {
+        n if n.ends_with("_total") => metric.get_counter().get_value(),
+        _ => metric.get_gauge().get_value(),
+    }
+}
+
+#[test]
+fn completed_and_panicked_jobs_are_exported() {
+    let registry = Registry::new();
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .with_metrics(&registry, "workers")
+        .build()
+        .unwrap();
+
+    for i in 0..5 {
+        pool.execute(move || assert_ne!(i, 0)).unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(
+        value(&registry, "thread_pool_completed_jobs_total", "workers"),
+        4.0
+    );
+    assert_eq!(value(&registry, "thread_pool_panics_total", "workers"), 1.0);
+    assert_eq!(
+        value(&registry, "thread_pool_active_threads", "workers"),
+        0.0
+    );
+    assert_eq!(value(&registry, "thread_pool_queued_jobs", "workers"), 0.0);
+}
+
+#[test]
+fn two_pools_are_told_apart_by_their_label() {
+    let registry = Registry::new();
+    let build = |name| {
+        ThreadPoolBuilder::new()
+            .num_threads(1)
+            .with_metrics(&registry, name)
+            .build()
+            .unwrap()
+    };
+    let (first, second) = (build("first"), build("second"));
+
+    first.execute(|| {}).unwrap();
+    first.join();
+
+    assert_eq!(
+        value(&registry, "thread_pool_completed_jobs_total", "first"),
+        1.0
+    );
+    assert_eq!(
+        value(&registry, "thread_pool_completed_jobs_total", "second"),
+        0.0
+    );
+
+    drop(second);
+}
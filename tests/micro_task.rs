@@ -0,0 +1,67 @@
+use mtserver::{MicroTaskPool, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+};
+
+#[test]
+fn many_tasks_share_a_few_workers() {
+    let pool = ThreadPool::new(4);
+    let micro = MicroTaskPool::new(pool.clone(), 2).unwrap();
+    let steps = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..1000 {
+        let steps = Arc::clone(&steps);
+        let mut left = 5;
+
+        micro
+            .execute(move || {
+                steps.fetch_add(1, Ordering::Relaxed);
+                left -= 1;
+
+                if left == 0 {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .unwrap();
+    }
+
+    micro.join();
+
+    assert_eq!(steps.load(Ordering::Relaxed), 5000);
+    assert_eq!(micro.task_count(), 0);
+
+    // The schedulers give their Workers back once the MicroTaskPool is gone.
+    drop(micro);
+    pool.join();
+}
+
+#[test]
+fn a_panicking_task_is_dropped_without_stopping_the_others() {
+    let micro = MicroTaskPool::new(ThreadPool::new(1), 1).unwrap();
+    let done = Arc::new(AtomicUsize::new(0));
+
+    micro
+        .execute(|| -> Poll<()> { panic!("micro-task panicked") })
+        .unwrap();
+
+    for _ in 0..10 {
+        let done = Arc::clone(&done);
+
+        micro
+            .execute(move || {
+                done.fetch_add(1, Ordering::Relaxed);
+                Poll::Ready(())
+            })
+            .unwrap();
+    }
+
+    micro.join();
+
+    assert_eq!(done.load(Ordering::Relaxed), 10);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "numa")]
+
+use mtserver::{NUMAPoolBuilder, ThreadPoolError};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn every_node_gets_a_pool_by_default() {
+    let builder = NUMAPoolBuilder::new();
+    let nodes = builder.node_count();
+    let pool = builder.build().unwrap();
+
+    assert!(nodes >= 1);
+    assert_eq!(pool.node_count(), nodes);
+    assert!(pool.current_node() < nodes);
+    assert!((0..nodes).any(|node| pool.pool(node).is_some()));
+    assert!(pool.pool(nodes).is_none());
+}
+
+#[test]
+fn node_threads_sets_the_size_of_one_node() {
+    let pool = NUMAPoolBuilder::new().node_threads(0, 3).build().unwrap();
+
+    assert_eq!(pool.pool(0).unwrap().size(), 3);
+}
+
+#[test]
+fn submit_local_runs_the_job() {
+    let pool = NUMAPoolBuilder::new().node_threads(0, 2).build().unwrap();
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..8 {
+        let ran = Arc::clone(&ran);
+
+        pool.submit_local(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 8);
+
+    pool.shutdown().unwrap();
+}
+
+#[test]
+fn jobs_for_a_node_without_workers_go_elsewhere() {
+    let builder = NUMAPoolBuilder::new();
+    let nodes = builder.node_count();
+    let builder = (0..nodes).fold(builder, |builder, node| builder.node_threads(node, 0));
+
+    assert!(matches!(
+        builder.clone().build(),
+        Err(ThreadPoolError::InvalidSize)
+    ));
+
+    // Only the last node has Workers, so every job ends up there.
+    let pool = builder.node_threads(nodes - 1, 1).build().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    pool.submit_local(move || tx.send(()).unwrap()).unwrap();
+
+    rx.recv().unwrap();
+    assert_eq!(pool.pool(nodes - 1).unwrap().size(), 1);
+}
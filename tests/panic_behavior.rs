@@ -0,0 +1,112 @@
+use mtserver::ThreadPool;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc, Mutex,
+};
+
+#[test]
+fn jobs_after_a_panic_still_run_on_the_same_worker() {
+    // A single Worker, so the job after the panic has nowhere else to run.
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute(|| panic!("first job panicked")).unwrap();
+
+    for i in 0..10 {
+        let tx = tx.clone();
+        pool.execute(move || tx.send(i).unwrap()).unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn every_normal_job_completes_alongside_panicking_ones() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for i in 0..105 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            if i % 21 == 20 {
+                panic!("job {i} panicked");
+            }
+
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn join_returns_after_panicking_jobs() {
+    let pool = ThreadPool::new(2);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for i in 0..10 {
+        pool.execute(move || panic!("job {i} panicked")).unwrap();
+    }
+
+    pool.join();
+
+    for _ in 0..10 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::Relaxed), 10);
+    assert_eq!(pool.queued_count(), 0);
+}
+
+#[test]
+fn the_panic_hook_fires_once_per_panic() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&messages);
+
+    let pool = ThreadPool::builder()
+        .num_threads(3)
+        .on_job_panic(move |_, e| {
+            seen.lock()
+                .unwrap()
+                .push(e.message().unwrap_or_default().to_owned());
+        })
+        .build()
+        .unwrap();
+
+    for i in 0..20 {
+        pool.execute(move || {
+            if i % 4 == 0 {
+                panic!("job {i} panicked");
+            }
+        })
+        .unwrap();
+    }
+
+    // The hook runs after a job stops being pending, so wait for the Workers
+    // themselves rather than just the jobs.
+    assert!(pool.shutdown().is_ok());
+
+    let mut messages = messages.lock().unwrap().clone();
+    messages.sort();
+
+    let mut expected: Vec<_> = (0..20)
+        .step_by(4)
+        .map(|i| format!("job {i} panicked"))
+        .collect();
+    expected.sort();
+
+    assert_eq!(messages, expected);
+}
@@ -0,0 +1,30 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+static HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn the_previous_hook_still_runs() {
+    panic::set_hook(Box::new(|_| {
+        HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+    }));
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .install_panic_hook()
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute(|| panic!("job panicked")).unwrap();
+    pool.execute(move || tx.send(()).unwrap()).unwrap();
+    rx.recv().unwrap();
+
+    assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,27 @@
+use mtserver::{worker_count_for_parallelism, ThreadPoolBuilder};
+use std::thread;
+
+#[test]
+fn the_worker_count_scales_with_the_cpus() {
+    let cpus = thread::available_parallelism().map_or(4, |n| n.get());
+
+    assert_eq!(worker_count_for_parallelism(1.0), cpus);
+    assert_eq!(worker_count_for_parallelism(2.0), cpus * 2);
+}
+
+#[test]
+fn the_worker_count_is_at_least_one() {
+    for factor in [0.0, 0.01, -3.0, f32::NAN] {
+        assert_eq!(worker_count_for_parallelism(factor), 1);
+    }
+}
+
+#[test]
+fn num_threads_by_factor_sizes_the_pool() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads_by_factor(2.0)
+        .build()
+        .unwrap();
+
+    assert_eq!(pool.size(), worker_count_for_parallelism(2.0));
+}
@@ -0,0 +1,59 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn a_paused_pool_keeps_its_jobs_until_resumed() {
+    let pool = ThreadPool::new(2);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    pool.pause();
+
+    for _ in 0..10 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    thread::sleep(Duration::from_millis(50));
+
+    assert!(pool.is_paused());
+    assert_eq!(done.load(Ordering::SeqCst), 0);
+    assert_eq!(pool.queued_count(), 10);
+
+    pool.resume();
+    pool.join();
+
+    assert!(!pool.is_paused());
+    assert_eq!(done.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn pausing_lets_the_running_job_finish() {
+    let pool = ThreadPool::new(1);
+    let (started_tx, started) = mpsc::channel();
+    let (finished_tx, finished) = mpsc::channel();
+
+    pool.execute(move || {
+        started_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        finished_tx.send(()).unwrap();
+    })
+    .unwrap();
+
+    started.recv().unwrap();
+    pool.pause();
+
+    assert!(finished.recv_timeout(Duration::from_secs(5)).is_ok());
+
+    pool.resume();
+}
@@ -0,0 +1,65 @@
+use mtserver::ThreadPool;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn a_panicking_job_does_not_kill_sibling_workers() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for i in 0..100 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            if i % 10 == 0 {
+                panic!("job {i} panicked");
+            }
+
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::Relaxed), 90);
+
+    // Every Worker is still alive to run its copy of a broadcast job.
+    assert!(pool.broadcast(|| ()).is_ok());
+}
+
+#[test]
+fn the_pool_keeps_working_after_a_panicking_hook() {
+    let pool = ThreadPool::builder()
+        .num_threads(2)
+        .on_job_panic(|_, _| panic!("hook panicked"))
+        .build()
+        .unwrap();
+
+    pool.execute(|| panic!("job panicked")).unwrap();
+    pool.join();
+
+    assert_eq!(
+        pool.map(0..10, |x| x + 1).unwrap(),
+        (1..11).collect::<Vec<_>>()
+    );
+    assert!(pool.shutdown().is_ok());
+}
+
+#[test]
+fn panicking_thread_hooks_leave_resize_and_shutdown_working() {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .on_thread_start(|_| panic!("start hook panicked"))
+        .on_thread_stop(|_| panic!("stop hook panicked"))
+        .build()
+        .unwrap();
+
+    pool.resize(3).unwrap();
+    pool.resize(1).unwrap();
+
+    assert_eq!(pool.spawn(|| 4).unwrap().join().unwrap(), 4);
+    assert!(pool.shutdown().is_ok());
+}
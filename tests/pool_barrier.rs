@@ -0,0 +1,37 @@
+use mtserver::{PoolBarrier, ThreadPool};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn no_job_starts_a_phase_before_every_job_has_finished_the_last() {
+    const JOBS: usize = 4;
+
+    let pool = ThreadPool::new(JOBS);
+    let barrier = Arc::new(PoolBarrier::new(JOBS));
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let leaders = pool
+        .map(0..JOBS, {
+            let (barrier, log) = (Arc::clone(&barrier), Arc::clone(&log));
+
+            move |_| {
+                let mut leader = 0;
+
+                for phase in 0..3 {
+                    log.lock().unwrap().push(phase);
+                    leader += usize::from(barrier.wait());
+                }
+
+                leader
+            }
+        })
+        .unwrap();
+
+    let log = log.lock().unwrap();
+
+    assert_eq!(barrier.parties(), JOBS);
+    assert_eq!(leaders.iter().sum::<usize>(), 3);
+    assert!(log
+        .chunks(JOBS)
+        .enumerate()
+        .all(|(phase, chunk)| chunk.iter().all(|&p| p == phase)));
+}
@@ -0,0 +1,67 @@
+use mtserver::{Priority, ThreadPool};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+#[test]
+fn a_high_job_submitted_after_a_low_burst_runs_first() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    for i in 0..20 {
+        let ran = Arc::clone(&ran);
+
+        pool.execute_with_priority(move || ran.lock().unwrap().push(Some(i)), Priority::Low)
+            .unwrap();
+    }
+
+    let high = Arc::clone(&ran);
+
+    pool.execute_with_priority(move || high.lock().unwrap().push(None), Priority::High)
+        .unwrap();
+
+    drop(tx);
+    pool.join();
+
+    let ran = ran.lock().unwrap();
+    let low: Vec<_> = ran[1..].iter().flatten().copied().collect();
+
+    assert_eq!(ran[0], None);
+    assert_eq!(low, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn normal_jobs_run_between_high_and_low_ones() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    for priority in [Priority::Low, Priority::Normal, Priority::High] {
+        let ran = Arc::clone(&ran);
+
+        pool.execute_with_priority(move || ran.lock().unwrap().push(priority), priority)
+            .unwrap();
+    }
+
+    drop(tx);
+    pool.join();
+
+    assert_eq!(
+        *ran.lock().unwrap(),
+        [Priority::High, Priority::Normal, Priority::Low]
+    );
+}
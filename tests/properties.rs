@@ -0,0 +1,138 @@
+use mtserver::ThreadPool;
+use proptest::prelude::*;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// A call made on the ThreadPool in a generated schedule.
+#[derive(Debug, Clone)]
+enum Op {
+    Execute(usize),
+    Pause,
+    Resume,
+    Resize(usize),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (0..200usize).prop_map(Op::Execute),
+        1 => Just(Op::Pause),
+        1 => Just(Op::Resume),
+        1 => (1..=8usize).prop_map(Op::Resize),
+    ]
+}
+
+fn submit(pool: &ThreadPool, n: usize, done: &Arc<AtomicUsize>) {
+    for _ in 0..n {
+        let done = Arc::clone(done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn every_job_completes(size in 1..=64usize, job_count in 0..=10_000usize) {
+        let pool = ThreadPool::new(size);
+        let done = Arc::new(AtomicUsize::new(0));
+
+        submit(&pool, job_count, &done);
+        pool.join();
+
+        prop_assert_eq!(done.load(Ordering::Relaxed), job_count);
+        prop_assert_eq!(pool.queued_count(), 0);
+
+        prop_assert_eq!(pool.active_count(), 0);
+
+        let snapshot = pool.snapshot();
+
+        prop_assert_eq!(snapshot.total_completed + snapshot.total_panics, job_count as u64);
+    }
+
+    #[test]
+    fn any_schedule_completes_every_job(
+        size in 1..=8usize,
+        ops in prop::collection::vec(op(), 0..20),
+    ) {
+        let pool = ThreadPool::new(size);
+        let done = Arc::new(AtomicUsize::new(0));
+        let mut submitted = 0;
+        let mut size = size;
+
+        for op in ops {
+            match op {
+                Op::Execute(n) => {
+                    submit(&pool, n, &done);
+                    submitted += n;
+                }
+                Op::Pause => pool.pause(),
+                Op::Resume => pool.resume(),
+                Op::Resize(n) => {
+                    pool.resize(n).unwrap();
+                    size = n;
+                }
+            }
+
+            prop_assert!(pool.active_count() <= pool.size());
+        }
+
+        pool.resume();
+        pool.join();
+
+        prop_assert_eq!(done.load(Ordering::Relaxed), submitted);
+        prop_assert_eq!(pool.queued_count(), 0);
+        prop_assert_eq!(pool.size(), size);
+
+        prop_assert_eq!(pool.active_count(), 0);
+
+        let snapshot = pool.snapshot();
+
+        prop_assert_eq!(snapshot.total_completed + snapshot.total_panics, submitted as u64);
+    }
+
+    #[test]
+    fn execute_fails_after_shutdown(size in 1..=8usize, job_count in 0..100usize) {
+        let pool = ThreadPool::new(size);
+        let handle = pool.clone();
+        let done = Arc::new(AtomicUsize::new(0));
+
+        submit(&pool, job_count, &done);
+        pool.shutdown().unwrap();
+
+        prop_assert_eq!(done.load(Ordering::Relaxed), job_count);
+        prop_assert!(handle.execute(|| ()).is_err());
+    }
+
+    #[test]
+    fn panicking_jobs_dont_lose_other_jobs(
+        size in 1..=8usize,
+        panics in prop::collection::vec(any::<bool>(), 0..200),
+    ) {
+        let pool = ThreadPool::new(size);
+        let done = Arc::new(AtomicUsize::new(0));
+        let expected = panics.iter().filter(|&&panics| !panics).count();
+
+        for panics in panics {
+            let done = Arc::clone(&done);
+
+            pool.execute(move || {
+                if panics {
+                    panic!("generated panic");
+                }
+
+                done.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        }
+
+        pool.join();
+
+        prop_assert_eq!(done.load(Ordering::Relaxed), expected);
+    }
+}
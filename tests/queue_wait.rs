@@ -0,0 +1,44 @@
+use mtserver::{Histogram, ThreadPool};
+use std::{sync::mpsc, thread, time::Duration};
+
+#[test]
+fn a_job_stuck_behind_a_slow_one_shows_up_in_the_tail() {
+    let pool = ThreadPool::new(1);
+    let (started_tx, started_rx) = mpsc::channel();
+
+    pool.execute(move || {
+        started_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+    })
+    .unwrap();
+    started_rx.recv().unwrap();
+    pool.execute(|| ()).unwrap();
+    pool.join();
+
+    let histogram = pool.queue_wait_histogram();
+
+    assert_eq!(histogram.count(), 2);
+    assert!(histogram.p99().unwrap() >= Duration::from_millis(15));
+}
+
+#[test]
+fn quantiles_are_bucket_upper_bounds() {
+    let mut counts = vec![0; 32];
+    counts[0] = 98;
+    counts[3] = 1;
+    counts[10] = 1;
+
+    let histogram = Histogram { counts };
+
+    assert_eq!(histogram.quantile(0.5), Some(Duration::from_micros(1)));
+    assert_eq!(histogram.quantile(0.99), Some(Duration::from_micros(8)));
+    assert_eq!(histogram.quantile(1.0), Some(Duration::from_micros(1024)));
+    assert_eq!(
+        Histogram {
+            counts: vec![0; 32]
+        }
+        .p99(),
+        None
+    );
+    assert_eq!(Histogram::upper_bound(31), Duration::MAX);
+}
@@ -0,0 +1,43 @@
+use mtserver::{RateLimitedPool, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+#[test]
+fn submissions_never_exceed_the_rate() {
+    let pool = RateLimitedPool::new(ThreadPool::new(4), 100.0);
+    let done = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+
+    for _ in 0..1000 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
+    pool.pool().join();
+
+    // The first second's worth of jobs goes through at once, and the other
+    // 900 at 100 per second.
+    assert!(started.elapsed() >= Duration::from_secs(9));
+    assert_eq!(done.load(Ordering::Relaxed), 1000);
+}
+
+#[test]
+fn a_burst_within_the_rate_is_not_delayed() {
+    let pool = RateLimitedPool::new(ThreadPool::new(2), 1000.0);
+    let started = Instant::now();
+
+    for _ in 0..100 {
+        pool.execute(|| ()).unwrap();
+    }
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
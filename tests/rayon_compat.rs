@@ -0,0 +1,50 @@
+#![cfg(feature = "rayon-compat")]
+
+use mtserver::ThreadPool;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
+fn fib(pool: &ThreadPool, n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+
+    let inner = pool.clone();
+    let (a, b) = pool.join_pair(|| fib(pool, n - 1), move || fib(&inner, n - 2));
+
+    a + b
+}
+
+#[test]
+fn install_runs_on_a_worker() {
+    let pool = ThreadPool::new(2);
+    let caller = thread::current().id();
+
+    assert_ne!(pool.install(move || thread::current().id()), caller);
+}
+
+#[test]
+fn nested_join_pairs_dont_deadlock_a_small_pool() {
+    let pool = ThreadPool::new(2);
+    let inner = pool.clone();
+
+    assert_eq!(pool.install(move || fib(&inner, 15)), 610);
+}
+
+#[test]
+fn join_runs_both_closures_on_the_global_pool() {
+    assert_eq!(mtserver::join(|| 1 + 1, || "two"), (2, "two"));
+}
+
+#[test]
+fn a_panic_in_either_closure_reaches_the_caller() {
+    let pool = ThreadPool::new(2);
+
+    let a = panic::catch_unwind(AssertUnwindSafe(|| pool.join_pair(|| panic!("a"), || ())));
+    let b = panic::catch_unwind(AssertUnwindSafe(|| pool.join_pair(|| (), || panic!("b"))));
+
+    assert!(a.is_err());
+    assert!(b.is_err());
+}
@@ -0,0 +1,30 @@
+use mtserver::ThreadPool;
+
+#[test]
+fn reduce_sums_the_mapped_items() {
+    let pool = ThreadPool::new(4);
+    let sum = pool.reduce(1..=100u64, |x| x * x, |a, b| a + b, 0).unwrap();
+
+    assert_eq!(sum, 338_350);
+}
+
+#[test]
+fn reduce_keeps_the_items_in_order() {
+    let pool = ThreadPool::new(3);
+    let joined = pool
+        .reduce(0..10, |i| i.to_string(), |a, b| a + &b, String::from(">"))
+        .unwrap();
+
+    assert_eq!(joined, ">0123456789");
+}
+
+#[test]
+fn reduce_of_nothing_is_init() {
+    let pool = ThreadPool::new(2);
+
+    assert_eq!(
+        pool.reduce(Vec::<u8>::new(), |x| x, |a, b| a.max(b), 7)
+            .unwrap(),
+        7
+    );
+}
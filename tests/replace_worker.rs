@@ -0,0 +1,106 @@
+use mtserver::{ThreadPool, ThreadPoolError};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// A ThreadPool whose Workers never restart their loop, along with a flag
+/// that makes the Worker that runs the next job crash once it finishes.
+fn fragile_pool(num_threads: usize) -> (ThreadPool, Arc<AtomicBool>) {
+    let crash = Arc::new(AtomicBool::new(false));
+    let hook_crash = Arc::clone(&crash);
+
+    let pool = ThreadPool::builder()
+        .num_threads(num_threads)
+        .max_restarts(0)
+        .on_job_complete(move |_| {
+            if hook_crash.swap(false, Ordering::Relaxed) {
+                panic!("worker crashed");
+            }
+        })
+        .build()
+        .unwrap();
+
+    (pool, crash)
+}
+
+/// Crash one of the pool's Workers and wait for its thread to stop.
+///
+/// # Return
+///
+/// The id of the crashed Worker.
+fn crash_a_worker(pool: &ThreadPool, crash: &AtomicBool) -> usize {
+    crash.store(true, Ordering::Relaxed);
+    pool.execute(|| ()).unwrap();
+
+    loop {
+        if let [id] = pool.unhealthy_worker_ids()[..] {
+            return id;
+        }
+
+        thread::yield_now();
+    }
+}
+
+#[test]
+fn a_crashed_worker_can_be_replaced() {
+    let (pool, crash) = fragile_pool(2);
+    let id = crash_a_worker(&pool, &crash);
+
+    assert!(!pool.is_healthy());
+
+    pool.replace_worker(id).unwrap();
+
+    assert!(pool.is_healthy());
+    assert!(pool.unhealthy_worker_ids().is_empty());
+
+    // Both Workers are alive to run their copy of a broadcast job.
+    let ran = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&ran);
+
+    pool.broadcast(move || {
+        counted.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+
+    assert_eq!(ran.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn a_live_worker_is_not_replaced() {
+    let (pool, crash) = fragile_pool(2);
+    let dead = crash_a_worker(&pool, &crash);
+    let alive = 1 - dead;
+
+    assert!(matches!(
+        pool.replace_worker(alive),
+        Err(ThreadPoolError::WorkerStillAlive(id)) if id == alive
+    ));
+}
+
+#[test]
+fn an_unknown_worker_is_not_replaced() {
+    let pool = ThreadPool::new(2);
+
+    assert!(matches!(
+        pool.replace_worker(7),
+        Err(ThreadPoolError::UnknownWorker(7))
+    ));
+}
+
+#[test]
+fn a_replacement_worker_gets_a_new_id() {
+    let (pool, crash) = fragile_pool(2);
+    let dead = crash_a_worker(&pool, &crash);
+    let replacement = pool.replace_worker(dead).unwrap();
+
+    assert_ne!(replacement, dead);
+    assert!(replacement >= 2);
+    assert!(matches!(
+        pool.replace_worker(dead),
+        Err(ThreadPoolError::UnknownWorker(id)) if id == dead
+    ));
+}
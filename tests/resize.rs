@@ -0,0 +1,54 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Number of jobs submitted at each size.
+const JOBS: usize = 200;
+
+/// Submit `JOBS` jobs that each take a moment and count themselves in
+/// `done`.
+fn submit(pool: &ThreadPool, done: &Arc<AtomicUsize>) {
+    for _ in 0..JOBS {
+        let done = Arc::clone(done);
+
+        pool.execute(move || {
+            thread::sleep(Duration::from_micros(100));
+            done.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn resizing_4_to_8_to_2_runs_every_job() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    submit(&pool, &done);
+    pool.resize(8).unwrap();
+    submit(&pool, &done);
+    pool.resize(2).unwrap();
+    submit(&pool, &done);
+
+    pool.join();
+
+    assert_eq!(done.load(Ordering::SeqCst), 3 * JOBS);
+    assert_eq!(pool.size(), 2);
+
+    // Shrinking retires the extra Workers once they are idle.
+    while (&pool).into_iter().count() > 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    submit(&pool, &done);
+    pool.join();
+
+    assert_eq!(done.load(Ordering::SeqCst), 4 * JOBS);
+    assert_eq!((&pool).into_iter().count(), 2);
+}
@@ -0,0 +1,58 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+#[test]
+fn a_crashed_worker_is_restarted_and_keeps_running_jobs() {
+    let crashed = AtomicBool::new(false);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .max_restarts(1)
+        .on_job_complete(move |_| {
+            if !crashed.swap(true, Ordering::SeqCst) {
+                panic!("hook crashed the worker");
+            }
+        })
+        .build()
+        .unwrap();
+    let ids: Vec<_> = (&pool).into_iter().collect();
+    let (tx, rx) = mpsc::channel();
+
+    for i in 0..5 {
+        let tx = tx.clone();
+
+        pool.execute(move || tx.send(i).unwrap()).unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), (0..5).collect::<Vec<_>>());
+
+    pool.join();
+
+    assert!(pool.is_healthy());
+    assert_eq!((&pool).into_iter().collect::<Vec<_>>(), ids);
+}
+
+#[test]
+fn a_worker_gives_up_after_max_restarts() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .max_restarts(0)
+        .on_job_complete(|_| panic!("hook crashed the worker"))
+        .build()
+        .unwrap();
+
+    pool.execute(|| {}).unwrap();
+
+    while pool.unhealthy_worker_ids().is_empty() {
+        thread::yield_now();
+    }
+
+    assert!(!pool.is_healthy());
+}
@@ -0,0 +1,55 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn a_job_is_retried_until_it_succeeds() {
+    let pool = ThreadPool::new(2);
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&attempts);
+
+    pool.execute_with_retry(
+        move || {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("not yet");
+            }
+        },
+        5,
+    )
+    .unwrap();
+
+    pool.join();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn only_the_last_attempt_reaches_on_job_panic() {
+    let panics = Arc::new(AtomicUsize::new(0));
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let hook = Arc::clone(&panics);
+    let counter = Arc::clone(&attempts);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .on_job_panic(move |_, _| {
+            hook.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+    pool.execute_with_retry(
+        move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            panic!("always fails");
+        },
+        2,
+    )
+    .unwrap();
+
+    pool.join();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(panics.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,122 @@
+use mtserver::ThreadPool;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+#[test]
+fn scoped_jobs_can_borrow_a_str_from_the_stack() {
+    let pool = ThreadPool::new(4);
+    let greeting = String::from("hello from the stack");
+    let borrowed: &str = &greeting;
+    let seen = Mutex::new(Vec::new());
+
+    pool.scope(|s| {
+        for _ in 0..8 {
+            s.execute(|| seen.lock().unwrap().push(borrowed.len()))
+                .unwrap();
+        }
+    });
+
+    assert_eq!(*seen.lock().unwrap(), vec![borrowed.len(); 8]);
+}
+
+#[test]
+fn scope_waits_for_every_job() {
+    let pool = ThreadPool::new(2);
+    let done = AtomicUsize::new(0);
+
+    pool.scope(|s| {
+        for _ in 0..100 {
+            s.execute(|| {
+                done.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        }
+    });
+
+    assert_eq!(done.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn scope_returns_the_closures_value() {
+    let pool = ThreadPool::new(2);
+    let numbers = [1, 2, 3];
+
+    let count = pool.scope(|s| {
+        s.execute(|| assert_eq!(numbers.iter().sum::<i32>(), 6))
+            .unwrap();
+        numbers.len()
+    });
+
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn a_panicking_scoped_job_panics_the_scope() {
+    let pool = ThreadPool::new(2);
+    let message = "boom";
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.execute(|| panic!("{message}")).unwrap();
+        })
+    }));
+
+    assert!(result.is_err());
+
+    // The pool is still usable afterwards.
+    pool.scope(|s| s.execute(|| ()).unwrap());
+}
+
+#[test]
+fn scope_with_pool_runs_sub_jobs_without_the_parents_workers() {
+    // The parent's only Worker is busy running the outer job, so the sub-jobs
+    // would never run if they were submitted to the parent.
+    let pool = ThreadPool::new(1);
+    let parent = pool.clone();
+    let (tx, rx) = mpsc::channel();
+
+    pool.execute(move || {
+        let total = parent.scope_with_pool(2, |child| {
+            let done = Arc::new(AtomicUsize::new(0));
+
+            for i in 1..=10 {
+                let done = Arc::clone(&done);
+
+                child
+                    .execute(move || {
+                        done.fetch_add(i, Ordering::Relaxed);
+                    })
+                    .unwrap();
+            }
+
+            done
+        });
+
+        tx.send(total.load(Ordering::Relaxed)).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(rx.recv().unwrap(), 55);
+}
+
+#[test]
+fn scoped_jobs_can_mutate_disjoint_borrowed_data() {
+    let pool = ThreadPool::new(4);
+    let mut chunks = vec![vec![1u64; 100]; 8];
+
+    pool.scope(|s| {
+        for (i, chunk) in chunks.iter_mut().enumerate() {
+            s.execute(move || chunk.iter_mut().for_each(|x| *x *= i as u64))
+                .unwrap();
+        }
+    });
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        assert!(chunk.iter().all(|&x| x == i as u64));
+    }
+}
@@ -0,0 +1,35 @@
+use mtserver::{JoinHandle, ThreadPool, ThreadPoolBuilder};
+use std::{sync::Arc, thread};
+
+fn assert_send<T: Send>() {}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn the_public_types_can_cross_threads() {
+    assert_send_sync::<ThreadPool>();
+    assert_send_sync::<ThreadPoolBuilder>();
+    assert_send::<JoinHandle<u8>>();
+}
+
+#[test]
+fn a_pool_can_be_shared_and_moved_across_threads() {
+    let pool = Arc::new(ThreadPool::new(2));
+
+    let results: Vec<_> = (0..4)
+        .map(|i| {
+            let pool = Arc::clone(&pool);
+
+            thread::spawn(move || pool.spawn(move || i * 2).unwrap().join().unwrap())
+        })
+        .map(|t| t.join().unwrap())
+        .collect();
+
+    assert_eq!(results, [0, 2, 4, 6]);
+
+    let pool = Arc::into_inner(pool).unwrap();
+
+    thread::spawn(move || pool.shutdown().unwrap())
+        .join()
+        .unwrap();
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "tower")]
+
+use mtserver::{ExecuteError, Job, ThreadPool};
+use std::{
+    future::Future,
+    pin::pin,
+    sync::mpsc,
+    task::{Context, Poll, Waker},
+    thread,
+};
+use tower_service::Service;
+
+#[test]
+fn call_submits_the_job() {
+    let mut pool = ThreadPool::new(1);
+    let mut cx = Context::from_waker(Waker::noop());
+    let (tx, rx) = mpsc::channel();
+
+    assert!(matches!(pool.poll_ready(&mut cx), Poll::Ready(Ok(()))));
+
+    let job: Job = Box::new(move || tx.send(8).unwrap());
+    let future = pin!(pool.call(job));
+
+    assert!(matches!(future.poll(&mut cx), Poll::Ready(Ok(()))));
+    assert_eq!(rx.recv().unwrap(), 8);
+}
+
+#[test]
+fn poll_ready_is_pending_while_the_queue_is_full() {
+    let mut pool = ThreadPool::builder()
+        .num_threads(1)
+        .channel_capacity(1)
+        .build()
+        .unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    pool.execute(|| {}).unwrap();
+
+    assert!(pool.poll_ready(&mut cx).is_pending());
+
+    drop(tx);
+    pool.join();
+
+    assert!(matches!(pool.poll_ready(&mut cx), Poll::Ready(Ok(()))));
+
+    pool.clone().shutdown().unwrap();
+
+    assert!(matches!(
+        pool.poll_ready(&mut cx),
+        Poll::Ready(Err(ExecuteError::Shutdown))
+    ));
+}
@@ -0,0 +1,121 @@
+use mtserver::{ExecuteError, ShedPolicy, ThreadPool};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+const DEPTH: usize = 10;
+const JOBS: usize = DEPTH * 10;
+
+/// The numbers of the jobs that ran, in the order they ran.
+type Ran = Arc<Mutex<Vec<usize>>>;
+
+/// A single-Worker ThreadPool whose Worker is stuck on a job until the
+/// returned Sender is dropped, so every job submitted meanwhile waits in the
+/// queue.
+fn blocked_pool(policy: ShedPolicy) -> (ThreadPool, mpsc::Sender<()>) {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .max_queue_depth(DEPTH)
+        .shed_policy(policy)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    (pool, tx)
+}
+
+/// Submit `JOBS` jobs numbered in order, checking the queue never grows past
+/// `DEPTH`.
+///
+/// # Return
+///
+/// The result of submitting each job, and where the jobs record their number
+/// when they run.
+fn overload(pool: &ThreadPool) -> (Vec<Result<(), ExecuteError>>, Ran) {
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let mut results = Vec::new();
+
+    for i in 0..JOBS {
+        let ran = Arc::clone(&ran);
+
+        results.push(pool.execute(move || ran.lock().unwrap().push(i)));
+
+        assert!(pool.queued_count() <= DEPTH);
+    }
+
+    (results, ran)
+}
+
+#[test]
+fn reject_new_fails_once_the_queue_is_full() {
+    let (pool, gate) = blocked_pool(ShedPolicy::RejectNew);
+    let (results, ran) = overload(&pool);
+
+    drop(gate);
+    pool.join();
+
+    assert!(results[..DEPTH].iter().all(Result::is_ok));
+    assert!(results[DEPTH..]
+        .iter()
+        .all(|r| *r == Err(ExecuteError::QueueFull)));
+    assert_eq!(*ran.lock().unwrap(), (0..DEPTH).collect::<Vec<_>>());
+    assert_eq!(pool.shed_count(), 0);
+}
+
+#[test]
+fn drop_newest_keeps_the_first_jobs() {
+    let (pool, gate) = blocked_pool(ShedPolicy::DropNewest);
+    let (results, ran) = overload(&pool);
+
+    drop(gate);
+    pool.join();
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(*ran.lock().unwrap(), (0..DEPTH).collect::<Vec<_>>());
+    assert_eq!(pool.shed_count(), JOBS - DEPTH);
+}
+
+#[test]
+fn drop_oldest_keeps_the_last_jobs() {
+    let (pool, gate) = blocked_pool(ShedPolicy::DropOldest);
+    let (results, ran) = overload(&pool);
+
+    drop(gate);
+    pool.join();
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(
+        *ran.lock().unwrap(),
+        (JOBS - DEPTH..JOBS).collect::<Vec<_>>()
+    );
+    assert_eq!(pool.shed_count(), JOBS - DEPTH);
+}
+
+#[test]
+fn shedding_never_blocks_execute_blocking() {
+    let (pool, gate) = blocked_pool(ShedPolicy::DropNewest);
+
+    for _ in 0..JOBS {
+        pool.execute_blocking(|| ()).unwrap();
+    }
+
+    assert_eq!(pool.queued_count(), DEPTH);
+
+    drop(gate);
+    pool.join();
+}
+
+#[test]
+fn capacity_reports_the_queue_depth() {
+    let (pool, _release) = blocked_pool(ShedPolicy::RejectNew);
+
+    assert_eq!(pool.capacity(), Some(DEPTH));
+    assert_eq!(ThreadPool::new(1).capacity(), None);
+}
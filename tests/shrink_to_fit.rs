@@ -0,0 +1,38 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{Arc, Barrier};
+
+#[test]
+fn shrink_to_fit_retires_idle_workers_down_to_the_minimum() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .min_threads(1)
+        .lazy_spawn()
+        .build()
+        .unwrap();
+    let barrier = Arc::new(Barrier::new(4));
+
+    for _ in 0..4 {
+        let barrier = Arc::clone(&barrier);
+
+        pool.execute(move || {
+            barrier.wait();
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    assert_eq!((&pool).into_iter().count(), 4);
+    assert_eq!(pool.shrink_to_fit(), 3);
+    assert_eq!((&pool).into_iter().count(), 1);
+    assert_eq!(pool.size(), 4);
+    assert_eq!(pool.spawn(|| 2).unwrap().join().unwrap(), 2);
+}
+
+#[test]
+fn a_pool_spawned_up_front_keeps_its_workers() {
+    let pool = ThreadPool::new(3);
+
+    assert_eq!(pool.shrink_to_fit(), 0);
+    assert_eq!((&pool).into_iter().count(), 3);
+}
@@ -0,0 +1,69 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn every_job_is_either_run_or_returned() {
+    let pool = ThreadPool::new(4);
+    let clone = pool.clone();
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..500 {
+        let ran = Arc::clone(&ran);
+
+        pool.execute(move || {
+            thread::sleep(Duration::from_micros(200));
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    thread::sleep(Duration::from_millis(5));
+
+    let returned = pool.shutdown_now();
+    let ran_by_shutdown = ran.load(Ordering::SeqCst);
+
+    assert!(!returned.is_empty());
+    assert_eq!(ran_by_shutdown + returned.len(), 500);
+
+    // The Workers were joined even though a clone is still alive, so nothing
+    // else runs afterwards.
+    thread::sleep(Duration::from_millis(20));
+
+    assert_eq!(ran.load(Ordering::SeqCst), ran_by_shutdown);
+    assert_eq!(clone.execute(|| {}), Err(ExecuteError::Shutdown));
+}
+
+#[test]
+fn returned_jobs_can_still_be_run() {
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    pool.pause();
+
+    for _ in 0..3 {
+        let ran = Arc::clone(&ran);
+
+        pool.execute(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    let returned = pool.shutdown_now();
+
+    assert_eq!(returned.len(), 3);
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+
+    for job in returned {
+        job();
+    }
+
+    assert_eq!(ran.load(Ordering::SeqCst), 3);
+}
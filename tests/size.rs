@@ -0,0 +1,33 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+
+#[test]
+fn size_is_the_number_of_workers_the_pool_was_built_with() {
+    assert_eq!(ThreadPool::new(3).size(), 3);
+
+    let pool = ThreadPoolBuilder::new().num_threads(5).build().unwrap();
+
+    assert_eq!(pool.size(), 5);
+}
+
+#[test]
+fn size_counts_workers_a_lazy_pool_hasnt_spawned_yet() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .lazy_spawn()
+        .build()
+        .unwrap();
+
+    assert_eq!(pool.size(), 4);
+    assert_eq!((&pool).into_iter().count(), 0);
+}
+
+#[test]
+fn size_follows_resize() {
+    let pool = ThreadPool::new(2);
+
+    pool.resize(6).unwrap();
+    assert_eq!(pool.size(), 6);
+
+    pool.resize(1).unwrap();
+    assert_eq!(pool.size(), 1);
+}
@@ -0,0 +1,27 @@
+use mtserver::ThreadPool;
+
+#[test]
+fn a_snapshot_counts_completed_and_panicked_jobs() {
+    let pool = ThreadPool::new(2);
+
+    for i in 0..10 {
+        pool.execute(move || {
+            if i % 5 == 0 {
+                panic!("job {i} panicked");
+            }
+        })
+        .unwrap();
+    }
+
+    pool.join();
+
+    let snapshot = pool.snapshot();
+
+    assert_eq!(snapshot.size, 2);
+    assert_eq!(snapshot.active, 0);
+    assert_eq!(snapshot.idle, 2);
+    assert_eq!(snapshot.queued, 0);
+    assert_eq!(snapshot.total_completed, 8);
+    assert_eq!(snapshot.total_panics, 2);
+    assert_eq!(snapshot.per_worker_busy_ns.len(), 2);
+}
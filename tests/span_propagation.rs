@@ -0,0 +1,109 @@
+#![cfg(feature = "tracing")]
+
+use mtserver::ThreadPool;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tracing::{span, Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+thread_local! {
+    /// The spans entered on the current thread, innermost last.
+    static ENTERED: RefCell<Vec<span::Id>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records which span each of the test's events was emitted in.
+#[derive(Default)]
+struct Spans {
+    next_id: AtomicU64,
+    metadata: Mutex<HashMap<u64, &'static Metadata<'static>>>,
+    events: Mutex<Vec<Option<&'static str>>>,
+}
+
+impl Spans {
+    fn innermost(&self) -> Option<(span::Id, &'static Metadata<'static>)> {
+        let id = ENTERED.with_borrow(|entered| entered.last().cloned())?;
+        let metadata = self.metadata.lock().unwrap()[&id.into_u64()];
+
+        Some((id, metadata))
+    }
+}
+
+impl Subscriber for &'static Spans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        self.metadata.lock().unwrap().insert(id, attrs.metadata());
+
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        // Leaves out the crate's own logging.
+        if event.metadata().target().starts_with("mtserver") {
+            return;
+        }
+
+        let name = self.innermost().map(|(_, metadata)| metadata.name());
+
+        self.events.lock().unwrap().push(name);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        ENTERED.with_borrow_mut(|entered| entered.push(span.clone()));
+    }
+
+    fn exit(&self, _: &span::Id) {
+        ENTERED.with_borrow_mut(|entered| entered.pop());
+    }
+
+    fn current_span(&self) -> Current {
+        match self.innermost() {
+            Some((id, metadata)) => Current::new(id, metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+#[test]
+fn jobs_run_in_the_span_they_were_submitted_from() {
+    let spans: &'static Spans = Box::leak(Box::default());
+
+    tracing::subscriber::set_global_default(spans).unwrap();
+
+    let pool = ThreadPool::new(2);
+
+    tracing::info_span!("request").in_scope(|| {
+        pool.execute(|| tracing::info!("inside the request"))
+            .unwrap();
+    });
+
+    pool.execute(|| tracing::info!("outside any request"))
+        .unwrap();
+    pool.join();
+
+    let events = spans.events.lock().unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events
+            .iter()
+            .filter(|name| **name == Some("request"))
+            .count(),
+        1,
+        "{events:?}"
+    );
+}
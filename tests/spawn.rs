@@ -0,0 +1,32 @@
+use mtserver::ThreadPool;
+use std::sync::mpsc;
+
+#[test]
+fn spawn_returns_the_value_of_the_job() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.spawn(|| "done".to_string()).unwrap();
+
+    assert_eq!(handle.join().unwrap(), "done");
+}
+
+#[test]
+fn every_handle_gets_its_own_result() {
+    let pool = ThreadPool::new(4);
+    let handles: Vec<_> = (0..100u64)
+        .map(|i| pool.spawn(move || i * i).unwrap())
+        .collect();
+
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    assert_eq!(results, (0..100u64).map(|i| i * i).collect::<Vec<_>>());
+}
+
+#[test]
+fn dropping_a_handle_doesnt_stop_the_job() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel();
+
+    drop(pool.spawn(move || tx.send(1).unwrap()).unwrap());
+
+    assert_eq!(rx.recv().unwrap(), 1);
+}
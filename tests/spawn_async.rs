@@ -0,0 +1,42 @@
+mod common;
+
+use common::block_on;
+use mtserver::{JoinError, ThreadPool};
+use std::{thread, time::Duration};
+
+#[test]
+fn awaiting_a_spawned_job_returns_its_value() {
+    let pool = ThreadPool::new(2);
+    let handle = pool
+        .spawn_async(|| {
+            thread::sleep(Duration::from_millis(20));
+            6 * 7
+        })
+        .unwrap();
+
+    assert_eq!(block_on(handle).unwrap(), 42);
+}
+
+#[test]
+fn a_panicking_job_resolves_to_its_panic() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.spawn_async(|| -> u8 { panic!("job failed") }).unwrap();
+
+    match block_on(handle) {
+        Err(JoinError::Panicked(e)) => assert_eq!(e.message(), Some("job failed")),
+        other => panic!("unexpected result {other:?}"),
+    }
+}
+
+#[test]
+fn a_job_dropped_without_running_resolves_to_disconnected() {
+    let pool = ThreadPool::new(1);
+
+    pool.pause();
+
+    let handle = pool.spawn_async(|| 1).unwrap();
+
+    drop(pool.drain_queue());
+
+    assert!(matches!(block_on(handle), Err(JoinError::Disconnected)));
+}
@@ -0,0 +1,34 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder, ThreadPoolError};
+
+/// A stack size no OS can give a thread, so spawning fails like it would at
+/// the process's thread limit.
+const IMPOSSIBLE_STACK_SIZE: usize = usize::MAX;
+
+#[test]
+fn build_returns_spawn_errors_instead_of_panicking() {
+    let result = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .stack_size(IMPOSSIBLE_STACK_SIZE)
+        .build();
+
+    match result {
+        Err(e @ ThreadPoolError::Spawn(_)) => {
+            assert!(e.to_string().starts_with("unable to spawn worker thread"));
+        }
+        other => panic!("expected a spawn error, got {other:?}"),
+    }
+}
+
+#[test]
+fn a_smaller_pool_can_be_built_after_a_spawn_failure() {
+    let failed = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .stack_size(IMPOSSIBLE_STACK_SIZE)
+        .build();
+
+    assert!(matches!(failed, Err(ThreadPoolError::Spawn(_))));
+
+    let pool = ThreadPool::try_new(1).unwrap();
+
+    assert_eq!(pool.spawn(|| 1 + 1).unwrap().join().unwrap(), 2);
+}
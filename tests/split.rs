@@ -0,0 +1,41 @@
+use mtserver::{ThreadPool, ThreadPoolError};
+use std::sync::{Arc, Barrier};
+
+#[test]
+fn split_divides_the_workers() {
+    let pool = ThreadPool::new(5);
+    let (first, second) = pool.split(2).unwrap();
+
+    assert_eq!(first.size(), 2);
+    assert_eq!(second.size(), 3);
+
+    // Every Worker of the second ThreadPool can be busy at once, so it
+    // really has three of its own.
+    let barrier = Arc::new(Barrier::new(4));
+
+    for _ in 0..3 {
+        let barrier = Arc::clone(&barrier);
+        second
+            .execute(move || {
+                barrier.wait();
+            })
+            .unwrap();
+    }
+
+    first.execute(|| ()).unwrap();
+    first.join();
+    barrier.wait();
+    second.join();
+}
+
+#[test]
+fn split_needs_a_worker_on_each_side() {
+    for first_size in [0, 3, 4] {
+        let pool = ThreadPool::new(3);
+
+        assert!(matches!(
+            pool.split(first_size),
+            Err(ThreadPoolError::InvalidSize)
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+use mtserver::ThreadPoolBuilder;
+use std::hint::black_box;
+
+/// Sum `depth` numbers with one stack frame per number.
+fn recurse(depth: usize) -> usize {
+    match depth {
+        0 => 0,
+        n => black_box(n) + recurse(n - 1),
+    }
+}
+
+#[test]
+fn a_pool_with_a_64_kb_stack_runs_jobs() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .stack_size(64 * 1024)
+        .build()
+        .unwrap();
+
+    let sum = pool.spawn(|| recurse(100)).unwrap().join().unwrap();
+
+    assert_eq!(sum, 5050);
+}
+
+#[test]
+fn a_pool_with_a_large_stack_runs_deeply_recursive_jobs() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .stack_size(64 << 20)
+        .build()
+        .unwrap();
+
+    let sum = pool.spawn(|| recurse(100_000)).unwrap().join().unwrap();
+
+    assert_eq!(sum, 100_000 * 100_001 / 2);
+}
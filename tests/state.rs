@@ -0,0 +1,53 @@
+use mtserver::{ExecuteError, PoolState, ThreadPool};
+
+#[test]
+fn a_new_pool_is_created_until_the_first_job() {
+    let pool = ThreadPool::new(2);
+
+    assert_eq!(pool.state(), PoolState::Created);
+
+    pool.execute(|| ()).unwrap();
+
+    assert_eq!(pool.state(), PoolState::Running);
+}
+
+#[test]
+fn pause_and_resume_move_between_running_and_paused() {
+    let pool = ThreadPool::new(2);
+
+    pool.execute(|| ()).unwrap();
+    pool.pause();
+
+    assert_eq!(pool.state(), PoolState::Paused);
+
+    pool.resume();
+
+    assert_eq!(pool.state(), PoolState::Running);
+}
+
+#[test]
+fn shutdown_is_final() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.clone();
+
+    pool.execute(|| ()).unwrap();
+    pool.pause();
+    pool.shutdown().unwrap();
+
+    assert_eq!(handle.state(), PoolState::Shutdown);
+
+    handle.resume();
+
+    assert_eq!(handle.state(), PoolState::Shutdown);
+}
+
+#[test]
+fn execute_fails_on_a_shutdown_pool() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert_eq!(handle.execute(|| ()), Err(ExecuteError::Shutdown));
+    assert_eq!(handle.state(), PoolState::Shutdown);
+}
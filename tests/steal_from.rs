@@ -0,0 +1,26 @@
+use mtserver::ThreadPool;
+
+#[test]
+fn a_worker_moves_from_the_idle_pool_to_the_busy_one() {
+    let busy = ThreadPool::new(1);
+    let idle = ThreadPool::new(3);
+
+    assert!(busy.steal_from(&idle).unwrap());
+
+    assert_eq!(busy.size(), 2);
+    assert_eq!(idle.size(), 2);
+    assert_eq!((&busy).into_iter().count(), 2);
+    assert_eq!((&idle).into_iter().count(), 2);
+    assert_eq!(busy.spawn(|| 5).unwrap().join().unwrap(), 5);
+}
+
+#[test]
+fn the_last_worker_and_the_pool_itself_are_off_limits() {
+    let pool = ThreadPool::new(2);
+    let single = ThreadPool::new(1);
+
+    assert!(!pool.steal_from(&single).unwrap());
+    assert!(!pool.steal_from(&pool.clone()).unwrap());
+    assert_eq!(pool.size(), 2);
+    assert_eq!(single.size(), 1);
+}
@@ -0,0 +1,73 @@
+use mtserver::{GraphError, TaskGraph, TaskId, ThreadPool};
+use std::sync::{Arc, Mutex};
+
+/// Add a task that records its id in `ran` when it runs.
+fn add(graph: &mut TaskGraph, ran: &Arc<Mutex<Vec<usize>>>, id: usize, deps: &[usize]) {
+    let ran = Arc::clone(ran);
+    let deps: Vec<_> = deps.iter().map(|&d| TaskId(d)).collect();
+
+    graph
+        .add_task(TaskId(id), move || ran.lock().unwrap().push(id), &deps)
+        .unwrap();
+}
+
+#[test]
+fn tasks_run_after_their_dependencies() {
+    let pool = ThreadPool::new(4);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = TaskGraph::new();
+
+    // B after A, C after B and D.
+    add(&mut graph, &ran, 0, &[]);
+    add(&mut graph, &ran, 3, &[]);
+    add(&mut graph, &ran, 1, &[0]);
+    add(&mut graph, &ran, 2, &[1, 3]);
+
+    assert_eq!(graph.len(), 4);
+
+    graph.submit_to(&pool).unwrap();
+
+    let ran = ran.lock().unwrap();
+    let at = |id| ran.iter().position(|&r| r == id).unwrap();
+
+    assert_eq!(ran.len(), 4);
+    assert!(at(0) < at(1));
+    assert!(at(1) < at(2));
+    assert!(at(3) < at(2));
+}
+
+#[test]
+fn unknown_dependencies_and_duplicates_are_rejected() {
+    let mut graph = TaskGraph::new();
+
+    assert_eq!(
+        graph.add_task(TaskId(1), || {}, &[TaskId(0)]),
+        Err(GraphError::UnknownDependency(TaskId(0)))
+    );
+
+    graph.add_task(TaskId(0), || {}, &[]).unwrap();
+
+    assert_eq!(
+        graph.add_task(TaskId(0), || {}, &[]),
+        Err(GraphError::DuplicateTask(TaskId(0)))
+    );
+}
+
+#[test]
+fn dependents_of_a_panicking_task_never_run() {
+    let pool = ThreadPool::new(2);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = TaskGraph::new();
+
+    graph
+        .add_task(TaskId(0), || panic!("task 0 failed"), &[])
+        .unwrap();
+    add(&mut graph, &ran, 1, &[0]);
+    add(&mut graph, &ran, 2, &[]);
+
+    assert_eq!(
+        graph.submit_to(&pool),
+        Err(GraphError::Panicked(vec![TaskId(0)]))
+    );
+    assert_eq!(*ran.lock().unwrap(), [2]);
+}
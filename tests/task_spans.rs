@@ -0,0 +1,95 @@
+#![cfg(feature = "tracing")]
+
+use mtserver::ThreadPool;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Keeps the fields of every span named `task`.
+#[derive(Default)]
+struct Spans {
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<u64, HashMap<String, String>>>,
+}
+
+struct Fields<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for &'static Spans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if attrs.metadata().name() == "task" {
+            let mut fields = HashMap::new();
+
+            attrs.record(&mut Fields(&mut fields));
+            self.tasks.lock().unwrap().insert(id, fields);
+        }
+
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        if let Some(fields) = self.tasks.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut Fields(fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn every_job_runs_in_a_blocking_task_span() {
+    let spans: &'static Spans = Box::leak(Box::default());
+
+    tracing::subscriber::set_global_default(spans).unwrap();
+
+    let pool = ThreadPool::new(2);
+
+    for _ in 0..4 {
+        pool.execute(|| {}).unwrap();
+    }
+
+    // Waits for the Workers, so every span has been closed too.
+    pool.shutdown().unwrap();
+
+    let tasks = spans.tasks.lock().unwrap();
+    let mut ids: Vec<_> = tasks.values().map(|fields| &fields["task.id"]).collect();
+
+    ids.sort();
+    ids.dedup();
+
+    assert_eq!(tasks.len(), 4);
+    assert_eq!(ids.len(), 4);
+
+    for fields in tasks.values() {
+        assert_eq!(fields["task.kind"], "\"blocking\"");
+        assert!(fields.contains_key("task.worker"));
+        assert!(fields.contains_key("task.poll_duration"));
+    }
+}
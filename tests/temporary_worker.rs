@@ -0,0 +1,29 @@
+use mtserver::ThreadPool;
+use std::{sync::mpsc, thread};
+
+#[test]
+fn a_temporary_worker_lasts_as_long_as_its_guard() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    let guard = pool.temporary_worker().unwrap();
+
+    assert_eq!((&pool).into_iter().count(), 2);
+    assert_eq!(pool.size(), 1);
+
+    // The only permanent Worker is blocked, so the temporary one runs this.
+    assert_eq!(pool.spawn(|| 3).unwrap().join().unwrap(), 3);
+
+    drop(guard);
+
+    assert_eq!((&pool).into_iter().count(), 1);
+
+    drop(tx);
+    pool.join();
+}
@@ -0,0 +1,97 @@
+use mtserver::{Pool, TestPool, ThreadPool};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+/// Code under test, which only needs something to submit jobs to.
+fn count_to(pool: &impl Pool, n: usize, total: &Arc<AtomicUsize>) {
+    for _ in 0..n {
+        let total = Arc::clone(total);
+
+        pool.execute(move || {
+            total.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn jobs_wait_until_run_all() {
+    let pool = TestPool::new();
+    let total = Arc::new(AtomicUsize::new(0));
+
+    count_to(&pool, 3, &total);
+
+    assert_eq!(pool.job_count(), 3);
+    assert_eq!(total.load(Ordering::Relaxed), 0);
+
+    pool.run_all();
+
+    assert_eq!(pool.job_count(), 0);
+    assert_eq!(total.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn run_all_runs_jobs_in_order_including_the_ones_they_submit() {
+    let pool = Arc::new(TestPool::new());
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    for i in 0..2 {
+        let order = Arc::clone(&order);
+        let inner_pool = Arc::clone(&pool);
+
+        pool.execute(move || {
+            order.lock().unwrap().push(i);
+
+            let order = Arc::clone(&order);
+            inner_pool
+                .execute(move || order.lock().unwrap().push(i + 10))
+                .unwrap();
+        })
+        .unwrap();
+    }
+
+    pool.run_all();
+
+    assert_eq!(*order.lock().unwrap(), [0, 1, 10, 11]);
+}
+
+#[test]
+fn a_thread_pool_runs_the_same_code() {
+    let pool = ThreadPool::new(2);
+    let total = Arc::new(AtomicUsize::new(0));
+
+    count_to(&pool, 3, &total);
+    pool.join();
+
+    assert_eq!(total.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn a_boxed_dyn_pool_can_be_injected() {
+    let test_pool = Arc::new(TestPool::new());
+    let pool: Box<dyn Pool> = Box::new(Arc::clone(&test_pool));
+    let total = Arc::new(AtomicUsize::new(0));
+
+    count_to(&pool, 2, &total);
+    count_to(&&*pool, 1, &total);
+
+    assert_eq!(test_pool.job_count(), 3);
+
+    test_pool.run_all();
+
+    assert_eq!(total.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn an_arc_of_a_thread_pool_submits_without_dereferencing() {
+    let pool = ThreadPool::new(2).into_arc();
+    let shared = Arc::clone(&pool);
+    let total = Arc::new(AtomicUsize::new(0));
+
+    count_to(&shared, 2, &total);
+    pool.join();
+
+    assert_eq!(total.load(Ordering::Relaxed), 2);
+}
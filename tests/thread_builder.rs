@@ -0,0 +1,35 @@
+use mtserver::{ThreadPoolBuilder, ThreadPoolError};
+use std::thread;
+
+#[test]
+fn workers_are_spawned_with_the_custom_builder() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .thread_name_prefix("ignored")
+        .thread_builder(|id| {
+            thread::Builder::new()
+                .name(format!("custom-{id}"))
+                .stack_size(4 << 20)
+        })
+        .build()
+        .unwrap();
+
+    let name = pool
+        .spawn(|| thread::current().name().map(str::to_string))
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+
+    assert!(name.starts_with("custom-"), "{name}");
+}
+
+#[test]
+fn spawn_errors_from_the_custom_builder_are_returned() {
+    let result = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .thread_builder(|_| thread::Builder::new().stack_size(usize::MAX))
+        .build();
+
+    assert!(matches!(result, Err(ThreadPoolError::Spawn(_))));
+}
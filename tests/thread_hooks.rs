@@ -0,0 +1,48 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+#[test]
+fn start_and_stop_hooks_run_once_per_worker() {
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let on_start = Arc::clone(&started);
+    let on_stop = Arc::clone(&stopped);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .on_thread_start(move |id| on_start.lock().unwrap().push(id))
+        .on_thread_stop(move |id| on_stop.lock().unwrap().push(id))
+        .build()
+        .unwrap();
+
+    pool.execute(|| {}).unwrap();
+
+    let ids: HashSet<_> = (&pool).into_iter().collect();
+
+    pool.shutdown().unwrap();
+
+    let started: HashSet<_> = started.lock().unwrap().iter().copied().collect();
+    let stopped = stopped.lock().unwrap();
+
+    assert_eq!(started, ids);
+    assert_eq!(stopped.len(), 3);
+    assert_eq!(stopped.iter().copied().collect::<HashSet<_>>(), ids);
+}
+
+#[test]
+fn the_start_hook_runs_for_workers_added_by_resize() {
+    let started = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&started);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .on_thread_start(move |_| *counter.lock().unwrap() += 1)
+        .build()
+        .unwrap();
+
+    pool.resize(3).unwrap();
+    pool.broadcast(|| {}).unwrap();
+
+    assert_eq!(*started.lock().unwrap(), 3);
+}
@@ -0,0 +1,35 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+thread_local! {
+    static CONNECTION: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+#[test]
+fn each_worker_is_initialized_once_before_its_jobs() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&opened);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .thread_local_init(move || {
+            CONNECTION.set(Some(counter.fetch_add(1, Ordering::SeqCst)));
+        })
+        .build()
+        .unwrap();
+
+    let connections = pool.map(0..30, |_| CONNECTION.get()).unwrap();
+
+    assert!(connections.iter().all(Option::is_some));
+
+    // Runs on every Worker, including any that took none of the jobs.
+    pool.broadcast(|| assert!(CONNECTION.get().is_some()))
+        .unwrap();
+
+    assert_eq!(opened.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,53 @@
+use mtserver::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Barrier},
+    thread,
+};
+
+/// The names of the threads that ran each of `n` jobs, which all wait for
+/// each other so that every Worker runs exactly one.
+fn worker_names(pool: &ThreadPool, n: usize) -> HashSet<String> {
+    let barrier = Arc::new(Barrier::new(n));
+    let handles: Vec<_> = (0..n)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+
+            pool.spawn(move || {
+                barrier.wait();
+                thread::current().name().unwrap().to_string()
+            })
+            .unwrap()
+        })
+        .collect();
+
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+#[test]
+fn workers_are_named_after_the_default_prefix_and_their_id() {
+    let pool = ThreadPool::new(2);
+    let names = worker_names(&pool, 2);
+    let expected: HashSet<_> = (&pool)
+        .into_iter()
+        .map(|id| format!("worker-{id}"))
+        .collect();
+
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn workers_are_named_after_a_custom_prefix() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .thread_name_prefix("http")
+        .build()
+        .unwrap();
+    let names = worker_names(&pool, 3);
+
+    assert_eq!(names.len(), 3);
+    assert!(
+        names.iter().all(|name| name.starts_with("http-")),
+        "{names:?}"
+    );
+}
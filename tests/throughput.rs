@@ -0,0 +1,56 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// Number of jobs submitted by each producer, as in the throughput benchmarks.
+const JOBS: usize = 10_000;
+
+/// Number of threads submitting jobs at the same time under contention.
+const PRODUCERS: usize = 8;
+
+/// Submit `JOBS` trivial jobs from each of `producers` threads and return how
+/// many of them ran.
+fn run_jobs(pool: &ThreadPool, producers: usize) -> usize {
+    let done = Arc::new(AtomicUsize::new(0));
+
+    thread::scope(|s| {
+        for _ in 0..producers {
+            s.spawn(|| {
+                for _ in 0..JOBS {
+                    let done = Arc::clone(&done);
+
+                    pool.execute(move || {
+                        done.fetch_add(1, Ordering::Relaxed);
+                    })
+                    .unwrap();
+                }
+            });
+        }
+    });
+
+    pool.join();
+
+    done.load(Ordering::Relaxed)
+}
+
+#[test]
+fn every_job_runs_whatever_the_pool_size() {
+    for threads in [1, 4, 8, 16] {
+        let pool = ThreadPool::new(threads);
+
+        assert_eq!(run_jobs(&pool, 1), JOBS, "{threads} threads");
+    }
+}
+
+#[test]
+fn every_job_from_contending_producers_runs() {
+    let pool = ThreadPool::new(PRODUCERS);
+
+    assert_eq!(run_jobs(&pool, PRODUCERS), JOBS * PRODUCERS);
+    assert_eq!(pool.queued_count(), 0);
+}
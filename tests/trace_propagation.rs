@@ -0,0 +1,84 @@
+use mtserver::{ThreadPoolBuilder, TraceContextPropagator};
+use std::{cell::RefCell, sync::mpsc};
+
+thread_local! {
+    static TRACEPARENT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn current() -> Option<String> {
+    TRACEPARENT.with_borrow(Clone::clone)
+}
+
+/// Puts the Worker's trace context back the way it was when dropped.
+struct Restore(Option<String>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        TRACEPARENT.set(self.0.take());
+    }
+}
+
+struct Propagator;
+
+impl TraceContextPropagator for Propagator {
+    type Context = Option<String>;
+    type Guard = Restore;
+
+    fn extract(&self) -> Option<String> {
+        current()
+    }
+
+    fn inject(&self, context: Option<String>) -> Restore {
+        Restore(TRACEPARENT.replace(context))
+    }
+}
+
+#[test]
+fn jobs_run_in_the_submitters_trace_context() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .with_trace_propagator(Propagator)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    for id in ["00-aaaa-01", "00-bbbb-01"] {
+        let tx = tx.clone();
+
+        TRACEPARENT.set(Some(id.to_string()));
+        pool.execute(move || tx.send(current()).unwrap()).unwrap();
+    }
+
+    TRACEPARENT.set(None);
+    pool.execute(move || tx.send(current()).unwrap()).unwrap();
+
+    let seen: Vec<_> = rx.iter().collect();
+
+    assert_eq!(
+        seen,
+        [
+            Some("00-aaaa-01".to_string()),
+            Some("00-bbbb-01".to_string()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn the_workers_own_context_is_restored_after_each_job() {
+    let (tx, rx) = mpsc::channel();
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .thread_local_init(|| TRACEPARENT.set(Some("worker".to_string())))
+        .on_job_complete(move |_| tx.send(current()).unwrap())
+        .with_trace_propagator(Propagator)
+        .build()
+        .unwrap();
+
+    TRACEPARENT.set(Some("00-cccc-01".to_string()));
+
+    let seen = pool.spawn(current).unwrap().join().unwrap();
+
+    assert_eq!(seen, Some("00-cccc-01".to_string()));
+    assert_eq!(rx.recv().unwrap(), Some("worker".to_string()));
+}
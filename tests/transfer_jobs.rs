@@ -0,0 +1,90 @@
+use mtserver::{ExecuteError, ThreadPool, ThreadPoolBuilder, TransferError};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+fn paused_pool_with_jobs(count: usize, ran: &Arc<AtomicUsize>) -> ThreadPool {
+    let pool = ThreadPool::new(2);
+
+    pool.pause();
+
+    for _ in 0..count {
+        let ran = Arc::clone(ran);
+
+        pool.execute(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    pool
+}
+
+#[test]
+fn requires_a_paused_pool() {
+    let pool = ThreadPool::new(1);
+    let dest = ThreadPool::new(1);
+
+    assert_eq!(pool.transfer_jobs_to(&dest), Err(TransferError::NotPaused));
+}
+
+#[test]
+fn moves_queued_jobs_to_the_other_pool() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    let pool = paused_pool_with_jobs(10, &ran);
+    let dest = ThreadPool::new(3);
+
+    assert_eq!(pool.transfer_jobs_to(&dest), Ok(10));
+    assert_eq!(pool.queued_count(), 0);
+
+    dest.join();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn bounded_destination_blocks_for_space() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    let pool = paused_pool_with_jobs(20, &ran);
+    let dest = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .channel_capacity(1)
+        .build()
+        .unwrap();
+
+    assert_eq!(pool.transfer_jobs_to(&dest), Ok(20));
+
+    dest.join();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 20);
+}
+
+#[test]
+fn jobs_go_back_if_the_destination_is_shut_down() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    let pool = paused_pool_with_jobs(5, &ran);
+    let dest = ThreadPool::new(1);
+
+    dest.initiate_shutdown();
+
+    assert_eq!(
+        pool.transfer_jobs_to(&dest),
+        Err(TransferError::Execute(ExecuteError::Shutdown))
+    );
+    assert_eq!(pool.queued_count(), 5);
+
+    pool.resume();
+    pool.join();
+
+    assert_eq!(ran.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn transferring_to_itself_does_nothing() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    let pool = paused_pool_with_jobs(3, &ran);
+
+    assert_eq!(pool.transfer_jobs_to(&pool.clone()), Ok(0));
+    assert_eq!(pool.queued_count(), 3);
+}
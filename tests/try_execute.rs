@@ -0,0 +1,36 @@
+use mtserver::{ThreadPool, TryExecuteError};
+use std::{sync::mpsc, thread};
+
+#[test]
+fn try_execute_on_a_full_queue_returns_queue_full_at_once() {
+    let pool = ThreadPool::builder()
+        .num_threads(1)
+        .channel_capacity(1)
+        .build()
+        .unwrap();
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    assert_eq!(pool.try_execute(|| {}), Ok(()));
+    assert_eq!(pool.try_execute(|| {}), Err(TryExecuteError::QueueFull));
+
+    drop(tx);
+    pool.join();
+
+    assert_eq!(pool.try_execute(|| {}), Ok(()));
+}
+
+#[test]
+fn try_execute_after_shutdown_returns_shutdown() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert_eq!(handle.try_execute(|| {}), Err(TryExecuteError::Shutdown));
+}
@@ -0,0 +1,26 @@
+use mtserver::{ThreadPool, ThreadPoolError};
+
+#[test]
+fn try_new_with_no_threads_is_an_error() {
+    let result = ThreadPool::try_new(0);
+
+    assert!(matches!(result, Err(ThreadPoolError::InvalidSize)));
+    assert_eq!(
+        result.err().unwrap().to_string(),
+        "thread pool size must be greater than 0"
+    );
+}
+
+#[test]
+fn try_new_builds_a_working_pool() {
+    let pool = ThreadPool::try_new(3).unwrap();
+
+    assert_eq!(pool.size(), 3);
+    assert_eq!(pool.spawn(|| 7).unwrap().join().unwrap(), 7);
+}
+
+#[test]
+#[should_panic(expected = "Unable to create the ThreadPool")]
+fn new_with_no_threads_panics() {
+    ThreadPool::new(0);
+}
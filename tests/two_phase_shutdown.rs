@@ -0,0 +1,51 @@
+use mtserver::{ExecuteError, ThreadPool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+#[test]
+fn new_jobs_are_refused_while_queued_ones_finish() {
+    let pool = ThreadPool::new(1);
+    let done = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel::<()>();
+
+    pool.execute(move || while rx.recv().is_ok() {}).unwrap();
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    for _ in 0..3 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    pool.initiate_shutdown();
+
+    assert!(matches!(pool.execute(|| {}), Err(ExecuteError::Shutdown)));
+    assert_eq!(done.load(Ordering::SeqCst), 0);
+
+    drop(tx);
+
+    assert!(pool.await_shutdown().is_ok());
+    assert_eq!(done.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn await_shutdown_shuts_the_pool_down_by_itself() {
+    let pool = ThreadPool::new(2);
+    let clone = pool.clone();
+
+    pool.execute(|| {}).unwrap();
+
+    assert!(pool.await_shutdown().is_ok());
+    assert!(matches!(clone.execute(|| {}), Err(ExecuteError::Shutdown)));
+}
@@ -0,0 +1,66 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn wait_for_idle_waits_for_jobs_submitted_by_other_threads() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            for _ in 0..50 {
+                let done = Arc::clone(&done);
+
+                pool.execute(move || {
+                    thread::sleep(Duration::from_millis(1));
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+
+        pool.wait_for_idle();
+
+        assert_eq!(done.load(Ordering::SeqCst), 50);
+    });
+
+    assert_eq!(pool.queued_count(), 0);
+    assert_eq!(pool.active_count(), 0);
+}
+
+#[test]
+fn wait_for_idle_waits_for_jobs_submitted_by_jobs() {
+    let pool = Arc::new(ThreadPool::new(2));
+    let done = Arc::new(AtomicUsize::new(0));
+    let inner = Arc::clone(&pool);
+    let counter = Arc::clone(&done);
+
+    pool.execute(move || {
+        thread::sleep(Duration::from_millis(10));
+
+        inner
+            .execute(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+    })
+    .unwrap();
+
+    pool.wait_for_idle();
+
+    assert_eq!(done.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn wait_for_idle_on_an_idle_pool_returns_at_once() {
+    ThreadPool::new(1).wait_for_idle();
+}
@@ -0,0 +1,52 @@
+use mtserver::ThreadPool;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn warm_up_runs_a_job_on_every_worker() {
+    let pool = ThreadPool::new(4);
+
+    pool.warm_up();
+
+    // The Workers count a job as completed just after it finishes.
+    while pool.snapshot().total_completed < 4 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(pool.snapshot().total_completed, 4);
+}
+
+#[test]
+fn jobs_start_quickly_after_warm_up() {
+    let pool = ThreadPool::new(4);
+    let waits = Arc::new(Mutex::new(Vec::new()));
+
+    pool.warm_up();
+
+    for _ in 0..4 {
+        let submitted = Instant::now();
+        let waits = Arc::clone(&waits);
+
+        pool.execute(move || waits.lock().unwrap().push(submitted.elapsed()))
+            .unwrap();
+    }
+
+    pool.join();
+
+    let waits = waits.lock().unwrap();
+
+    assert_eq!(waits.len(), 4);
+    assert!(waits.iter().all(|wait| *wait < Duration::from_millis(500)));
+}
+
+#[test]
+fn warm_up_after_shutdown_does_nothing() {
+    let pool = ThreadPool::new(2);
+    let clone = pool.clone();
+
+    pool.shutdown().unwrap();
+    clone.warm_up();
+}
@@ -0,0 +1,54 @@
+use mtserver::ThreadPoolBuilder;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const JOBS: usize = 200;
+
+#[test]
+fn an_idle_worker_steals_the_batch_of_a_busy_one() {
+    // Huge batches, so the Worker that takes the blocking job is likely to
+    // take most of the other jobs with it.
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .worker_batch_size(JOBS)
+        .build()
+        .unwrap();
+    let done = Arc::new(AtomicUsize::new(0));
+    let seen = Arc::clone(&done);
+
+    pool.pause();
+
+    // Blocks its Worker until every other job has run, which only happens if
+    // the other Worker steals the ones queued behind it.
+    pool.execute(move || {
+        let deadline = Instant::now() + Duration::from_secs(10);
+
+        while seen.load(Ordering::SeqCst) < JOBS && Instant::now() < deadline {
+            thread::yield_now();
+        }
+    })
+    .unwrap();
+
+    for _ in 0..JOBS {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+    }
+
+    let started = Instant::now();
+
+    pool.resume();
+    pool.join();
+
+    assert_eq!(done.load(Ordering::SeqCst), JOBS);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
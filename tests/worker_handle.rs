@@ -0,0 +1,54 @@
+use mtserver::ThreadPool;
+use std::{sync::mpsc, thread};
+
+#[test]
+fn run_one_runs_a_queued_job_on_the_calling_thread() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.receiver_handle().unwrap();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let (started_tx, started_rx) = mpsc::channel();
+    let (tx, rx) = mpsc::channel();
+
+    // Keep the only Worker busy, so the next job stays in the queue.
+    pool.execute(move || {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+    })
+    .unwrap();
+    started_rx.recv().unwrap();
+
+    pool.execute(move || tx.send(thread::current().id()).unwrap())
+        .unwrap();
+
+    assert_eq!(handle.run_one(), Some(()));
+    assert_eq!(rx.recv().unwrap(), thread::current().id());
+    assert_eq!(handle.run_one(), None);
+
+    release_tx.send(()).unwrap();
+    pool.join();
+}
+
+#[test]
+fn run_one_takes_nothing_from_a_paused_pool() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.receiver_handle().unwrap();
+
+    pool.pause();
+    pool.execute(|| ()).unwrap();
+
+    assert_eq!(handle.run_one(), None);
+    assert_eq!(pool.queued_count(), 1);
+
+    pool.resume();
+    pool.join();
+}
+
+#[test]
+fn a_shutdown_pool_has_no_handle() {
+    let pool = ThreadPool::new(1);
+    let other = pool.clone();
+
+    pool.shutdown().unwrap();
+
+    assert!(other.receiver_handle().is_none());
+}
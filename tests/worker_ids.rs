@@ -0,0 +1,25 @@
+use mtserver::ThreadPool;
+use std::collections::HashSet;
+
+#[test]
+fn iterating_a_pool_yields_its_live_workers() {
+    let pool = ThreadPool::new(3);
+    let ids: HashSet<usize> = (&pool).into_iter().collect();
+
+    assert_eq!(ids.len(), 3);
+
+    for id in &pool {
+        assert!(pool.worker_is_alive(id));
+    }
+
+    assert!(!pool.worker_is_alive(1000));
+}
+
+#[test]
+fn a_shrunk_pool_yields_fewer_workers() {
+    let pool = ThreadPool::new(4);
+
+    pool.resize(2).unwrap();
+
+    assert_eq!((&pool).into_iter().count(), 2);
+}